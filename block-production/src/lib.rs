@@ -1,4 +1,8 @@
-use nimiq_account::Inherent;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use beserial::Serialize;
+use nimiq_account::{Account, AccountError, AccountTransactionInteraction, Accounts, Inherent};
 use nimiq_block::{
     ForkProof, MacroBlock, MacroBody, MacroHeader, MicroBlock, MicroBody, MicroHeader,
     MicroJustification, ViewChangeProof, ViewChanges,
@@ -7,8 +11,58 @@ use nimiq_blockchain::{AbstractBlockchain, Blockchain, ExtendedTransaction};
 use nimiq_bls::KeyPair as BlsKeyPair;
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::KeyPair as SchnorrKeyPair;
+use nimiq_mempool::mempool::Mempool;
 use nimiq_primitives::policy;
-use nimiq_transaction::Transaction;
+use nimiq_transaction::{Transaction, TransactionFlags};
+
+/// The reason why a transaction was left out of a produced block.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RejectReason {
+    /// The block height is outside of the validity window of the transaction.
+    InvalidValidityWindow,
+    /// The transaction was already included in the chain or was supplied more than once.
+    Duplicate,
+    /// The transaction can't be applied to the accounts, e.g. because of insufficient funds.
+    Account(AccountError),
+    /// The fee per byte of the transaction is below the configured minimum.
+    FeeTooLow,
+    /// The transaction doesn't fit into the block anymore.
+    BlockFull,
+    /// The configured time budget for selecting the transactions ran out.
+    TimeBudgetExceeded,
+}
+
+/// A record of which of the supplied transactions made it into a produced block, and why the
+/// others didn't.
+#[derive(Debug, Default)]
+pub struct ProductionReport {
+    pub included: Vec<Transaction>,
+    pub rejected: Vec<(Transaction, RejectReason)>,
+}
+
+/// The tunables of the block producer.
+#[derive(Clone, Debug)]
+pub struct ProducerConfig {
+    /// The maximum size of a micro block body in bytes. Transactions that would make the body
+    /// larger are left out.
+    pub max_body_size: usize,
+    /// The minimum fee per byte a transaction needs to pay to be included.
+    pub min_fee_per_byte: f64,
+    /// The maximum time spent on checking transactions against the accounts when producing a
+    /// block with `next_micro_block_with_report`. Transactions that weren't checked in time are
+    /// left out.
+    pub time_budget: Option<Duration>,
+}
+
+impl Default for ProducerConfig {
+    fn default() -> Self {
+        ProducerConfig {
+            max_body_size: policy::MAX_SIZE_MICRO_BODY,
+            min_fee_per_byte: 0.0,
+            time_budget: None,
+        }
+    }
+}
 
 /// Struct that contains all necessary information to actually produce blocks.
 /// It has the validator keys for this validator.
@@ -16,14 +70,25 @@ use nimiq_transaction::Transaction;
 pub struct BlockProducer {
     pub signing_key: SchnorrKeyPair,
     pub voting_key: BlsKeyPair,
+    pub config: ProducerConfig,
 }
 
 impl BlockProducer {
     /// Creates a new BlockProducer struct given a blockchain and a validator key.
     pub fn new(signing_key: SchnorrKeyPair, voting_key: BlsKeyPair) -> Self {
+        Self::new_with_config(signing_key, voting_key, ProducerConfig::default())
+    }
+
+    /// Creates a new BlockProducer struct with the given validator keys and tunables.
+    pub fn new_with_config(
+        signing_key: SchnorrKeyPair,
+        voting_key: BlsKeyPair,
+        config: ProducerConfig,
+    ) -> Self {
         BlockProducer {
             signing_key,
             voting_key,
+            config,
         }
     }
 
@@ -40,8 +105,9 @@ impl BlockProducer {
         // height.
         view_change_proof: Option<ViewChangeProof>,
         // Proofs of any forks created by malicious validators. A fork proof may be submitted during
-        // the batch when it happened or in the next one, but not after that.
-        fork_proofs: Vec<ForkProof>,
+        // the batch when it happened or in the next one, but not after that. At most
+        // `policy::MAX_FORK_PROOFS_PER_BLOCK` proofs are included, the rest is dropped.
+        mut fork_proofs: Vec<ForkProof>,
         // The transactions to be included in the block body.
         mut transactions: Vec<Transaction>,
         // Extra data for this block.
@@ -62,8 +128,13 @@ impl BlockProducer {
         let prev_seed = blockchain.head().seed().clone();
         let seed = prev_seed.sign_next(&self.signing_key);
 
-        // Sort the transactions.
+        // Drop the fork proofs that exceed the per block limit.
+        fork_proofs.truncate(policy::MAX_FORK_PROOFS_PER_BLOCK);
+
+        // Sort the transactions, drop duplicates and the ones that the config doesn't allow.
         transactions.sort_unstable();
+        transactions.dedup();
+        let (transactions, _) = self.apply_config(&fork_proofs, transactions);
 
         // Creates a new ViewChanges struct.
         let view_changes = ViewChanges::new(
@@ -140,6 +211,258 @@ impl BlockProducer {
         }
     }
 
+    /// Creates the next micro block like `next_micro_block`, but first drops every transaction that
+    /// can't be included in the block, either because of the config or because it can't be applied
+    /// to the accounts. Returns the block together with a report of the included and rejected
+    /// transactions.
+    #[allow(clippy::too_many_arguments)]
+    pub fn next_micro_block_with_report(
+        &self,
+        blockchain: &Blockchain,
+        timestamp: u64,
+        view_number: u32,
+        view_change_proof: Option<ViewChangeProof>,
+        fork_proofs: Vec<ForkProof>,
+        transactions: Vec<Transaction>,
+        extra_data: Vec<u8>,
+    ) -> (MicroBlock, ProductionReport) {
+        let report = self.select_transactions(
+            blockchain,
+            timestamp,
+            view_number,
+            &fork_proofs,
+            transactions,
+        );
+
+        let block = self.next_micro_block(
+            blockchain,
+            timestamp,
+            view_number,
+            view_change_proof,
+            fork_proofs,
+            report.included.clone(),
+            extra_data,
+        );
+
+        (block, report)
+    }
+
+    /// Creates the next micro block with the best transactions from the mempool. The transactions
+    /// with the highest fee per byte, as many as fit into the block body, are checked like in
+    /// `next_micro_block_with_report`. The included transactions and the ones that can't be
+    /// applied to the accounts are removed from the mempool, all others stay there for later
+    /// blocks.
+    #[allow(clippy::too_many_arguments)]
+    pub fn next_micro_block_from_mempool(
+        &self,
+        blockchain: &Blockchain,
+        mempool: &Mempool,
+        timestamp: u64,
+        view_number: u32,
+        view_change_proof: Option<ViewChangeProof>,
+        fork_proofs: Vec<ForkProof>,
+        extra_data: Vec<u8>,
+    ) -> MicroBlock {
+        let empty_body_size = MicroBody {
+            fork_proofs: fork_proofs.clone(),
+            transactions: vec![],
+        }
+        .serialized_size();
+        let transactions = mempool
+            .peek_transactions_for_block(self.config.max_body_size.saturating_sub(empty_body_size));
+
+        let (block, report) = self.next_micro_block_with_report(
+            blockchain,
+            timestamp,
+            view_number,
+            view_change_proof,
+            fork_proofs,
+            transactions,
+            extra_data,
+        );
+
+        let mut removed: Vec<Blake2bHash> = report.included.iter().map(|tx| tx.hash()).collect();
+        for (tx, reason) in &report.rejected {
+            if let RejectReason::Account(_) = reason {
+                let tx_hash = tx.hash::<Blake2bHash>();
+                log::debug!(%tx_hash, ?reason, "Dropped transaction from the mempool");
+                removed.push(tx_hash);
+            }
+        }
+        mempool.remove_transactions(&removed);
+
+        block
+    }
+
+    /// Splits the given transactions into the ones that can be included in the next micro block
+    /// and the ones that can't. The transactions are applied to a scratch copy of the accounts in
+    /// the same order as during block application: first all senders, then all recipients and
+    /// finally all contract creations. The limits of the config are checked before the sender of a
+    /// transaction is applied, so that a transaction that is left out doesn't affect the others.
+    fn select_transactions(
+        &self,
+        blockchain: &Blockchain,
+        timestamp: u64,
+        view_number: u32,
+        fork_proofs: &[ForkProof],
+        mut transactions: Vec<Transaction>,
+    ) -> ProductionReport {
+        let block_number = blockchain.block_number() + 1;
+        let timestamp = u64::max(timestamp, blockchain.head().timestamp());
+
+        let view_changes = ViewChanges::new(
+            block_number,
+            blockchain.next_view_number(),
+            view_number,
+            blockchain.head().seed().entropy(),
+        );
+        let inherents = blockchain.create_slash_inherents(fork_proofs, &view_changes, None);
+
+        let accounts = &blockchain.state().accounts;
+        let mut txn = blockchain.write_transaction();
+        let mut report = ProductionReport::default();
+
+        accounts
+            .commit_batch(&mut txn, &[], &inherents, block_number, timestamp)
+            .expect("Failed to apply inherents during block production");
+
+        transactions.sort_unstable();
+
+        let mut body_size = MicroBody {
+            fork_proofs: fork_proofs.to_vec(),
+            transactions: vec![],
+        }
+        .serialized_size();
+
+        // Apply the senders.
+        let start = Instant::now();
+        let mut seen = HashSet::new();
+        let mut candidates = vec![];
+        for tx in transactions {
+            if let Some(budget) = self.config.time_budget {
+                if start.elapsed() > budget {
+                    report.rejected.push((tx, RejectReason::TimeBudgetExceeded));
+                    continue;
+                }
+            }
+
+            if !tx.is_valid_at(block_number) {
+                report
+                    .rejected
+                    .push((tx, RejectReason::InvalidValidityWindow));
+                continue;
+            }
+
+            let hash: Blake2bHash = tx.hash();
+            if !seen.insert(hash.clone())
+                || blockchain.contains_tx_in_validity_window(&hash, Some(&txn))
+            {
+                report.rejected.push((tx, RejectReason::Duplicate));
+                continue;
+            }
+
+            if tx.fee_per_byte() < self.config.min_fee_per_byte {
+                report.rejected.push((tx, RejectReason::FeeTooLow));
+                continue;
+            }
+
+            let tx_size = tx.serialized_size();
+            if body_size + tx_size > self.config.max_body_size {
+                report.rejected.push((tx, RejectReason::BlockFull));
+                continue;
+            }
+
+            match Account::commit_outgoing_transaction(
+                &accounts.tree,
+                &mut txn,
+                &tx,
+                block_number,
+                timestamp,
+            ) {
+                Ok(receipt) => {
+                    body_size += tx_size;
+                    candidates.push((tx, receipt));
+                }
+                Err(e) => report.rejected.push((tx, RejectReason::Account(e))),
+            }
+        }
+
+        // Apply the recipients and the contract creations in the same order as `Accounts::commit`.
+        // If that fails, the sender side of the transaction is reverted again.
+        for index in Accounts::recipient_order(candidates.iter().map(|(tx, _)| tx)) {
+            let (tx, receipt) = &candidates[index];
+
+            let result = if tx.flags.contains(TransactionFlags::CONTRACT_CREATION) {
+                Account::create(&accounts.tree, &mut txn, tx, block_number, timestamp)
+            } else {
+                Account::commit_incoming_transaction(
+                    &accounts.tree,
+                    &mut txn,
+                    tx,
+                    block_number,
+                    timestamp,
+                )
+                .map(|_| ())
+            };
+
+            match result {
+                Ok(()) => report.included.push(tx.clone()),
+                Err(e) => {
+                    Account::revert_outgoing_transaction(
+                        &accounts.tree,
+                        &mut txn,
+                        tx,
+                        block_number,
+                        timestamp,
+                        receipt.as_ref(),
+                    )
+                    .expect("Failed to revert sender during block production");
+                    report.rejected.push((tx.clone(), RejectReason::Account(e)));
+                }
+            }
+        }
+
+        txn.abort();
+
+        report
+    }
+
+    /// Splits the given (sorted) transactions into the ones that the config allows in a micro block
+    /// with the given fork proofs and the ones it doesn't. Transactions are added in order as long
+    /// as they fit into the maximum body size.
+    fn apply_config(
+        &self,
+        fork_proofs: &[ForkProof],
+        transactions: Vec<Transaction>,
+    ) -> (Vec<Transaction>, Vec<(Transaction, RejectReason)>) {
+        let mut included = vec![];
+        let mut rejected = vec![];
+
+        let mut body_size = MicroBody {
+            fork_proofs: fork_proofs.to_vec(),
+            transactions: vec![],
+        }
+        .serialized_size();
+
+        for tx in transactions {
+            if tx.fee_per_byte() < self.config.min_fee_per_byte {
+                rejected.push((tx, RejectReason::FeeTooLow));
+                continue;
+            }
+
+            let tx_size = tx.serialized_size();
+            if body_size + tx_size > self.config.max_body_size {
+                rejected.push((tx, RejectReason::BlockFull));
+                continue;
+            }
+
+            body_size += tx_size;
+            included.push(tx);
+        }
+
+        (included, rejected)
+    }
+
     /// Creates a proposal for the next macro block (checkpoint or election). It is just a proposal,
     /// NOT a complete block. It still needs to go through the Tendermint protocol in order to be
     /// finalized.
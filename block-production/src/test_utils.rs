@@ -4,8 +4,8 @@ use parking_lot::RwLock;
 
 use beserial::Deserialize;
 use nimiq_block::{
-    Block, MacroBlock, MacroBody, MultiSignature, SignedViewChange, TendermintIdentifier,
-    TendermintProof, TendermintProposal, TendermintStep, TendermintVote, ViewChange,
+    view_change_message, Block, MacroBlock, MacroBody, MultiSignature, SignedViewChange,
+    TendermintIdentifier, TendermintProof, TendermintProposal, TendermintStep, TendermintVote,
     ViewChangeProof,
 };
 use nimiq_blockchain::{AbstractBlockchain, Blockchain, PushError, PushResult};
@@ -176,11 +176,11 @@ impl TemporaryBlockProducer {
 
         let view_change = {
             let blockchain = self.blockchain.read();
-            ViewChange {
-                block_number: blockchain.block_number() + 1,
-                new_view_number: view_number,
-                vrf_entropy: blockchain.head().seed().entropy(),
-            }
+            view_change_message(
+                blockchain.block_number() + 1,
+                view_number,
+                blockchain.head().seed(),
+            )
         };
 
         // create signed view change
@@ -3,20 +3,23 @@ use std::convert::TryInto;
 use std::sync::Arc;
 use tempfile::tempdir;
 
-use beserial::Deserialize;
-use nimiq_block::{Block, BlockError, ForkProof};
-use nimiq_block_production::BlockProducer;
+use beserial::{Deserialize, Serialize};
+use nimiq_account::{AccountError, InherentType, StakingContract};
+use nimiq_block::{Block, BlockError, ForkProof, ForkProofError, MicroBody};
+use nimiq_block_production::{BlockProducer, ProducerConfig, RejectReason};
+use nimiq_blockchain::reward::block_reward_for_batch_with_genesis;
 use nimiq_blockchain::{AbstractBlockchain, Blockchain, PushError, PushResult};
-use nimiq_database::{mdbx::MdbxEnvironment, volatile::VolatileEnvironment};
-use nimiq_genesis::NetworkId;
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_genesis::{NetworkId, NetworkInfo};
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::{Address, KeyPair as SchnorrKeyPair, PrivateKey as SchnorrPrivateKey};
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::policy;
 use nimiq_test_log::test;
+use nimiq_test_utils::benchmark::BlockchainBenchmark;
 use nimiq_test_utils::blockchain::{
-    fill_micro_blocks, fill_micro_blocks_with_txns, sign_macro_block, sign_view_change,
-    signing_key, voting_key,
+    fill_micro_blocks, fill_micro_blocks_with_txns, produce_macro_blocks,
+    produce_macro_blocks_with_txns, sign_macro_block, sign_view_change, signing_key, voting_key,
 };
 use nimiq_transaction_builder::TransactionBuilder;
 use nimiq_utils::time::OffsetTime;
@@ -29,8 +32,6 @@ pub const ACCOUNT_SECRET_KEY: &str =
 
 const STAKER_ADDRESS: &str = "NQ20TSB0DFSMUH9C15GQGAGJTTE4D3MA859E";
 
-const VOLATILE_ENV: bool = true;
-
 #[test]
 fn it_can_produce_micro_blocks() {
     let time = Arc::new(OffsetTime::new());
@@ -63,15 +64,29 @@ fn it_can_produce_micro_blocks() {
         header2.timestamp += 1;
         let hash2 = header2.hash::<Blake2bHash>();
         let justification2 = signing_key().sign(hash2.as_slice());
-        ForkProof {
+        ForkProof::new(
             header1,
-            header2,
             justification1,
+            header2,
             justification2,
             prev_vrf_seed,
-        }
+        )
     };
 
+    // The headers in the proof must be in canonical order.
+    let swapped_proof = ForkProof {
+        header1: fork_proof.header2.clone(),
+        header2: fork_proof.header1.clone(),
+        justification1: fork_proof.justification2.clone(),
+        justification2: fork_proof.justification1.clone(),
+        prev_vrf_seed: fork_proof.prev_vrf_seed.clone(),
+    };
+    assert_eq!(
+        swapped_proof.verify(&signing_key().public),
+        Err(ForkProofError::NonCanonicalOrder)
+    );
+    assert_eq!(fork_proof.verify(&signing_key().public), Ok(()));
+
     let bc = blockchain.upgradable_read();
     // #2.0: Empty micro block with fork proof
     let block = producer.next_micro_block(
@@ -130,6 +145,38 @@ fn it_can_produce_micro_blocks() {
     assert_eq!(blockchain.read().next_view_number(), 1);
 }
 
+#[test]
+fn it_can_produce_blocks_with_a_fixed_clock() {
+    let genesis_timestamp = NetworkInfo::from_network_id(NetworkId::UnitAlbatross)
+        .genesis_block::<Block>()
+        .timestamp();
+    let time = Arc::new(OffsetTime::with_fixed(genesis_timestamp));
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(RwLock::new(
+        Blockchain::new(env, NetworkId::UnitAlbatross, Arc::clone(&time)).unwrap(),
+    ));
+    let producer = BlockProducer::new(signing_key(), voting_key());
+
+    for i in 1..=3 {
+        time.advance(100);
+
+        let bc = blockchain.upgradable_read();
+        let block =
+            producer.next_micro_block(&bc, bc.time.now(), 0, None, vec![], vec![], vec![0x42]);
+        assert_eq!(block.header.timestamp, genesis_timestamp + i * 100);
+
+        assert_eq!(
+            Blockchain::push(bc, Block::Micro(block)),
+            Ok(PushResult::Extended)
+        );
+    }
+
+    assert_eq!(
+        blockchain.read().head().timestamp(),
+        genesis_timestamp + 300
+    );
+}
+
 #[test]
 fn it_can_produce_macro_blocks() {
     let time = Arc::new(OffsetTime::new());
@@ -192,45 +239,88 @@ fn it_can_produce_election_blocks() {
 
 #[test]
 fn it_can_produce_a_chain_with_txns() {
+    // Small chain, otherwise the test takes too long. Use `BlockchainBenchmark` with a high number
+    // of blocks and transactions per block to measure the throughput.
+    let report = BlockchainBenchmark::new(5, policy::BLOCKS_PER_BATCH).run();
+
+    assert_eq!(report.num_blocks, policy::BLOCKS_PER_BATCH);
+    assert_eq!(
+        report.num_transactions,
+        5 * (policy::BLOCKS_PER_BATCH as usize - 1)
+    );
+}
+
+#[test]
+fn it_can_run_a_blockchain_benchmark_on_mdbx() {
+    let tmp_dir = tempdir().expect("Could not create temporal directory");
+
+    let report = BlockchainBenchmark::new(2, 3)
+        .with_mdbx(tmp_dir.path())
+        .run();
+
+    assert_eq!(report.num_blocks, 3);
+    assert_eq!(report.num_transactions, 6);
+    assert!(report.tps > 0.0);
+    assert!(report.latency_p50 <= report.latency_p90);
+    assert!(report.latency_p90 <= report.latency_p99);
+    assert!(report.latency_p99 <= report.elapsed);
+}
+
+#[test]
+fn it_distributes_accumulated_fees_on_batch_finalization() {
     let time = Arc::new(OffsetTime::new());
-    let env = if VOLATILE_ENV {
-        VolatileEnvironment::new(10).unwrap()
-    } else {
-        let tmp_dir = tempdir().expect("Could not create temporal directory");
-        let tmp_dir = tmp_dir.path().to_str().unwrap();
-        MdbxEnvironment::new(tmp_dir, 1024 * 1024 * 1024 * 1024, 21).unwrap()
-    };
+    let env = VolatileEnvironment::new(10).unwrap();
     let blockchain = Arc::new(RwLock::new(
         Blockchain::new(env, NetworkId::UnitAlbatross, time).unwrap(),
     ));
     let producer = BlockProducer::new(signing_key(), voting_key());
 
-    // Small chain, otherwise test takes too long, use a small number of txns when running in volatile env
-    // This test was intended to be used with an infinite loop and a high number of transactions per block though
-    for _ in 0..1 {
-        fill_micro_blocks_with_txns(&producer, &blockchain, 5, 0);
+    // Fill the first batch with transactions, each paying a fee of 2 Lunas.
+    produce_macro_blocks_with_txns(&producer, &blockchain, 1, 5, 0);
 
-        let blockchain = blockchain.upgradable_read();
-        let next_block_height = (blockchain.block_number() + 1) as u64;
+    let tx_fees = Coin::from_u64_unchecked(2 * 5 * (policy::BLOCKS_PER_BATCH as u64 - 1));
+    assert_eq!(blockchain.read().state().macro_info.cum_tx_fees, tx_fees);
 
-        let macro_block_proposal = producer.next_macro_block_proposal(
-            &blockchain,
-            blockchain.time.now() + next_block_height as u64 * 100,
-            0u32,
-            vec![],
-        );
+    // The rewards of the first batch are paid out when the second batch is finalized.
+    fill_micro_blocks(&producer, &blockchain);
 
-        let block = sign_macro_block(
-            &producer.voting_key,
-            macro_block_proposal.header,
-            macro_block_proposal.body,
-        );
+    let bc = blockchain.upgradable_read();
+    let macro_block = producer.next_macro_block_proposal(
+        &bc,
+        bc.time.now() + bc.block_number() as u64 * 1000,
+        0u32,
+        vec![],
+    );
 
-        assert_eq!(
-            Blockchain::push(blockchain, Block::Macro(block)),
-            Ok(PushResult::Extended)
-        );
-    }
+    let block_reward = block_reward_for_batch_with_genesis(
+        &macro_block.header,
+        &bc.macro_head().header,
+        &bc.election_head().header,
+    );
+
+    let inherents = bc.finalize_previous_batch(bc.state(), &macro_block.header);
+
+    let rewards = inherents
+        .iter()
+        .filter(|inherent| inherent.ty == InherentType::Reward)
+        .fold(Coin::ZERO, |sum, inherent| sum + inherent.value);
+    assert_eq!(rewards, block_reward + tx_fees);
+
+    assert_eq!(
+        inherents
+            .iter()
+            .filter(|inherent| inherent.ty == InherentType::FinalizeBatch)
+            .count(),
+        1
+    );
+
+    // Applying the finalization resets the fee accumulator for the (empty) second batch.
+    let block = sign_macro_block(&voting_key(), macro_block.header, macro_block.body);
+    assert_eq!(
+        Blockchain::push(bc, Block::Macro(block)),
+        Ok(PushResult::Extended)
+    );
+    assert_eq!(blockchain.read().state().macro_info.cum_tx_fees, Coin::ZERO);
 }
 
 #[test]
@@ -429,6 +519,293 @@ fn it_can_revert_create_stacker_transaction() {
     let result = bc.revert_blocks(3, &mut txn);
 
     assert_eq!(result, Ok(()));
+    StakingContract::assert_invariants(&bc.state().accounts.tree, &txn);
+}
+
+#[test]
+fn it_cannot_revert_past_an_election_block() {
+    let time = Arc::new(OffsetTime::new());
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(RwLock::new(
+        Blockchain::new(env, NetworkId::UnitAlbatross, time).unwrap(),
+    ));
+    let producer = BlockProducer::new(signing_key(), voting_key());
+
+    // Produce the first epoch, ending in an election block, followed by two micro blocks.
+    produce_macro_blocks(&producer, &blockchain, policy::BATCHES_PER_EPOCH as usize);
+    assert!(policy::is_election_block_at(
+        blockchain.read().block_number()
+    ));
+
+    for _ in 0..2 {
+        let bc = blockchain.upgradable_read();
+        let block = producer.next_micro_block(
+            &bc,
+            bc.time.now() + (bc.block_number() + 1) as u64 * 1000,
+            0,
+            None,
+            vec![],
+            vec![],
+            vec![0x41],
+        );
+
+        assert_eq!(
+            Blockchain::push(bc, Block::Micro(block)),
+            Ok(PushResult::Extended)
+        );
+    }
+
+    let election_block_number = policy::BLOCKS_PER_EPOCH;
+    assert_eq!(blockchain.read().block_number(), election_block_number + 2);
+
+    let bc = blockchain.upgradable_read();
+    let mut txn = bc.write_transaction();
+
+    // Reverting across the election block is refused and leaves the chain untouched.
+    assert_eq!(bc.revert_blocks(3, &mut txn), Err(PushError::InvalidRevert));
+    assert_eq!(bc.block_number(), election_block_number + 2);
+
+    // Reverting only the micro blocks still works.
+    assert_eq!(bc.revert_blocks(2, &mut txn), Ok(()));
+}
+
+#[test]
+fn it_reports_rejected_transactions() {
+    let time = Arc::new(OffsetTime::new());
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(RwLock::new(
+        Blockchain::new(env, NetworkId::UnitAlbatross, time).unwrap(),
+    ));
+    let producer = BlockProducer::new(signing_key(), voting_key());
+
+    let key_pair = ed25519_key_pair(ACCOUNT_SECRET_KEY);
+    let recipient = Address::from([1u8; Address::SIZE]);
+
+    // The genesis account can afford the first transaction, but not the second one.
+    let funded_tx = TransactionBuilder::new_basic(
+        &key_pair,
+        recipient.clone(),
+        Coin::from_u64_unchecked(10),
+        Coin::from_u64_unchecked(1),
+        1,
+        NetworkId::UnitAlbatross,
+    )
+    .unwrap();
+    let underfunded_tx = TransactionBuilder::new_basic(
+        &key_pair,
+        recipient,
+        Coin::from_u64_unchecked(Coin::MAX_SAFE_VALUE - 1),
+        Coin::from_u64_unchecked(1),
+        1,
+        NetworkId::UnitAlbatross,
+    )
+    .unwrap();
+
+    let bc = blockchain.upgradable_read();
+    let (block, report) = producer.next_micro_block_with_report(
+        &bc,
+        bc.time.now(),
+        0,
+        None,
+        vec![],
+        vec![underfunded_tx.clone(), funded_tx.clone()],
+        vec![0x41],
+    );
+
+    assert_eq!(report.included, vec![funded_tx.clone()]);
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(report.rejected[0].0, underfunded_tx);
+    assert!(matches!(
+        report.rejected[0].1,
+        RejectReason::Account(AccountError::InsufficientFunds { .. })
+    ));
+    assert_eq!(block.body.as_ref().unwrap().transactions, vec![funded_tx]);
+
+    assert_eq!(
+        Blockchain::push(bc, Block::Micro(block)),
+        Ok(PushResult::Extended)
+    );
+}
+
+#[test]
+fn it_respects_the_configured_max_body_size() {
+    let time = Arc::new(OffsetTime::new());
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(RwLock::new(
+        Blockchain::new(env, NetworkId::UnitAlbatross, time).unwrap(),
+    ));
+
+    let key_pair = ed25519_key_pair(ACCOUNT_SECRET_KEY);
+    let txs: Vec<_> = (1..=2u8)
+        .map(|i| {
+            TransactionBuilder::new_basic(
+                &key_pair,
+                Address::from([i; Address::SIZE]),
+                Coin::from_u64_unchecked(10),
+                Coin::from_u64_unchecked(1),
+                1,
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    // Only leave room for a single transaction in the body.
+    let empty_body = MicroBody {
+        fork_proofs: vec![],
+        transactions: vec![],
+    };
+    let config = ProducerConfig {
+        max_body_size: empty_body.serialized_size() + txs[0].serialized_size(),
+        ..Default::default()
+    };
+    let producer = BlockProducer::new_with_config(signing_key(), voting_key(), config.clone());
+
+    let bc = blockchain.upgradable_read();
+    let block =
+        producer.next_micro_block(&bc, bc.time.now(), 0, None, vec![], txs.clone(), vec![0x41]);
+
+    let body = block.body.as_ref().unwrap();
+    assert_eq!(body.transactions.len(), 1);
+    assert!(body.serialized_size() <= config.max_body_size);
+
+    let (_, report) =
+        producer.next_micro_block_with_report(&bc, bc.time.now(), 0, None, vec![], txs, vec![0x41]);
+    assert_eq!(report.included.len(), 1);
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(report.rejected[0].1, RejectReason::BlockFull);
+
+    assert_eq!(
+        Blockchain::push(bc, Block::Micro(block)),
+        Ok(PushResult::Extended)
+    );
+}
+
+#[test]
+fn it_does_not_apply_transactions_rejected_by_the_config() {
+    let time = Arc::new(OffsetTime::new());
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(RwLock::new(
+        Blockchain::new(env, NetworkId::UnitAlbatross, time).unwrap(),
+    ));
+
+    let key_pair = ed25519_key_pair(ACCOUNT_SECRET_KEY);
+
+    // The first transaction pays no fee, but spends almost all funds of the genesis account.
+    let free_tx = TransactionBuilder::new_basic(
+        &key_pair,
+        Address::from([1u8; Address::SIZE]),
+        Coin::from_u64_unchecked(10_000_000_00000 - 10),
+        Coin::ZERO,
+        1,
+        NetworkId::UnitAlbatross,
+    )
+    .unwrap();
+    let paying_tx = TransactionBuilder::new_basic(
+        &key_pair,
+        Address::from([2u8; Address::SIZE]),
+        Coin::from_u64_unchecked(10),
+        Coin::from_u64_unchecked(1),
+        1,
+        NetworkId::UnitAlbatross,
+    )
+    .unwrap();
+
+    let config = ProducerConfig {
+        min_fee_per_byte: 0.5 / paying_tx.serialized_size() as f64,
+        ..Default::default()
+    };
+    let producer = BlockProducer::new_with_config(signing_key(), voting_key(), config);
+
+    let bc = blockchain.upgradable_read();
+    let (block, report) = producer.next_micro_block_with_report(
+        &bc,
+        bc.time.now(),
+        0,
+        None,
+        vec![],
+        vec![free_tx.clone(), paying_tx.clone()],
+        vec![0x41],
+    );
+
+    // The free transaction must not use up the funds of the paying one.
+    assert_eq!(report.included, vec![paying_tx.clone()]);
+    assert_eq!(report.rejected, vec![(free_tx, RejectReason::FeeTooLow)]);
+    assert_eq!(block.body.as_ref().unwrap().transactions, vec![paying_tx]);
+
+    assert_eq!(
+        Blockchain::push(bc, Block::Micro(block)),
+        Ok(PushResult::Extended)
+    );
+}
+
+#[test]
+fn it_rejects_blocks_with_too_many_fork_proofs() {
+    let time = Arc::new(OffsetTime::new());
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(RwLock::new(
+        Blockchain::new(env, NetworkId::UnitAlbatross, time).unwrap(),
+    ));
+    let producer = BlockProducer::new(signing_key(), voting_key());
+
+    let bc = blockchain.upgradable_read();
+    let prev_vrf_seed = bc.head().seed().clone();
+    let block = producer.next_micro_block(&bc, bc.time.now(), 0, None, vec![], vec![], vec![0x41]);
+    assert_eq!(
+        Blockchain::push(bc, Block::Micro(block.clone())),
+        Ok(PushResult::Extended)
+    );
+
+    // Create fork at #1.0
+    let fork_proof = {
+        let header1 = block.header.clone();
+        let justification1 = block.justification.unwrap().signature;
+        let mut header2 = header1.clone();
+        header2.timestamp += 1;
+        let hash2 = header2.hash::<Blake2bHash>();
+        let justification2 = signing_key().sign(hash2.as_slice());
+        ForkProof::new(
+            header1,
+            justification1,
+            header2,
+            justification2,
+            prev_vrf_seed,
+        )
+    };
+    let fork_proofs = vec![fork_proof; policy::MAX_FORK_PROOFS_PER_BLOCK + 1];
+
+    // The producer never includes more fork proofs than allowed.
+    let bc = blockchain.upgradable_read();
+    let block = producer.next_micro_block(
+        &bc,
+        bc.time.now() + 1000,
+        0,
+        None,
+        fork_proofs.clone(),
+        vec![],
+        vec![0x41],
+    );
+    assert_eq!(
+        block.body.as_ref().unwrap().fork_proofs.len(),
+        policy::MAX_FORK_PROOFS_PER_BLOCK
+    );
+
+    // Sneak in the remaining fork proofs and sign the block again.
+    let mut block = block;
+    let body = MicroBody {
+        fork_proofs,
+        transactions: vec![],
+    };
+    block.header.body_root = body.hash();
+    block.body = Some(body);
+    let hash = block.header.hash::<Blake2bHash>();
+    block.justification.as_mut().unwrap().signature = signing_key().sign(hash.as_slice());
+
+    assert_eq!(
+        Blockchain::push(bc, Block::Micro(block)),
+        Err(PushError::InvalidBlock(BlockError::TooManyForkProofs))
+    );
+    assert_eq!(blockchain.read().block_number(), 1);
 }
 
 fn ed25519_key_pair(secret_key: &str) -> SchnorrKeyPair {
@@ -1,12 +1,20 @@
-use nimiq_account::Accounts;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use nimiq_account::{Accounts, AccountsEvents};
 use nimiq_block::{Block, MicroBlock, ViewChanges};
-use nimiq_database::WriteTransaction;
+use nimiq_database::{Environment, WriteTransaction};
+use nimiq_genesis::NetworkInfo;
+use nimiq_primitives::networks::NetworkId;
 use nimiq_primitives::policy;
+use nimiq_utils::time::OffsetTime;
 use nimiq_vrf::VrfEntropy;
 
 use crate::blockchain_state::BlockchainState;
+use crate::chain_store::ChainStore;
 use crate::history::ExtendedTransaction;
-use crate::{Blockchain, PushError};
+use crate::{Blockchain, BlockchainError, PushError};
 
 /// Implements methods to handle the accounts.
 impl Blockchain {
@@ -19,6 +27,21 @@ impl Blockchain {
         first_view_number: u32,
         txn: &mut WriteTransaction,
     ) -> Result<(), PushError> {
+        self.commit_accounts_with_events(state, block, prev_entropy, first_view_number, txn)
+            .map(|_| ())
+    }
+
+    /// Updates the accounts given a block like `commit_accounts`, but also returns the events
+    /// that were produced. They must only be delivered with `Accounts::notify` once the given
+    /// transaction has been committed.
+    pub(crate) fn commit_accounts_with_events(
+        &self,
+        state: &BlockchainState,
+        block: &Block,
+        prev_entropy: VrfEntropy,
+        first_view_number: u32,
+        txn: &mut WriteTransaction,
+    ) -> Result<AccountsEvents, PushError> {
         // Get the accounts from the state.
         let accounts = &state.accounts;
 
@@ -29,7 +52,7 @@ impl Blockchain {
                 let inherents = self.create_macro_block_inherents(state, &macro_block.header);
 
                 // Commit block to AccountsTree and create the receipts.
-                let receipts = accounts.commit(
+                let receipts = accounts.commit_with_events(
                     txn,
                     &[],
                     &inherents,
@@ -38,9 +61,10 @@ impl Blockchain {
                 );
 
                 // Check if the receipts contain an error.
-                if let Err(e) = receipts {
-                    return Err(PushError::AccountsError(e));
-                }
+                let events = match receipts {
+                    Ok((_, events)) => events,
+                    Err(e) => return Err(PushError::AccountsError(e)),
+                };
 
                 // Macro blocks are final and receipts for the previous batch are no longer necessary
                 // as rebranching across this block is not possible.
@@ -60,6 +84,8 @@ impl Blockchain {
                     policy::epoch_at(macro_block.header.block_number),
                     &ext_txs,
                 );
+
+                Ok(events)
             }
             Block::Micro(ref micro_block) => {
                 // Get the body of the block.
@@ -78,7 +104,7 @@ impl Blockchain {
                     self.create_slash_inherents(&body.fork_proofs, &view_changes, Some(txn));
 
                 // Commit block to AccountsTree and create the receipts.
-                let receipts = accounts.commit(
+                let receipts = accounts.commit_with_events(
                     txn,
                     &body.transactions,
                     &inherents,
@@ -87,15 +113,23 @@ impl Blockchain {
                 );
 
                 // Check if the receipts contain an error.
-                if let Err(e) = receipts {
-                    return Err(PushError::AccountsError(e));
-                }
+                let (receipts, events) = match receipts {
+                    Ok(result) => result,
+                    Err(e) => return Err(PushError::AccountsError(e)),
+                };
 
                 // Store receipts.
-                let receipts = receipts.unwrap();
                 self.chain_store
                     .put_receipts(txn, micro_block.header.block_number, &receipts);
 
+                // Prune the receipts that fell out of the revert window.
+                if let Some(revert_window) = self.revert_window {
+                    self.chain_store.prune_receipts(
+                        txn,
+                        (micro_block.header.block_number + 1).saturating_sub(revert_window),
+                    );
+                }
+
                 // Store the transactions and the inherents into the History tree.
                 let ext_txs = ExtendedTransaction::from(
                     self.network_id,
@@ -110,14 +144,15 @@ impl Blockchain {
                     policy::epoch_at(micro_block.header.block_number),
                     &ext_txs,
                 );
+
+                Ok(events)
             }
         }
-
-        Ok(())
     }
 
     /// Reverts the accounts given a block. This only applies to micro blocks, since macro blocks
-    /// are final and can't be reverted.
+    /// are final and can't be reverted. Like for `commit_accounts`, the returned events must only be
+    /// delivered once the given transaction has been committed.
     pub(crate) fn revert_accounts(
         &self,
         accounts: &Accounts,
@@ -125,7 +160,7 @@ impl Blockchain {
         micro_block: &MicroBlock,
         prev_entropy: VrfEntropy,
         prev_view_number: u32,
-    ) -> Result<(), PushError> {
+    ) -> Result<AccountsEvents, PushError> {
         assert_eq!(
             micro_block.header.state_root,
             accounts.get_root(Some(txn)),
@@ -152,14 +187,15 @@ impl Blockchain {
         // Create the inherents from any forks and view changes.
         let inherents = self.create_slash_inherents(&body.fork_proofs, &view_changes, Some(txn));
 
-        // Get the receipts for this block.
+        // Get the receipts for this block. They are missing if the block is outside of the revert
+        // window.
         let receipts = self
             .chain_store
             .get_receipts(micro_block.header.block_number, Some(txn))
-            .expect("Failed to revert - missing receipts");
+            .ok_or(PushError::BeyondRevertWindow)?;
 
         // Revert the block from AccountsTree.
-        if let Err(e) = accounts.revert(
+        let events = match accounts.revert_with_events(
             txn,
             &body.transactions,
             &inherents,
@@ -167,8 +203,9 @@ impl Blockchain {
             micro_block.header.timestamp,
             &receipts,
         ) {
-            panic!("Failed to revert - {:?}", e);
-        }
+            Ok(events) => events,
+            Err(e) => panic!("Failed to revert - {:?}", e),
+        };
 
         // Remove the transactions from the History tree. For this you only need to calculate the
         // number of transactions that you want to remove.
@@ -180,6 +217,50 @@ impl Blockchain {
             num_txs,
         );
 
-        Ok(())
+        Ok(events)
+    }
+
+    /// Rebuilds the accounts from the blocks stored in `blocks_env`, e.g. after the accounts
+    /// database got corrupted. The blocks of the main chain are replayed from the genesis block
+    /// into a new blockchain in `env`, which must be empty. Every block is verified when it is
+    /// pushed, including its state root, so this fails at the first block whose state can't be
+    /// reproduced. It also fails if the body of a block is missing, e.g. because it was pruned.
+    pub fn rebuild_accounts_from_blocks(
+        env: Environment,
+        blocks_env: Environment,
+        network_id: NetworkId,
+    ) -> Result<Accounts, PushError> {
+        let chain_store = ChainStore::new(blocks_env);
+
+        let head_hash = chain_store
+            .get_head(None)
+            .ok_or(BlockchainError::FailedLoadingMainChain)?;
+        let head_block_number = chain_store
+            .get_block(&head_hash, false, None)
+            .ok_or(BlockchainError::FailedLoadingMainChain)?
+            .block_number();
+
+        let genesis_block = chain_store
+            .get_block_at(0, true, None)
+            .ok_or(BlockchainError::FailedLoadingMainChain)?;
+        let genesis_accounts = NetworkInfo::from_network_id(network_id).genesis_accounts();
+
+        let blockchain = Arc::new(RwLock::new(Blockchain::with_genesis(
+            env.clone(),
+            Arc::new(OffsetTime::new()),
+            network_id,
+            genesis_block,
+            genesis_accounts,
+        )?));
+
+        for block_number in 1..=head_block_number {
+            let block = chain_store
+                .get_block_at(block_number, true, None)
+                .ok_or(BlockchainError::FailedLoadingMainChain)?;
+
+            Blockchain::push(blockchain.upgradable_read(), block)?;
+        }
+
+        Ok(Accounts::new(env))
     }
 }
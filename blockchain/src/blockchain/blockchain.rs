@@ -11,6 +11,7 @@ use nimiq_primitives::policy;
 use nimiq_primitives::slots::Validators;
 use nimiq_utils::observer::Notifier;
 use nimiq_utils::time::OffsetTime;
+use tokio::sync::broadcast;
 
 use crate::blockchain_state::BlockchainState;
 use crate::chain_info::ChainInfo;
@@ -19,9 +20,13 @@ use crate::chain_metrics::BlockchainMetrics;
 use crate::chain_store::ChainStore;
 use crate::history::HistoryStore;
 use crate::reward::genesis_parameters;
-use crate::{BlockchainError, BlockchainEvent, ForkEvent};
+use crate::{BlockchainError, BlockchainEvent, ForkEvent, HeadInfo, ReorgInfo};
 use nimiq_trie::key_nibbles::KeyNibbles;
 
+/// The number of heads that are buffered for each head subscriber. Subscribers that fall further
+/// behind miss the oldest heads.
+pub const HEAD_SUBSCRIPTION_CAPACITY: usize = 64;
+
 /// The Blockchain struct. It stores all information of the blockchain. It is the main data
 /// structure in this crate.
 pub struct Blockchain {
@@ -35,6 +40,10 @@ pub struct Blockchain {
     pub notifier: Notifier<BlockchainEvent>,
     // The fork notifier processes fork events.
     pub fork_notifier: Notifier<ForkEvent>,
+    // The reorg notifier reports the details of every rebranch.
+    pub reorg_notifier: Notifier<ReorgInfo>,
+    // The sender for the subscribers of new heads.
+    pub(crate) head_sender: broadcast::Sender<HeadInfo>,
     // The chain store is a database containing all of the chain infos, blocks and receipts.
     pub chain_store: ChainStore,
     // The history store is a database containing all of the history trees and transactions.
@@ -43,6 +52,9 @@ pub struct Blockchain {
     pub state: BlockchainState,
     // A reference to a "function" to test whether a given transaction is known and valid.
     pub tx_verification_cache: Arc<dyn TransactionVerificationCache>,
+    // The number of micro blocks for which the receipts are retained. `None` keeps the receipts
+    // for the whole batch.
+    pub(crate) revert_window: Option<u32>,
     // The metrics for the blockchain. Needed for analysis.
     #[cfg(feature = "metrics")]
     pub(crate) metrics: BlockchainMetrics,
@@ -74,6 +86,13 @@ impl Blockchain {
         genesis_block: Block,
         genesis_accounts: Vec<(KeyNibbles, Account)>,
     ) -> Result<Self, BlockchainError> {
+        // The genesis block isn't verified like the other blocks, so check its version here.
+        if genesis_block.version() != policy::VERSION {
+            return Err(BlockchainError::UnsupportedGenesisVersion(
+                genesis_block.version(),
+            ));
+        }
+
         let chain_store = ChainStore::new(env.clone());
         let history_store = HistoryStore::new(env.clone());
 
@@ -194,6 +213,8 @@ impl Blockchain {
             time,
             notifier: Notifier::new(),
             fork_notifier: Notifier::new(),
+            reorg_notifier: Notifier::new(),
+            head_sender: broadcast::channel(HEAD_SUBSCRIPTION_CAPACITY).0,
             chain_store,
             history_store,
             state: BlockchainState {
@@ -208,6 +229,7 @@ impl Blockchain {
                 previous_slots: last_slots,
             },
             tx_verification_cache: Arc::new(DEFAULT_TX_VERIFICATION_CACHE),
+            revert_window: None,
             #[cfg(feature = "metrics")]
             metrics: BlockchainMetrics::default(),
             genesis_supply,
@@ -250,6 +272,8 @@ impl Blockchain {
             time,
             notifier: Notifier::new(),
             fork_notifier: Notifier::new(),
+            reorg_notifier: Notifier::new(),
+            head_sender: broadcast::channel(HEAD_SUBSCRIPTION_CAPACITY).0,
             chain_store,
             history_store,
             state: BlockchainState {
@@ -264,6 +288,7 @@ impl Blockchain {
                 previous_slots: Some(Validators::default()),
             },
             tx_verification_cache: Arc::new(DEFAULT_TX_VERIFICATION_CACHE),
+            revert_window: None,
             #[cfg(feature = "metrics")]
             metrics: BlockchainMetrics::default(),
             genesis_supply,
@@ -278,6 +303,36 @@ impl Blockchain {
     pub fn write_transaction(&self) -> WriteTransaction {
         WriteTransaction::new(&self.env)
     }
+
+    /// Sets the number of past micro blocks for which the receipts are retained. Receipts of older
+    /// blocks are pruned as new blocks are committed, so reverting more than `revert_window` blocks
+    /// fails with `PushError::BeyondRevertWindow`. `None` retains the receipts for the whole batch.
+    pub fn set_revert_window(&mut self, revert_window: Option<u32>) {
+        self.revert_window = revert_window;
+    }
+
+    /// Subscribes to the new heads of the main chain. A head is sent for every block that extends
+    /// the main chain and for the new head after a rebranch. The channel holds up to
+    /// `HEAD_SUBSCRIPTION_CAPACITY` heads; a subscriber that falls further behind gets a
+    /// `RecvError::Lagged` with the number of heads it missed.
+    pub fn subscribe_heads(&self) -> broadcast::Receiver<HeadInfo> {
+        self.head_sender.subscribe()
+    }
+
+    /// Sends the current head of the main chain to the head subscribers.
+    pub(crate) fn notify_head(&self, rebranched: bool) {
+        let head = &self.state.main_chain.head;
+        let head_info = HeadInfo {
+            hash: self.state.head_hash.clone(),
+            block_number: head.block_number(),
+            view_number: head.view_number(),
+            timestamp: head.timestamp(),
+            rebranched,
+        };
+
+        // Sending only fails if there are no subscribers.
+        let _ = self.head_sender.send(head_info);
+    }
 }
 
 pub trait TransactionVerificationCache: Send + Sync {
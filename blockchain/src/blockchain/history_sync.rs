@@ -11,7 +11,7 @@ use nimiq_primitives::policy;
 use crate::chain_info::ChainInfo;
 use crate::history::{ExtTxData, ExtendedTransaction, HistoryStore};
 use crate::{AbstractBlockchain, Blockchain, BlockchainEvent, PushError, PushResult};
-use nimiq_account::{Inherent, InherentType};
+use nimiq_account::{AccountsEvents, Inherent, InherentType};
 
 /// Implements methods to push macro blocks into the chain when an history node is syncing. This
 /// type of syncing is called history syncing. It works by having the node get all the election
@@ -224,7 +224,7 @@ impl Blockchain {
         // We might already know the given epoch partially.
         // Revert our chain to a common ancestor state in case we have adopted a different history.
         // Also skip over any transactions that we already know.
-        let first_new_ext_tx = this.revert_to_common_state(&block, history, &mut txn);
+        let (first_new_ext_tx, mut events) = this.revert_to_common_state(&block, history, &mut txn);
 
         // Separate the extended transactions by block number and type.
         // We know it comes sorted because we already checked it against the history root and
@@ -281,7 +281,7 @@ impl Blockchain {
         // Update the accounts tree, one block at a time.
         for i in 0..block_numbers.len() {
             // Commit block to AccountsTree and create the receipts.
-            let receipts = this.state.accounts.commit_batch(
+            let receipts = this.state.accounts.commit_batch_with_events(
                 &mut txn,
                 &block_transactions[i],
                 &block_inherents[i],
@@ -290,21 +290,24 @@ impl Blockchain {
             );
 
             // Check if the receipts contain an error.
-            if let Err(e) = receipts {
-                warn!(
-                    %block,
-                    reason = "commit of block failed",
-                    block_no = block_numbers[i],
-                    num_transactions = block_transactions[i].len(),
-                    num_inherents = block_inherents[i].len(),
-                    error = &e as &dyn Error,
-                    "Rejecting block",
-                );
-
-                txn.abort();
-                #[cfg(feature = "metrics")]
-                this.metrics.note_invalid_block();
-                return Err(PushError::AccountsError(e));
+            match receipts {
+                Ok((_, block_events)) => events.append(block_events),
+                Err(e) => {
+                    warn!(
+                        %block,
+                        reason = "commit of block failed",
+                        block_no = block_numbers[i],
+                        num_transactions = block_transactions[i].len(),
+                        num_inherents = block_inherents[i].len(),
+                        error = &e as &dyn Error,
+                        "Rejecting block",
+                    );
+
+                    txn.abort();
+                    #[cfg(feature = "metrics")]
+                    this.metrics.note_invalid_block();
+                    return Err(PushError::AccountsError(e));
+                }
             }
         }
         this.state.accounts.finalize_batch(&mut txn);
@@ -360,6 +363,8 @@ impl Blockchain {
 
         let this = RwLockWriteGuard::downgrade_to_upgradable(this);
 
+        this.state.accounts.notify(events);
+
         debug!(
             %block,
             num_transactions = block.num_transactions(),
@@ -380,6 +385,7 @@ impl Blockchain {
         } else {
             this.notifier.notify(BlockchainEvent::Finalized(block_hash));
         }
+        this.notify_head(false);
 
         // Return result.
         Ok(PushResult::Extended)
@@ -390,13 +396,14 @@ impl Blockchain {
         block: &Block,
         history: &[ExtendedTransaction],
         txn: &mut WriteTransaction,
-    ) -> usize {
+    ) -> (usize, AccountsEvents) {
         // Find the index of the first extended transaction in the current batch.
         let last_macro_block = policy::last_macro_block(self.block_number());
         let mut first_new_ext_tx = history
             .iter()
             .position(|ext_tx| ext_tx.block_number > last_macro_block)
             .unwrap_or(history.len());
+        let mut events = AccountsEvents::default();
 
         // Check if our adopted non-final history matches the given history.
         // Revert any blocks that don't match.
@@ -433,7 +440,8 @@ impl Blockchain {
             if let Some(diverging_block) = diverging_block {
                 // The histories diverge, so revert our state to the block before the divergence.
                 let num_blocks_to_revert = self.block_number() - diverging_block + 1;
-                self.revert_blocks(num_blocks_to_revert, txn)
+                events = self
+                    .revert_blocks_with_events(num_blocks_to_revert, txn)
                     .expect("Failed to revert chain");
 
                 // TODO We could incorporate this into the parallel iteration loop above.
@@ -453,20 +461,54 @@ impl Blockchain {
             let first_block_number = history[first_new_ext_tx].block_number;
             if first_block_number <= self.block_number() {
                 let num_blocks_to_revert = self.block_number() - first_block_number + 1;
-                self.revert_blocks(num_blocks_to_revert, txn)
+                events = self
+                    .revert_blocks_with_events(num_blocks_to_revert, txn)
                     .expect("Failed to revert chain");
             }
         };
 
-        first_new_ext_tx
+        (first_new_ext_tx, events)
     }
 
-    /// Reverts a given number of micro blocks from the blockchain.
+    /// Reverts a given number of micro blocks from the blockchain. Macro blocks are final, so
+    /// trying to revert past the last macro block fails with `PushError::InvalidRevert` without
+    /// reverting any block. Likewise, reverting more blocks than the revert window fails with
+    /// `PushError::BeyondRevertWindow`.
     pub fn revert_blocks(
         &self,
         num_blocks: u32,
         write_txn: &mut WriteTransaction,
     ) -> Result<(), PushError> {
+        self.revert_blocks_with_events(num_blocks, write_txn)
+            .map(|_| ())
+    }
+
+    /// Reverts a given number of micro blocks like `revert_blocks`, but also returns the events
+    /// that were produced. They must only be delivered with `Accounts::notify` once the given
+    /// transaction has been committed.
+    pub(crate) fn revert_blocks_with_events(
+        &self,
+        num_blocks: u32,
+        write_txn: &mut WriteTransaction,
+    ) -> Result<AccountsEvents, PushError> {
+        let num_micro_blocks = self.block_number() - self.state.macro_info.head.block_number();
+        if num_blocks > num_micro_blocks {
+            warn!(
+                num_blocks,
+                num_micro_blocks, "Refusing to revert past the last macro block",
+            );
+            return Err(PushError::InvalidRevert);
+        }
+        if let Some(revert_window) = self.revert_window {
+            if num_blocks > revert_window {
+                warn!(
+                    num_blocks,
+                    revert_window, "Refusing to revert beyond the revert window",
+                );
+                return Err(PushError::BeyondRevertWindow);
+            }
+        }
+
         debug!(
             num_blocks,
             "Need to revert micro blocks from the current epoch",
@@ -476,6 +518,8 @@ impl Blockchain {
             .get_chain_info(&self.head_hash(), true, Some(write_txn))
             .expect("Couldn't fetch chain info for the head of the chain!");
 
+        let mut events = AccountsEvents::default();
+
         // Revert each block individually.
         for _ in 0..num_blocks {
             match current_info.head {
@@ -486,13 +530,13 @@ impl Blockchain {
                         .expect("Failed to find main chain predecessor while reverting blocks!");
 
                     // Revert the accounts tree. This also reverts the history store.
-                    self.revert_accounts(
+                    events.append(self.revert_accounts(
                         &self.state.accounts,
                         write_txn,
                         micro_block,
                         prev_info.head.seed().entropy(),
                         prev_info.head.next_view_number(),
-                    )?;
+                    )?);
 
                     current_info = prev_info;
                 }
@@ -502,6 +546,6 @@ impl Blockchain {
             }
         }
 
-        Ok(())
+        Ok(events)
     }
 }
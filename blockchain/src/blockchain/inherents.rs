@@ -1,5 +1,4 @@
-use beserial::Serialize;
-use nimiq_account::{Inherent, InherentType, StakingContract};
+use nimiq_account::{Inherent, StakingContract};
 use nimiq_block::{ForkProof, MacroHeader, ViewChanges};
 use nimiq_database as db;
 use nimiq_keys::Address;
@@ -82,12 +81,7 @@ impl Blockchain {
         };
 
         // Create the corresponding slash inherent.
-        Inherent {
-            ty: InherentType::Slash,
-            target: self.staking_contract_address(),
-            value: Coin::ZERO,
-            data: slot.serialize_to_vec(),
-        }
+        Inherent::slash(&slot)
     }
 
     /// It creates a slash inherent(s) from a view change(s). It expects a *verified* view change!
@@ -122,12 +116,7 @@ impl Blockchain {
                 };
 
                 // Create the corresponding slash inherent.
-                Inherent {
-                    ty: InherentType::Slash,
-                    target: self.staking_contract_address(),
-                    value: Coin::ZERO,
-                    data: slot.serialize_to_vec(),
-                }
+                Inherent::slash(&slot)
             })
             .collect::<Vec<Inherent>>()
     }
@@ -203,8 +192,30 @@ impl Blockchain {
         // accept the inherent.
         let mut burned_reward = Coin::ZERO;
 
+        // Look up the reward addresses of all validators and the accounts behind them up front,
+        // within a single database transaction.
+        let txn = self.read_transaction();
+        let reward_addresses: Vec<Address> = validator_slots
+            .iter()
+            .map(|validator_slot| {
+                StakingContract::get_validator(
+                    &self.state().accounts.tree,
+                    &txn,
+                    &validator_slot.address,
+                )
+                .expect("Couldn't find validator in the accounts trie when paying rewards!")
+                .reward_address
+            })
+            .collect();
+        let reward_keys: Vec<KeyNibbles> = reward_addresses.iter().map(KeyNibbles::from).collect();
+        let reward_accounts = state.accounts.get_multi(&reward_keys, Some(&txn));
+
         // Compute inherents
-        for validator_slot in validator_slots.iter() {
+        for ((validator_slot, reward_address), account) in validator_slots
+            .iter()
+            .zip(reward_addresses)
+            .zip(reward_accounts)
+        {
             // The interval of slot numbers for the current slot band is
             // [first_slot_number, last_slot_number). So it actually doesn't include
             // `last_slot_number`.
@@ -238,26 +249,10 @@ impl Blockchain {
                 .expect("Overflow in reward");
 
             // Create inherent for the reward.
-            let validator = StakingContract::get_validator(
-                &self.state().accounts.tree,
-                &self.read_transaction(),
-                &validator_slot.address,
-            )
-            .expect("Couldn't find validator in the accounts trie when paying rewards!");
-
-            let inherent = Inherent {
-                ty: InherentType::Reward,
-                target: validator.reward_address.clone(),
-                value: reward,
-                data: vec![],
-            };
+            let inherent = Inherent::reward(reward_address, reward);
 
             // Test whether account will accept inherent. If it can't then the reward will be
             // burned.
-            let account = state
-                .accounts
-                .get(&KeyNibbles::from(&inherent.target), None);
-
             if account.is_none() || account.unwrap().account_type() == AccountType::Basic {
                 num_eligible_slots_for_accepted_inherent.push(num_eligible_slots);
                 inherents.push(inherent);
@@ -293,23 +288,13 @@ impl Blockchain {
 
         // Create the inherent for the burned reward.
         if burned_reward > Coin::ZERO {
-            let inherent = Inherent {
-                ty: InherentType::Reward,
-                target: Address::burn_address(),
-                value: burned_reward,
-                data: vec![],
-            };
+            let inherent = Inherent::reward(Address::burn_address(), burned_reward);
 
             inherents.push(inherent);
         }
 
         // Push FinalizeBatch inherent to update StakingContract.
-        inherents.push(Inherent {
-            ty: InherentType::FinalizeBatch,
-            target: self.staking_contract_address(),
-            value: Coin::ZERO,
-            data: Vec::new(),
-        });
+        inherents.push(Inherent::finalize_batch());
 
         inherents
     }
@@ -317,11 +302,6 @@ impl Blockchain {
     /// Creates the inherent to finalize an epoch. The inherent is for updating the StakingContract.
     pub fn finalize_previous_epoch(&self) -> Inherent {
         // Create the FinalizeEpoch inherent.
-        Inherent {
-            ty: InherentType::FinalizeEpoch,
-            target: self.staking_contract_address(),
-            value: Coin::ZERO,
-            data: Vec::new(),
-        }
+        Inherent::finalize_epoch()
     }
 }
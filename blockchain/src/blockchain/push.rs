@@ -1,8 +1,12 @@
 use std::error::Error;
 use std::ops::Deref;
+use std::panic;
+use std::sync::Arc;
 
-use parking_lot::{RwLockUpgradableReadGuard, RwLockWriteGuard};
+use parking_lot::{RwLock, RwLockUpgradableReadGuard, RwLockWriteGuard};
+use tokio::task::spawn_blocking;
 
+use nimiq_account::AccountsEvents;
 use nimiq_block::{Block, ForkProof};
 use nimiq_database::WriteTransaction;
 use nimiq_hash::{Blake2bHash, Hash};
@@ -14,7 +18,7 @@ use crate::chain_info::ChainInfo;
 use crate::chain_store::MAX_EPOCHS_STORED;
 use crate::{
     AbstractBlockchain, Blockchain, BlockchainEvent, ChainOrdering, ForkEvent, PushError,
-    PushResult,
+    PushResult, ReorgInfo,
 };
 
 /// Implements methods to push blocks into the chain. This is used when the node has already synced
@@ -150,13 +154,13 @@ impl Blockchain {
                         .expect("Missing justification!")
                         .signature;
 
-                    let proof = ForkProof {
-                        header1: micro_header1.clone(),
-                        header2: micro_header2,
-                        justification1: justification1.clone(),
+                    let proof = ForkProof::new(
+                        micro_header1.clone(),
+                        justification1.clone(),
+                        micro_header2,
                         justification2,
-                        prev_vrf_seed: prev_info.head.seed().clone(),
-                    };
+                        prev_info.head.seed().clone(),
+                    );
 
                     this.fork_notifier.notify(ForkEvent::Detected(proof));
                 }
@@ -225,6 +229,21 @@ impl Blockchain {
         Self::do_push(this, block, true)
     }
 
+    /// Pushes a block into the chain without blocking the async executor.
+    /// The whole push runs on the blocking thread pool, including waiting for the upgradable lock
+    /// and committing the transactions to the accounts tree. Pushes are therefore serialized on
+    /// the blocking pool instead of the executor, and the lock is only upgraded for the final
+    /// write, exactly like in `push`. A panic during the push is resumed in the caller.
+    pub async fn push_async(
+        this: Arc<RwLock<Self>>,
+        block: Block,
+    ) -> Result<PushResult, PushError> {
+        match spawn_blocking(move || Self::push(this.upgradable_read(), block)).await {
+            Ok(result) => result,
+            Err(e) => panic::resume_unwind(e.into_panic()),
+        }
+    }
+
     /// Extends the current main chain.
     fn extend(
         this: RwLockUpgradableReadGuard<Blockchain>,
@@ -238,16 +257,19 @@ impl Blockchain {
         let is_macro_block = policy::is_macro_block_at(block_number);
         let is_election_block = policy::is_election_block_at(block_number);
 
-        if let Err(e) = this.check_and_commit(
+        let events = match this.check_and_commit(
             &this.state,
             &chain_info.head,
             prev_info.head.seed().entropy(),
             prev_info.head.next_view_number(),
             &mut txn,
         ) {
-            txn.abort();
-            return Err(e);
-        }
+            Ok(events) => events,
+            Err(e) => {
+                txn.abort();
+                return Err(e);
+            }
+        };
 
         chain_info.on_main_chain = true;
         prev_info.main_chain_successor = Some(chain_info.head.hash());
@@ -299,6 +321,8 @@ impl Blockchain {
             "Accepted block",
         );
 
+        this.state.accounts.notify(events);
+
         if is_election_block {
             this.notifier
                 .notify(BlockchainEvent::EpochFinalized(block_hash));
@@ -307,6 +331,7 @@ impl Blockchain {
         } else {
             this.notifier.notify(BlockchainEvent::Extended(block_hash));
         }
+        this.notify_head(false);
 
         Ok(PushResult::Extended)
     }
@@ -364,7 +389,24 @@ impl Blockchain {
             return Err(PushError::InvalidFork);
         }
 
+        // Check if the receipts needed to revert to the ancestor are still available.
+        let num_blocks_to_revert = this.block_number() - ancestor.1.head.block_number();
+        if let Some(revert_window) = this.revert_window {
+            if num_blocks_to_revert > revert_window {
+                warn!(
+                    block = %target_block,
+                    reason = "ancestor block outside of the revert window",
+                    ancestor_block = %ancestor.1.head,
+                    num_blocks_to_revert,
+                    revert_window,
+                    "Rejecting block",
+                );
+                return Err(PushError::BeyondRevertWindow);
+            }
+        }
+
         let mut write_txn = this.write_transaction();
+        let mut events = AccountsEvents::default();
 
         current = (this.state.head_hash.clone(), this.state.main_chain.clone());
 
@@ -383,13 +425,13 @@ impl Blockchain {
                         .get_chain_info(&prev_hash, true, Some(&write_txn))
                         .expect("Corrupted store: Failed to find main chain predecessor while rebranching");
 
-                    this.revert_accounts(
+                    events.append(this.revert_accounts(
                         &this.state.accounts,
                         &mut write_txn,
                         micro_block,
                         prev_info.head.seed().entropy(),
                         prev_info.head.next_view_number(),
-                    )?;
+                    )?);
 
                     assert_eq!(
                         prev_info.head.state_root(),
@@ -411,34 +453,37 @@ impl Blockchain {
         let mut fork_iter = fork_chain.iter().rev();
 
         while let Some(fork_block) = fork_iter.next() {
-            if let Err(e) = this.check_and_commit(
+            match this.check_and_commit(
                 &this.state,
                 &fork_block.1.head,
                 prev_entropy,
                 prev_view_number,
                 &mut write_txn,
             ) {
-                warn!(
-                    block = %target_block,
-                    reason = "failed to apply for block while rebranching",
-                    fork_block = %fork_block.1.head,
-                    error = &e as &dyn Error,
-                    "Rejecting block",
-                );
-                write_txn.abort();
+                Ok(fork_events) => events.append(fork_events),
+                Err(e) => {
+                    warn!(
+                        block = %target_block,
+                        reason = "failed to apply for block while rebranching",
+                        fork_block = %fork_block.1.head,
+                        error = &e as &dyn Error,
+                        "Rejecting block",
+                    );
+                    write_txn.abort();
+
+                    // Delete invalid fork blocks from store.
+                    let mut write_txn = this.write_transaction();
+                    for block in vec![fork_block].into_iter().chain(fork_iter) {
+                        this.chain_store.remove_chain_info(
+                            &mut write_txn,
+                            &block.0,
+                            fork_block.1.head.block_number(),
+                        )
+                    }
+                    write_txn.commit();
 
-                // Delete invalid fork blocks from store.
-                let mut write_txn = this.write_transaction();
-                for block in vec![fork_block].into_iter().chain(fork_iter) {
-                    this.chain_store.remove_chain_info(
-                        &mut write_txn,
-                        &block.0,
-                        fork_block.1.head.block_number(),
-                    )
+                    return Err(PushError::InvalidFork);
                 }
-                write_txn.commit();
-
-                return Err(PushError::InvalidFork);
             }
 
             prev_entropy = fork_block.1.head.seed().entropy();
@@ -511,6 +556,8 @@ impl Blockchain {
         // Downgrade the lock again as the notified listeners might want to acquire read themselves.
         let this = RwLockWriteGuard::downgrade_to_upgradable(this);
 
+        this.state.accounts.notify(events);
+
         let mut reverted_blocks = Vec::with_capacity(revert_chain.len());
         for (hash, chain_info) in revert_chain.into_iter().rev() {
             debug!(
@@ -539,8 +586,19 @@ impl Blockchain {
             "Rebranched",
         );
 
+        let reorg_info = ReorgInfo {
+            common_ancestor: ancestor.0,
+            reverted_blocks: reverted_blocks.clone(),
+            applied_blocks: adopted_blocks.clone(),
+        };
+
+        #[cfg(feature = "metrics")]
+        this.metrics.note_reorg(reorg_info.reverted_blocks.len());
+
         let event = BlockchainEvent::Rebranched(reverted_blocks, adopted_blocks);
         this.notifier.notify(event);
+        this.reorg_notifier.notify(reorg_info);
+        this.notify_head(true);
 
         Ok(PushResult::Rebranched)
     }
@@ -552,7 +610,7 @@ impl Blockchain {
         prev_entropy: VrfEntropy,
         first_view_number: u32,
         txn: &mut WriteTransaction,
-    ) -> Result<(), PushError> {
+    ) -> Result<AccountsEvents, PushError> {
         // Check transactions against replay attacks. This is only necessary for micro blocks.
         if block.is_micro() {
             let transactions = block.transactions();
@@ -574,12 +632,21 @@ impl Blockchain {
         }
 
         // Commit block to AccountsTree.
-        if let Err(e) = self.commit_accounts(state, block, prev_entropy, first_view_number, txn) {
-            warn!(%block, reason = "commit failed", error = &e as &dyn Error, "Rejecting block");
-            #[cfg(feature = "metrics")]
-            self.metrics.note_invalid_block();
-            return Err(e);
-        }
+        let events = match self.commit_accounts_with_events(
+            state,
+            block,
+            prev_entropy,
+            first_view_number,
+            txn,
+        ) {
+            Ok(events) => events,
+            Err(e) => {
+                warn!(%block, reason = "commit failed", error = &e as &dyn Error, "Rejecting block");
+                #[cfg(feature = "metrics")]
+                self.metrics.note_invalid_block();
+                return Err(e);
+            }
+        };
 
         // Verify the state against the block.
         if let Err(e) = self.verify_block_state(state, block, Some(txn)) {
@@ -587,6 +654,6 @@ impl Blockchain {
             return Err(e);
         }
 
-        Ok(())
+        Ok(events)
     }
 }
@@ -2,7 +2,7 @@ use nimiq_account::StakingContract;
 use nimiq_collections::BitSet;
 use nimiq_database::Transaction;
 use nimiq_primitives::policy;
-use nimiq_primitives::slots::{Validator, Validators};
+use nimiq_primitives::slots::{Validator, ValidatorSetDiff, Validators};
 use nimiq_vrf::{Rng, VrfEntropy, VrfSeed, VrfUseCase};
 
 use crate::{AbstractBlockchain, Blockchain};
@@ -37,6 +37,17 @@ impl Blockchain {
         }
     }
 
+    /// Computes how the validator set changed from `epoch_from` to `epoch_to`, based on the
+    /// validators stored in the election blocks. Returns None if the validators of either epoch
+    /// are unknown.
+    pub fn validator_set_diff(&self, epoch_from: u32, epoch_to: u32) -> Option<ValidatorSetDiff> {
+        let read_txn = self.read_transaction();
+        let validators_from = self.get_validators_for_epoch(epoch_from, Some(&read_txn))?;
+        let validators_to = self.get_validators_for_epoch(epoch_to, Some(&read_txn))?;
+
+        Some(validators_from.diff(&validators_to))
+    }
+
     /// Calculates the next validators from a given seed.
     pub fn next_validators(&self, seed: &VrfSeed) -> Validators {
         StakingContract::select_validators(
@@ -2,8 +2,8 @@ use std::cmp::Ordering;
 
 use beserial::Serialize;
 use nimiq_block::{
-    Block, BlockBody, BlockError, BlockHeader, BlockType, ForkProof, MacroBlock, MacroBody,
-    TendermintProof, ViewChange,
+    view_change_message, Block, BlockBody, BlockError, BlockHeader, BlockJustification, BlockType,
+    ForkProof, MacroBlock, MacroBody, MicroJustification, TendermintProof,
 };
 use nimiq_database::Transaction as DBtx;
 use nimiq_hash::{Blake2bHash, Hash};
@@ -32,6 +32,37 @@ impl Blockchain {
         txn_opt: Option<&DBtx>,
         check_seed: bool,
     ) -> Result<(), PushError> {
+        // Check if the block's immediate predecessor is part of the chain.
+        let prev_info = blockchain
+            .get_chain_info(header.parent_hash(), false, txn_opt)
+            .ok_or(PushError::Orphan)?;
+
+        Self::verify_header_successor(
+            blockchain,
+            header,
+            &prev_info.head.header(),
+            signing_key,
+            check_seed,
+        )
+        .map_err(|e| match e {
+            BlockError::InvalidBlockType
+            | BlockError::InvalidBlockNumber
+            | BlockError::InvalidTimestamp
+            | BlockError::InvalidParentHash => PushError::InvalidSuccessor,
+            e => PushError::InvalidBlock(e),
+        })
+    }
+
+    /// Verifies that a block header is a valid successor of the header of its immediate
+    /// predecessor. These are the checks of `verify_block_header` that don't need the predecessor
+    /// to be stored in the chain.
+    fn verify_header_successor<B: AbstractBlockchain>(
+        blockchain: &B,
+        header: &BlockHeader,
+        prev: &BlockHeader,
+        signing_key: &SchnorrPublicKey,
+        check_seed: bool,
+    ) -> Result<(), BlockError> {
         // Check the version
         if header.version() != policy::VERSION {
             warn!(
@@ -42,7 +73,7 @@ impl Blockchain {
                 "Rejecting block"
             );
 
-            return Err(PushError::InvalidBlock(BlockError::UnsupportedVersion));
+            return Err(BlockError::UnsupportedVersion);
         }
 
         // Check that the extra data does not exceed the permitted size. This is also checked during deserialization.
@@ -52,27 +83,25 @@ impl Blockchain {
                 reason = "too much extra data",
                 "Rejecting block"
             );
-            return Err(PushError::InvalidBlock(BlockError::ExtraDataTooLarge));
+            return Err(BlockError::ExtraDataTooLarge);
         }
 
-        // Check if the block's immediate predecessor is part of the chain.
-        let prev_info = blockchain
-            .get_chain_info(header.parent_hash(), false, txn_opt)
-            .ok_or(PushError::Orphan)?;
-
         // Check that the block is a valid successor of its predecessor.
-        let next_block_type = blockchain.get_next_block_type(Some(prev_info.head.block_number()));
+        let next_block_type = blockchain.get_next_block_type(Some(prev.block_number()));
         if header.ty() != next_block_type {
             warn!(
                 header = %header,
                 reason = "Wrong block type",
                 "Rejecting block obtained_type={:?} expected_type={:?}", header.ty(), next_block_type
             );
-            return Err(PushError::InvalidSuccessor);
+            return Err(BlockError::InvalidBlockType);
         }
 
         // Check the block number.
-        let next_block_number = prev_info.head.block_number() + 1;
+        let next_block_number = prev
+            .block_number()
+            .checked_add(1)
+            .ok_or(BlockError::InvalidBlockNumber)?;
         if header.block_number() != next_block_number {
             warn!(
                 header = %header,
@@ -81,20 +110,20 @@ impl Blockchain {
                 reason = "Wrong block number",
                 "Rejecting block"
             );
-            return Err(PushError::InvalidSuccessor);
+            return Err(BlockError::InvalidBlockNumber);
         }
 
         // Check that the current block timestamp is equal or greater than the timestamp of the
         // previous block.
-        if header.timestamp() < prev_info.head.timestamp() {
+        if header.timestamp() < prev.timestamp() {
             warn!(
                 header = %header,
                 obtained_timestamp = header.timestamp(),
-                parent_timestamp   = prev_info.head.timestamp(),
+                parent_timestamp   = prev.timestamp(),
                 reason = "Block timestamp precedes parent timestamp",
                 "Rejecting block"
             );
-            return Err(PushError::InvalidSuccessor);
+            return Err(BlockError::InvalidTimestamp);
         }
 
         // Check that the current block timestamp less the node's current time is less than or equal
@@ -110,16 +139,16 @@ impl Blockchain {
                 reason = "Block timestamp exceeds allowed maximum drift",
                 "Rejecting block"
             );
-            return Err(PushError::InvalidBlock(BlockError::FromTheFuture));
+            return Err(BlockError::FromTheFuture);
         }
 
         // Check if the seed was signed by the intended producer.
         if check_seed {
-            if let Err(e) = header.seed().verify(prev_info.head.seed(), signing_key) {
+            if let Err(e) = header.seed().verify(prev.seed(), signing_key) {
                 warn!(header = %header,
                       reason = "Invalid seed",
                       "Rejecting block vrf_error={:?}", e);
-                return Err(PushError::InvalidBlock(BlockError::InvalidSeed));
+                return Err(BlockError::InvalidSeed);
             }
         }
 
@@ -134,8 +163,162 @@ impl Blockchain {
                     reason = "Wrong parent election hash",
                     "Rejecting block"
                 );
-                return Err(PushError::InvalidSuccessor);
+                return Err(BlockError::InvalidParentHash);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates a block header against the header of its immediate predecessor, without needing
+    /// the block body and without touching the accounts. This is meant to be used by header-first
+    /// sync, where headers get validated before their bodies are fetched.
+    /// The header and the justification go through the same checks as in `push`, the predecessor
+    /// just doesn't need to be stored in the chain.
+    /// Note that the justification of an election block commits to the next validator set, which
+    /// is part of the body. Election block headers are therefore rejected with `MissingBody`, they
+    /// can only be validated together with their body.
+    pub fn validate_header(
+        &self,
+        header: &BlockHeader,
+        justification: &BlockJustification,
+        prev: &BlockHeader,
+    ) -> Result<(), BlockError> {
+        if *header.parent_hash() != prev.hash() {
+            warn!(
+                header = %header,
+                reason = "Parent hash doesn't match predecessor",
+                "Rejecting header"
+            );
+            return Err(BlockError::InvalidParentHash);
+        }
+
+        if header.ty() != justification.ty() {
+            return Err(BlockError::InvalidJustification);
+        }
+
+        // Get the intended block proposer.
+        let proposer_slot = self
+            .get_proposer_at(
+                header.block_number(),
+                header.view_number(),
+                prev.seed().entropy(),
+                None,
+            )
+            .ok_or(BlockError::UnknownProposer)?;
+        let signing_key = &proposer_slot.validator.signing_key;
+
+        Self::verify_header_successor(self, header, prev, signing_key, true)?;
+
+        match (header, justification) {
+            (BlockHeader::Micro(_), BlockJustification::Micro(justification)) => {
+                Self::verify_micro_justification(
+                    self,
+                    header,
+                    Some(justification),
+                    prev,
+                    signing_key,
+                    true,
+                )
+            }
+            (BlockHeader::Macro(macro_header), BlockJustification::Macro(justification)) => {
+                if policy::is_election_block_at(macro_header.block_number) {
+                    return Err(BlockError::MissingBody);
+                }
+
+                let macro_block = MacroBlock {
+                    header: macro_header.clone(),
+                    body: None,
+                    justification: Some(justification.clone()),
+                };
+                Self::verify_macro_justification(self, &macro_block, true)
             }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Validates a macro block proposal from another proposer against the local chain, so that we
+    /// only vote for proposals that we would produce ourselves. The proposal must extend our head
+    /// and goes through the same header, body and state checks as in `push`. The state is checked
+    /// by applying the proposal to the accounts in a transaction that is aborted afterwards.
+    pub fn validate_macro_proposal(&self, proposal: &MacroBlock) -> Result<(), BlockError> {
+        let head = self.head();
+
+        if proposal.header.parent_hash != self.head_hash() {
+            warn!(
+                reason = "Proposal doesn't extend our head",
+                "Rejecting macro block proposal"
+            );
+            return Err(BlockError::InvalidParentHash);
+        }
+
+        // Get the intended proposer of the block on top of our head.
+        let proposer_slot = self
+            .get_proposer_at(
+                head.block_number() + 1,
+                proposal.header.view_number,
+                head.seed().entropy(),
+                None,
+            )
+            .ok_or(BlockError::UnknownProposer)?;
+
+        let block = Block::Macro(proposal.clone());
+        let header = block.header();
+
+        Self::verify_header_successor(
+            self,
+            &header,
+            &head.header(),
+            &proposer_slot.validator.signing_key,
+            true,
+        )?;
+
+        // Besides invalid blocks, the only error is failing to apply the proposal to our accounts.
+        // In that case its state root can't match ours.
+        let into_block_error = |e: PushError| match e {
+            PushError::InvalidBlock(e) => e,
+            _ => BlockError::AccountsHashMismatch,
+        };
+
+        self.verify_block_body(&header, &block.body(), None, false)
+            .map_err(into_block_error)?;
+
+        let state = self.state();
+        let mut txn = self.write_transaction();
+        let result = self
+            .commit_accounts(
+                state,
+                &block,
+                head.seed().entropy(),
+                head.next_view_number(),
+                &mut txn,
+            )
+            .and_then(|_| self.verify_block_state(state, &block, Some(&txn)));
+        txn.abort();
+
+        result.map(|_| ()).map_err(into_block_error)
+    }
+
+    /// Verifies that the validators committed to in the body of a macro block are the ones we
+    /// select from our current state with the seed of the block. Election blocks must contain the
+    /// next validators, checkpoint blocks must not contain any. The state must be the one right
+    /// before the block, since the selection depends on the stakes in the staking contract.
+    pub fn verify_election_commitment(&self, macro_block: &MacroBlock) -> Result<(), BlockError> {
+        let body = macro_block.body.as_ref().ok_or(BlockError::MissingBody)?;
+
+        let real_validators = if macro_block.is_election_block() {
+            Some(self.next_validators(&macro_block.header.seed))
+        } else {
+            None
+        };
+
+        if body.validators != real_validators {
+            warn!(
+                block = %macro_block,
+                reason = "Validators don't match real validators",
+                "Rejecting block"
+            );
+            return Err(BlockError::InvalidValidators);
         }
 
         Ok(())
@@ -153,102 +336,118 @@ impl Blockchain {
     ) -> Result<(), PushError> {
         match block {
             Block::Micro(micro_block) => {
-                // Checks if the justification exists. If yes, unwrap it.
-                let justification = micro_block
-                    .justification
-                    .as_ref()
-                    .ok_or(PushError::InvalidBlock(BlockError::NoJustification))?;
-
-                if check_signature {
-                    // Verify the signature on the justification.
-                    let hash = block.hash();
-                    if !signing_key.verify(&justification.signature, hash.as_slice()) {
-                        warn!(
-                            %block,
-                            %signing_key,
-                            reason = "Invalid signature for slot owner",
-                            "Rejecting block"
-                        );
-                        return Err(PushError::InvalidBlock(BlockError::InvalidJustification));
-                    }
-                }
-
-                // Check if a view change occurred - if so, validate the proof
                 let prev_info = blockchain
                     .get_chain_info(block.parent_hash(), false, txn_opt)
                     .unwrap();
 
-                let next_view_number = prev_info.head.next_view_number();
-                let view_number = block.view_number();
-
-                if view_number < next_view_number {
-                    warn!(
-                        %block,
-                        view_number = view_number,
-                        next_view_number = next_view_number,
-                        reason = "Decreasing view number",
-                        "Rejecting block"
-                    );
-                    return Err(PushError::InvalidBlock(BlockError::InvalidViewNumber));
-                } else if view_number == next_view_number
-                    && justification.view_change_proof.is_some()
-                {
-                    warn!(
-                        %block,
-                        reason = "Must not contain view change proof",
-                        "Rejecting block"
-                    );
-                    return Err(PushError::InvalidBlock(BlockError::InvalidJustification));
-                } else if view_number > next_view_number
-                    && justification.view_change_proof.is_none()
-                {
-                    warn!(
-                        %block,
-                        view_number = view_number,
-                        next_view_number = next_view_number,
-                        reason = "Missing view change proof", "Rejecting block");
-                    return Err(PushError::InvalidBlock(BlockError::NoViewChangeProof));
-                } else if view_number > next_view_number
-                    && justification.view_change_proof.is_some()
-                {
-                    let view_change = ViewChange {
-                        block_number: block.block_number(),
-                        new_view_number: block.view_number(),
-                        vrf_entropy: prev_info.head.seed().entropy(),
-                    };
-
-                    if !justification
-                        .view_change_proof
-                        .as_ref()
-                        .unwrap()
-                        .verify(&view_change, &blockchain.current_validators().unwrap())
-                    {
-                        warn!(
-                              %block,
-                              reason = "Bad view change proof",
-                              "Rejecting block");
-                        return Err(PushError::InvalidBlock(BlockError::InvalidViewChangeProof));
-                    }
-                }
+                Self::verify_micro_justification(
+                    blockchain,
+                    &block.header(),
+                    micro_block.justification.as_ref(),
+                    &prev_info.head.header(),
+                    signing_key,
+                    check_signature,
+                )
             }
             Block::Macro(macro_block) => {
-                // Verify the Tendermint proof.
-                if check_signature
-                    && !TendermintProof::verify(
-                        macro_block,
-                        &blockchain.current_validators().unwrap(),
-                    )
-                {
-                    warn!(
-                        %block,
-                        reason = "Macro block with bad justification",
-                        "Rejecting block"
-                    );
-                    return Err(PushError::InvalidBlock(BlockError::InvalidJustification));
-                }
+                Self::verify_macro_justification(blockchain, macro_block, check_signature)
+            }
+        }
+        .map_err(PushError::InvalidBlock)
+    }
+
+    /// Verifies the justification of a micro block against the header of its immediate
+    /// predecessor.
+    fn verify_micro_justification<B: AbstractBlockchain>(
+        blockchain: &B,
+        header: &BlockHeader,
+        justification: Option<&MicroJustification>,
+        prev: &BlockHeader,
+        signing_key: &SchnorrPublicKey,
+        check_signature: bool,
+    ) -> Result<(), BlockError> {
+        // Checks if the justification exists. If yes, unwrap it.
+        let justification = justification.ok_or(BlockError::NoJustification)?;
+
+        if check_signature {
+            // Verify the signature on the justification.
+            let hash = header.hash();
+            if !signing_key.verify(&justification.signature, hash.as_slice()) {
+                warn!(
+                    %header,
+                    %signing_key,
+                    reason = "Invalid signature for slot owner",
+                    "Rejecting block"
+                );
+                return Err(BlockError::InvalidJustification);
             }
         }
 
+        // Check if a view change occurred - if so, validate the proof
+        let next_view_number = prev.next_view_number();
+        let view_number = header.view_number();
+
+        if view_number < next_view_number {
+            warn!(
+                %header,
+                view_number = view_number,
+                next_view_number = next_view_number,
+                reason = "Decreasing view number",
+                "Rejecting block"
+            );
+            return Err(BlockError::InvalidViewNumber);
+        } else if view_number == next_view_number && justification.view_change_proof.is_some() {
+            warn!(
+                %header,
+                reason = "Must not contain view change proof",
+                "Rejecting block"
+            );
+            return Err(BlockError::InvalidJustification);
+        } else if view_number > next_view_number && justification.view_change_proof.is_none() {
+            warn!(
+                %header,
+                view_number = view_number,
+                next_view_number = next_view_number,
+                reason = "Missing view change proof", "Rejecting block");
+            return Err(BlockError::NoViewChangeProof);
+        } else if view_number > next_view_number && justification.view_change_proof.is_some() {
+            let view_change =
+                view_change_message(header.block_number(), header.view_number(), prev.seed());
+
+            if !justification
+                .view_change_proof
+                .as_ref()
+                .unwrap()
+                .verify(&view_change, &blockchain.current_validators().unwrap())
+            {
+                warn!(
+                      %header,
+                      reason = "Bad view change proof",
+                      "Rejecting block");
+                return Err(BlockError::InvalidViewChangeProof);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the Tendermint proof in the justification of a macro block.
+    fn verify_macro_justification<B: AbstractBlockchain>(
+        blockchain: &B,
+        macro_block: &MacroBlock,
+        check_signature: bool,
+    ) -> Result<(), BlockError> {
+        if check_signature
+            && !TendermintProof::verify(macro_block, &blockchain.current_validators().unwrap())
+        {
+            warn!(
+                %macro_block,
+                reason = "Macro block with bad justification",
+                "Rejecting block"
+            );
+            return Err(BlockError::InvalidJustification);
+        }
+
         Ok(())
     }
 
@@ -296,6 +495,17 @@ impl Blockchain {
                     return Err(PushError::InvalidBlock(BlockError::BodyHashMismatch));
                 }
 
+                // Check the number of fork proofs.
+                if body.fork_proofs.len() > policy::MAX_FORK_PROOFS_PER_BLOCK {
+                    warn!(
+                        %header,
+                        num_fork_proofs = body.fork_proofs.len(),
+                        reason = "Too many fork proofs",
+                        "Rejecting block"
+                    );
+                    return Err(PushError::InvalidBlock(BlockError::TooManyForkProofs));
+                }
+
                 // Validate the fork proofs.
                 let mut previous_proof: Option<&ForkProof> = None;
 
@@ -349,6 +559,27 @@ impl Blockchain {
                     }
                 }
 
+                // Verify all transactions that aren't known to be valid, their signatures are
+                // verified in a single batch.
+                if verify_txns {
+                    let unknown_txs: Vec<&Transaction> = body
+                        .transactions
+                        .iter()
+                        .filter(|tx| !self.tx_verification_cache.is_known(&tx.hash()))
+                        .collect();
+
+                    if let Err((index, e)) = Transaction::verify_all(&unknown_txs, self.network_id)
+                    {
+                        warn!(
+                            %header,
+                            tx_hash = %unknown_txs[index].hash::<Blake2bHash>(),
+                            reason = "invalid transaction",
+                            "Rejecting block, transaction_error={:?}", e
+                        );
+                        return Err(PushError::InvalidBlock(BlockError::InvalidTransaction(e)));
+                    }
+                }
+
                 // Verify transactions.
                 let mut previous_tx: Option<&Transaction> = None;
 
@@ -375,13 +606,6 @@ impl Blockchain {
                         return Err(PushError::InvalidBlock(BlockError::ExpiredTransaction));
                     }
 
-                    if verify_txns && !self.tx_verification_cache.is_known(&tx.hash()) {
-                        // Check intrinsic transaction invariants.
-                        if let Err(e) = tx.verify(self.network_id) {
-                            return Err(PushError::InvalidBlock(BlockError::InvalidTransaction(e)));
-                        }
-                    }
-
                     previous_tx = Some(tx);
                 }
             }
@@ -492,13 +716,6 @@ impl Blockchain {
 
             let real_disabled_slots = staking_contract.previous_disabled_slots();
 
-            // Get the validators.
-            let real_validators = if macro_block.is_election_block() {
-                Some(self.next_validators(&macro_block.header.seed))
-            } else {
-                None
-            };
-
             // Check the real values against the block.
             if let Some(body) = &macro_block.body {
                 // If we were given a body, then check each value against the corresponding value in
@@ -521,20 +738,20 @@ impl Blockchain {
                     return Err(PushError::InvalidBlock(BlockError::InvalidValidators));
                 }
 
-                if real_validators != body.validators {
-                    warn!(
-                        %block,
-                        reason = "Validators don't match real validators",
-                        "Rejecting block"
-                    );
-                    return Err(PushError::InvalidBlock(BlockError::InvalidValidators));
-                }
+                self.verify_election_commitment(macro_block)
+                    .map_err(PushError::InvalidBlock)?;
 
                 // We don't need to check the nano_zkp_hash here since it was already checked in the
                 // `verify_block_body` method.
             } else {
                 // If we were not given a body, then we construct a body from our values and check
                 // its hash against the block header.
+                let real_validators = if macro_block.is_election_block() {
+                    Some(self.next_validators(&macro_block.header.seed))
+                } else {
+                    None
+                };
+
                 let real_pk_tree_root = real_validators.as_ref().map(MacroBlock::pk_tree_root);
 
                 let real_body = MacroBody {
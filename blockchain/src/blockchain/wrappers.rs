@@ -1,5 +1,5 @@
 use nimiq_account::{Account, StakingContract};
-use nimiq_block::Block;
+use nimiq_block::{Block, MacroBlock};
 use nimiq_database::Transaction;
 use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
@@ -9,7 +9,8 @@ use nimiq_utils::observer::{Listener, ListenerHandle};
 use crate::blockchain_state::BlockchainState;
 #[cfg(feature = "metrics")]
 use crate::chain_metrics::BlockchainMetrics;
-use crate::{AbstractBlockchain, Blockchain, BlockchainEvent, Direction};
+use crate::chain_store::MAX_EPOCHS_STORED;
+use crate::{AbstractBlockchain, Blockchain, BlockchainError, BlockchainEvent, Direction};
 use nimiq_trie::key_nibbles::KeyNibbles;
 
 /// Implements several wrapper functions.
@@ -52,6 +53,59 @@ impl Blockchain {
         )
     }
 
+    /// Fetches up to `count` macro blocks of the main chain, starting with the first macro block of
+    /// the epoch `start_epoch`. If `election_only` is set, only election blocks are returned, i.e.
+    /// one block per epoch. The count is capped at `policy::MAX_MACRO_BLOCKS_PER_REQUEST` and fewer
+    /// blocks are returned if the chain ends earlier.
+    pub fn get_macro_blocks_from_epoch(
+        &self,
+        start_epoch: u32,
+        count: u32,
+        election_only: bool,
+    ) -> Vec<MacroBlock> {
+        let count = count.min(policy::MAX_MACRO_BLOCKS_PER_REQUEST);
+        let txn = self.read_transaction();
+
+        // The first macro block of an epoch is the election block if we skip checkpoints,
+        // otherwise it's the first checkpoint after the previous election block. Epoch 0 only
+        // consists of the genesis block.
+        let first_block_number = if election_only || start_epoch == 0 {
+            policy::checked_election_block_of(start_epoch)
+        } else {
+            policy::checked_election_block_of(start_epoch - 1)
+                .and_then(policy::checked_macro_block_after)
+        };
+
+        // Epochs past the last representable block number don't have any blocks.
+        let mut block_number = match first_block_number {
+            Some(block_number) => block_number,
+            None => return vec![],
+        };
+
+        let mut blocks = Vec::new();
+        while (blocks.len() as u32) < count {
+            match self
+                .chain_store
+                .get_block_at(block_number, true, Some(&txn))
+            {
+                Some(Block::Macro(block)) => blocks.push(block),
+                _ => break,
+            }
+
+            let next_block_number = if election_only {
+                policy::checked_election_block_after(block_number)
+            } else {
+                policy::checked_macro_block_after(block_number)
+            };
+            block_number = match next_block_number {
+                Some(block_number) => block_number,
+                None => break,
+            };
+        }
+
+        blocks
+    }
+
     /// Returns the current staking contract.
     pub fn get_staking_contract(&self) -> StakingContract {
         let staking_contract_address = StakingContract::get_key_staking_contract();
@@ -64,6 +118,30 @@ impl Blockchain {
         }
     }
 
+    /// Removes the bodies of all micro blocks before the given block number, keeping their headers.
+    /// This is meant for non-archival nodes, which don't need the bodies once the micro blocks are
+    /// finalized by a macro block. The given block number must not be after the last macro block,
+    /// otherwise unfinalized blocks would be pruned.
+    pub fn prune_micro_blocks_before(&self, block_number: u32) -> Result<(), BlockchainError> {
+        let macro_block_number = self.macro_head().header.block_number;
+        if block_number > macro_block_number {
+            return Err(BlockchainError::PruningUnfinalizedBlocks(block_number));
+        }
+
+        // The epochs before the last `MAX_EPOCHS_STORED` ones are removed completely when an
+        // election block is pushed, so we only need to start after the last election block of
+        // those epochs.
+        let election_epoch = policy::epoch_at(self.election_head().header.block_number);
+        let start = policy::election_block_of(election_epoch.saturating_sub(MAX_EPOCHS_STORED)) + 1;
+
+        let mut txn = self.write_transaction();
+        self.chain_store
+            .prune_micro_bodies(start, block_number, &mut txn);
+        txn.commit();
+
+        Ok(())
+    }
+
     pub fn register_listener<T: Listener<BlockchainEvent> + 'static>(
         &mut self,
         listener: T,
@@ -88,6 +166,13 @@ impl Blockchain {
         self.state.accounts.get(&key, None)
     }
 
+    /// Checks if a transaction with this hash is included in the main chain. The history only
+    /// contains the transactions of the main chain, so transactions of blocks that were reverted
+    /// (e.g. during a rebranch) aren't included anymore.
+    pub fn contains_transaction(&self, tx_hash: &Blake2bHash) -> bool {
+        self.history_store.contains_tx_hash(tx_hash, None)
+    }
+
     /// Checks if we have seen some transaction with this hash inside the a validity window.
     pub fn tx_in_validity_window(
         &self,
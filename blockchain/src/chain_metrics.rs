@@ -1,5 +1,8 @@
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+use parking_lot::Mutex;
+
 use crate::{PushError, PushResult};
 
 #[derive(Default)]
@@ -11,6 +14,9 @@ pub struct BlockchainMetrics {
     block_rebranched_count: AtomicUsize,
     block_forked_count: AtomicUsize,
     block_ignored_count: AtomicUsize,
+    reorg_count: AtomicUsize,
+    // The number of reorgs by depth, i.e. by the number of reverted blocks.
+    reorg_depths: Mutex<BTreeMap<usize, usize>>,
 }
 
 impl BlockchainMetrics {
@@ -96,4 +102,27 @@ impl BlockchainMetrics {
     pub fn block_forked_count(&self) -> usize {
         self.block_forked_count.load(Ordering::Acquire)
     }
+
+    /// Records a reorg that reverted `depth` blocks of the main chain.
+    #[inline]
+    pub fn note_reorg(&self, depth: usize) {
+        self.reorg_count.fetch_add(1, Ordering::Release);
+        *self.reorg_depths.lock().entry(depth).or_insert(0) += 1;
+    }
+
+    #[inline]
+    pub fn reorg_count(&self) -> usize {
+        self.reorg_count.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of reorgs that reverted exactly `depth` blocks.
+    #[inline]
+    pub fn reorg_depth_count(&self, depth: usize) -> usize {
+        self.reorg_depths.lock().get(&depth).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of reorgs by depth.
+    pub fn reorg_depths(&self) -> BTreeMap<usize, usize> {
+        self.reorg_depths.lock().clone()
+    }
 }
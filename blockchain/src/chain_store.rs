@@ -470,6 +470,35 @@ impl ChainStore {
         }
     }
 
+    /// Removes the bodies of all micro blocks (on any chain) from `start` up to, but excluding,
+    /// `end`. The chain infos, which contain the block headers, are kept so that they can still be
+    /// used for proofs. Macro blocks are never pruned.
+    pub fn prune_micro_bodies(&self, start: u32, end: u32, txn: &mut WriteTransaction) {
+        for height in start..end {
+            if policy::is_macro_block_at(height) {
+                continue;
+            }
+
+            // Collect the hashes of all blocks at this height.
+            let mut hashes = vec![];
+            {
+                let mut cursor = txn.cursor(&self.height_idx);
+                let mut hash_opt = cursor.seek_key::<u32, Blake2bHash>(&height);
+
+                while let Some(hash) = hash_opt {
+                    hashes.push(hash);
+                    hash_opt = cursor
+                        .next_duplicate::<u32, Blake2bHash>()
+                        .map(|(_, hash)| hash);
+                }
+            }
+
+            for hash in hashes {
+                txn.remove(&self.block_db, &hash);
+            }
+        }
+    }
+
     pub fn put_receipts(&self, txn: &mut WriteTransaction, block_height: u32, receipts: &Receipts) {
         txn.put_reserve(&self.receipt_db, &block_height, receipts);
     }
@@ -491,6 +520,21 @@ impl ChainStore {
         txn.get(&self.receipt_db, &block_height)
     }
 
+    /// Removes the receipts of all blocks below the given block height.
+    pub fn prune_receipts(&self, txn: &mut WriteTransaction, min_block_height: u32) {
+        let mut cursor = txn.write_cursor(&self.receipt_db);
+        let mut pos: Option<(u32, Receipts)> = cursor.first();
+
+        // The receipts are keyed by block height, so we can stop at the first one we keep.
+        while let Some((block_height, _)) = pos {
+            if block_height >= min_block_height {
+                break;
+            }
+            cursor.remove();
+            pos = cursor.next();
+        }
+    }
+
     pub fn clear_receipts(&self, txn: &mut WriteTransaction) {
         let mut cursor = txn.write_cursor(&self.receipt_db);
         let mut pos: Option<(u32, Receipts)> = cursor.first();
@@ -4,6 +4,7 @@ use nimiq_account::AccountError;
 use nimiq_block::{Block, BlockError, ForkProof};
 use nimiq_hash::Blake2bHash;
 use nimiq_primitives::networks::NetworkId;
+use nimiq_transaction::Transaction;
 
 /// An enum used when a fork is detected.
 #[derive(Clone, Debug)]
@@ -11,6 +12,50 @@ pub enum ForkEvent {
     Detected(ForkProof),
 }
 
+/// Details about a rebranch of the main chain, so that subscribers (e.g. wallets) can find out
+/// which blocks, and thus which transactions, were affected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReorgInfo {
+    /// The hash of the newest block that the old and the new main chain have in common.
+    pub common_ancestor: Blake2bHash,
+    /// The blocks that were removed from the main chain, ordered from oldest to newest.
+    pub reverted_blocks: Vec<(Blake2bHash, Block)>,
+    /// The blocks that were added to the main chain, ordered from oldest to newest.
+    pub applied_blocks: Vec<(Blake2bHash, Block)>,
+}
+
+impl ReorgInfo {
+    /// Returns the transactions of the reverted blocks.
+    pub fn reverted_transactions(&self) -> Vec<&Transaction> {
+        Self::transactions(&self.reverted_blocks)
+    }
+
+    /// Returns the transactions of the applied blocks.
+    pub fn applied_transactions(&self) -> Vec<&Transaction> {
+        Self::transactions(&self.applied_blocks)
+    }
+
+    fn transactions(blocks: &[(Blake2bHash, Block)]) -> Vec<&Transaction> {
+        blocks
+            .iter()
+            .filter_map(|(_, block)| block.transactions())
+            .flatten()
+            .collect()
+    }
+}
+
+/// A summary of a new head of the main chain, as sent to the subscribers of
+/// `Blockchain::subscribe_heads`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeadInfo {
+    pub hash: Blake2bHash,
+    pub block_number: u32,
+    pub view_number: u32,
+    pub timestamp: u64,
+    /// Whether the head was adopted through a rebranch.
+    pub rebranched: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BlockchainEvent {
     Extended(Blake2bHash),
@@ -41,12 +86,16 @@ impl BlockchainEvent {
 pub enum BlockchainError {
     #[error("Invalid genesis block stored. Are you on the right network?")]
     InvalidGenesisBlock,
+    #[error("Unsupported genesis block version: {0}")]
+    UnsupportedGenesisVersion(u16),
     #[error("Failed to load the main chain. Reset your consensus database.")]
     FailedLoadingMainChain,
     #[error("Inconsistent chain/accounts state. Reset your consensus database.")]
     InconsistentState,
     #[error("No network for: {:?}", _0)]
     NoNetwork(NetworkId),
+    #[error("Can't prune blocks after the last finalized macro block: {0}")]
+    PruningUnfinalizedBlocks(u32),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -76,6 +125,10 @@ pub enum PushError {
     AccountsError(#[from] AccountError),
     #[error("Invalid fork")]
     InvalidFork,
+    #[error("Invalid revert: can't revert past the last macro block")]
+    InvalidRevert,
+    #[error("Can't revert beyond the revert window: receipts were pruned")]
+    BeyondRevertWindow,
     #[error("Blockchain error: {0}")]
     BlockchainError(#[from] BlockchainError),
 }
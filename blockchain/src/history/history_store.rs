@@ -4,7 +4,7 @@ use std::collections::{HashSet, VecDeque};
 use nimiq_account::InherentType;
 use nimiq_database::cursor::{ReadCursor, WriteCursor};
 use nimiq_database::{
-    Database, DatabaseFlags, Environment, ReadTransaction, Transaction, WriteTransaction,
+    Cursor, Database, DatabaseFlags, Environment, ReadTransaction, Transaction, WriteTransaction,
 };
 use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
@@ -365,6 +365,28 @@ impl HistoryStore {
         ext_txs
     }
 
+    /// Returns true if the history contains a transaction with the given hash. This only looks up
+    /// the transaction hash index, without loading the extended transactions.
+    pub fn contains_tx_hash(
+        &self,
+        tx_hash: &Blake2bHash,
+        txn_option: Option<&Transaction>,
+    ) -> bool {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        let mut cursor = txn.cursor(&self.tx_hash_db);
+        cursor
+            .seek_key::<Blake2bHash, OrderedHash>(tx_hash)
+            .is_some()
+    }
+
     /// Gets all extended transactions for a given block number.
     /// This method returns the transactions in the same order that they appear in the block.
     pub fn get_block_transactions(
@@ -436,6 +458,30 @@ impl HistoryStore {
         ext_txs
     }
 
+    /// Returns an iterator over all extended transactions of a given epoch, in the order in which
+    /// they were added to the history tree. Unlike `get_epoch_transactions`, the transactions are
+    /// fetched lazily, block by block, by seeking to the start of the epoch in the database.
+    pub fn iter_epoch<'txn, 'env>(
+        &'txn self,
+        epoch_number: u32,
+        txn: &'txn Transaction<'env>,
+    ) -> EpochHistoryIter<'txn, 'env> {
+        EpochHistoryIter {
+            store: self,
+            txn,
+            tree: MerkleMountainRange::new(MMRStore::with_read_transaction(
+                &self.hist_tree_db,
+                txn,
+                epoch_number,
+            )),
+            cursor: txn.cursor(&self.last_leaf_db),
+            epoch_number,
+            started: false,
+            next_leaf: 0,
+            end_leaf: 0,
+        }
+    }
+
     /// Returns the number of extended transactions for a given epoch.
     pub fn num_epoch_transactions(
         &self,
@@ -967,6 +1013,60 @@ impl HistoryStore {
     }
 }
 
+/// An iterator over the extended transactions of an epoch. It is created by
+/// `HistoryStore::iter_epoch`.
+pub struct EpochHistoryIter<'txn, 'env> {
+    store: &'txn HistoryStore,
+    txn: &'txn Transaction<'env>,
+    tree: MerkleMountainRange<Blake2bHash, MMRStore<'txn, 'env>>,
+    // A cursor over the last leaf index of each block.
+    cursor: Cursor<'txn>,
+    epoch_number: u32,
+    started: bool,
+    // The range of leaf indexes of the current block that haven't been returned yet.
+    next_leaf: u32,
+    end_leaf: u32,
+}
+
+impl<'txn, 'env> Iterator for EpochHistoryIter<'txn, 'env> {
+    type Item = ExtendedTransaction;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Move to the next block with transactions if we are done with the current one.
+        while self.next_leaf >= self.end_leaf {
+            let (block_number, last_leaf) = if self.started {
+                self.cursor.next::<u32, u32>()?
+            } else {
+                self.started = true;
+
+                // We need to convert the block number to big-endian since that's how the LMDB
+                // database orders the keys.
+                let first_block = if self.epoch_number == 0 {
+                    0
+                } else {
+                    policy::first_block_of(self.epoch_number)
+                };
+
+                self.cursor
+                    .seek_range_key::<u32, u32>(&first_block.to_be())?
+            };
+
+            // Stop once we reach a block of the next epoch.
+            if policy::epoch_at(block_number.to_be()) != self.epoch_number {
+                self.end_leaf = 0;
+                return None;
+            }
+
+            self.end_leaf = last_leaf + 1;
+        }
+
+        let leaf_hash = self.tree.get_leaf(self.next_leaf as usize)?;
+        self.next_leaf += 1;
+
+        self.store.get_extended_tx(&leaf_hash, Some(self.txn))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use nimiq_account::{Inherent, InherentType};
@@ -1259,6 +1359,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn iter_epoch_works() {
+        // Initialize History Store.
+        let env = VolatileEnvironment::new(10).unwrap();
+        let history_store = HistoryStore::new(env.clone());
+
+        // Create extended transactions for two epochs.
+        let next_epoch_block = policy::BLOCKS_PER_EPOCH + 1;
+        let epoch_1 = vec![
+            create_transaction(1, 0),
+            create_inherent(1, 1),
+            create_transaction(3, 2),
+        ];
+        let epoch_2 = vec![
+            create_transaction(next_epoch_block, 3),
+            create_transaction(next_epoch_block + 2, 4),
+            create_inherent(next_epoch_block + 2, 5),
+        ];
+
+        // Add extended transactions to History Store.
+        let mut txn = WriteTransaction::new(&env);
+        history_store.add_to_history(&mut txn, 1, &epoch_1);
+        history_store.add_to_history(&mut txn, 2, &epoch_2);
+
+        // Verify method works.
+        let query: Vec<_> = history_store.iter_epoch(1, &txn).collect();
+        assert_eq!(query, epoch_1);
+
+        let query: Vec<_> = history_store.iter_epoch(2, &txn).collect();
+        assert_eq!(query, epoch_2);
+
+        assert_eq!(
+            history_store.iter_epoch(2, &txn).count(),
+            history_store.num_epoch_transactions(2, Some(&txn))
+        );
+
+        assert_eq!(history_store.iter_epoch(3, &txn).next(), None);
+    }
+
     #[test]
     fn get_num_extended_transactions_works() {
         // Initialize History Store.
@@ -1,4 +1,3 @@
-use beserial::Serialize;
 use nimiq_account::{Inherent, InherentType};
 use nimiq_block::MacroHeader;
 use nimiq_blockchain::Blockchain;
@@ -69,12 +68,7 @@ fn it_can_create_batch_finalization_inherents() {
         validator_address: validator_address.clone(),
         event_block: 0,
     };
-    let slash_inherent = Inherent {
-        ty: InherentType::Slash,
-        target: staking_contract_address.clone(),
-        value: Coin::ZERO,
-        data: slot.serialize_to_vec(),
-    };
+    let slash_inherent = Inherent::slash(&slot);
 
     let mut txn = blockchain.write_transaction();
     // adds slot 0 to previous_lost_rewards -> slot won't get reward on next finalize_previous_batch
@@ -1,15 +1,27 @@
 use parking_lot::RwLock;
-use std::sync::Arc;
+use std::str::FromStr;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
 
-use nimiq_block::Block;
+use nimiq_block::{Block, BlockError};
 use nimiq_block_production::{test_utils::TemporaryBlockProducer, BlockProducer};
 use nimiq_blockchain::{AbstractBlockchain, Blockchain};
-use nimiq_blockchain::{ForkEvent, PushResult};
+use nimiq_blockchain::{BlockchainError, ForkEvent, HeadInfo, PushError, PushResult, ReorgInfo};
 use nimiq_database::volatile::VolatileEnvironment;
 use nimiq_genesis::NetworkId;
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::{Address, KeyPair, PrivateKey};
+use nimiq_primitives::coin::Coin;
 use nimiq_primitives::policy;
+use nimiq_primitives::slots::{ValidatorSetDiff, ValidatorsBuilder};
 use nimiq_test_log::test;
-use nimiq_test_utils::blockchain::{sign_view_change, signing_key, voting_key};
+use nimiq_test_utils::blockchain::{
+    generate_transactions, produce_macro_blocks_with_txns, sign_view_change, signing_key,
+    voting_key, UNIT_KEY,
+};
+use nimiq_transaction::Transaction;
+use nimiq_transaction_builder::TransactionBuilder;
 use nimiq_utils::time::OffsetTime;
 
 #[test]
@@ -242,6 +254,465 @@ fn it_can_rebranch_forks() {
     assert_eq!(temp_producer2.push(fork1d), Ok(PushResult::Ignored));
 }
 
+#[test]
+fn it_rejects_rebranches_beyond_the_revert_window() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+    temp_producer1.blockchain.write().set_revert_window(Some(2));
+
+    //              a     b     c
+    // [0] - [0] - [0] - [0] - [0]
+    //          \- [0] - [1]
+    let block = temp_producer1.next_block(0, vec![]);
+    temp_producer2.push(block).unwrap();
+
+    let fork1a = temp_producer1.next_block(0, vec![0x48]);
+    let fork2a = temp_producer2.next_block(0, vec![]);
+
+    temp_producer1.next_block(0, vec![]);
+    let fork2b = temp_producer2.next_block(1, vec![]);
+
+    temp_producer1.next_block(0, vec![]);
+
+    // Only the receipts of the last two blocks are retained.
+    let head = temp_producer1.blockchain.read().head();
+    {
+        let blockchain = temp_producer1.blockchain.read();
+        let chain_store = &blockchain.chain_store;
+        assert!(chain_store
+            .get_receipts(fork1a.block_number(), None)
+            .is_none());
+        assert!(chain_store
+            .get_receipts(head.block_number() - 1, None)
+            .is_some());
+        assert!(chain_store
+            .get_receipts(head.block_number(), None)
+            .is_some());
+    }
+
+    assert_eq!(temp_producer1.push(fork2a), Ok(PushResult::Forked));
+
+    // Rebranching would require reverting three blocks.
+    assert_eq!(
+        temp_producer1.push(fork2b),
+        Err(PushError::BeyondRevertWindow)
+    );
+    assert_eq!(temp_producer1.blockchain.read().head(), head);
+}
+
+#[test]
+fn it_knows_which_transactions_are_on_the_main_chain() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    let key_pair = KeyPair::from(PrivateKey::from_str(UNIT_KEY).unwrap());
+    let tx = generate_transactions(&key_pair, 1, NetworkId::UnitAlbatross, 1, 0)
+        .pop()
+        .unwrap();
+    let tx_hash = tx.hash::<Blake2bHash>();
+
+    let block = {
+        let blockchain = temp_producer1.blockchain.read();
+        temp_producer1.producer.next_micro_block(
+            &blockchain,
+            blockchain.time.now() + 1000,
+            0,
+            None,
+            vec![],
+            vec![tx],
+            vec![],
+        )
+    };
+    assert_eq!(
+        temp_producer1.push(Block::Micro(block)),
+        Ok(PushResult::Extended)
+    );
+
+    assert!(temp_producer1
+        .blockchain
+        .read()
+        .contains_transaction(&tx_hash));
+    assert!(!temp_producer2
+        .blockchain
+        .read()
+        .contains_transaction(&tx_hash));
+
+    // The fork replaces the block that included the transaction.
+    let fork = temp_producer2.next_block(1, vec![]);
+    assert_eq!(temp_producer1.push(fork), Ok(PushResult::Rebranched));
+
+    assert!(!temp_producer1
+        .blockchain
+        .read()
+        .contains_transaction(&tx_hash));
+}
+
+#[test]
+fn it_reports_rebranches() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    let reorg = Arc::new(std::sync::RwLock::new(None));
+    let reorg_listener = reorg.clone();
+    temp_producer1
+        .blockchain
+        .write()
+        .reorg_notifier
+        .register(move |info: &ReorgInfo| {
+            *reorg_listener.write().unwrap() = Some(info.clone());
+        });
+
+    // [0] - [0] - [0] - [0] - [0]
+    //          \- [0] - [0] - [1]
+    let ancestor = temp_producer1.next_block(0, vec![]);
+    temp_producer2.push(ancestor.clone()).unwrap();
+
+    let fork1a = temp_producer1.next_block(0, vec![0x48]);
+    let fork2a = temp_producer2.next_block(0, vec![]);
+
+    let fork1b = temp_producer1.next_block(0, vec![]);
+    let fork2b = temp_producer2.next_block(0, vec![]);
+
+    let fork1c = temp_producer1.next_block(0, vec![]);
+    let fork2c = temp_producer2.next_block(1, vec![]);
+
+    assert_eq!(temp_producer1.push(fork2a.clone()), Ok(PushResult::Forked));
+    assert_eq!(temp_producer1.push(fork2b.clone()), Ok(PushResult::Forked));
+    assert!(reorg.read().unwrap().is_none());
+
+    assert_eq!(
+        temp_producer1.push(fork2c.clone()),
+        Ok(PushResult::Rebranched)
+    );
+
+    let info = reorg.read().unwrap().clone().unwrap();
+    assert_eq!(info.common_ancestor, ancestor.hash());
+    assert_eq!(
+        info.reverted_blocks,
+        vec![
+            (fork1a.hash(), fork1a),
+            (fork1b.hash(), fork1b),
+            (fork1c.hash(), fork1c)
+        ]
+    );
+    assert_eq!(
+        info.applied_blocks,
+        vec![
+            (fork2a.hash(), fork2a),
+            (fork2b.hash(), fork2b),
+            (fork2c.hash(), fork2c)
+        ]
+    );
+    assert!(info.reverted_transactions().is_empty());
+    assert!(info.applied_transactions().is_empty());
+}
+
+#[test]
+#[cfg(feature = "metrics")]
+fn it_records_the_depth_of_rebranches() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    // [0] - [0] - [0] - [0]
+    //    \- [0] - [0] - [1]
+    for _ in 0..3 {
+        temp_producer1.next_block(0, vec![0x48]);
+    }
+    let fork1 = temp_producer2.next_block(0, vec![]);
+    let fork2 = temp_producer2.next_block(0, vec![]);
+    let fork3 = temp_producer2.next_block(1, vec![]);
+
+    assert_eq!(temp_producer1.push(fork1), Ok(PushResult::Forked));
+    assert_eq!(temp_producer1.push(fork2), Ok(PushResult::Forked));
+    assert_eq!(temp_producer1.blockchain.read().metrics().reorg_count(), 0);
+
+    assert_eq!(temp_producer1.push(fork3), Ok(PushResult::Rebranched));
+
+    let blockchain = temp_producer1.blockchain.read();
+    assert_eq!(blockchain.metrics().reorg_count(), 1);
+    assert_eq!(blockchain.metrics().reorg_depth_count(3), 1);
+    assert_eq!(
+        blockchain
+            .metrics()
+            .reorg_depths()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![(3, 1)]
+    );
+}
+
+#[test]
+fn it_notifies_head_subscribers() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    let mut heads = temp_producer1.blockchain.read().subscribe_heads();
+    let head_info = |block: &Block, rebranched: bool| HeadInfo {
+        hash: block.hash(),
+        block_number: block.block_number(),
+        view_number: block.view_number(),
+        timestamp: block.timestamp(),
+        rebranched,
+    };
+
+    // [0] - [0] - [0]
+    //          \- [1]
+    let ancestor = temp_producer1.next_block(0, vec![]);
+    temp_producer2.push(ancestor.clone()).unwrap();
+    assert_eq!(heads.try_recv().unwrap(), head_info(&ancestor, false));
+
+    let block = temp_producer1.next_block(0, vec![]);
+    assert_eq!(heads.try_recv().unwrap(), head_info(&block, false));
+
+    let fork = temp_producer2.next_block(1, vec![]);
+    assert_eq!(
+        temp_producer1.push(fork.clone()),
+        Ok(PushResult::Rebranched)
+    );
+    assert_eq!(heads.try_recv().unwrap(), head_info(&fork, true));
+
+    // Known blocks don't produce a new head.
+    assert_eq!(temp_producer1.push(fork), Ok(PushResult::Known));
+    assert_eq!(temp_producer1.push(block), Ok(PushResult::Known));
+    assert!(heads.try_recv().is_err());
+}
+
+#[test]
+fn it_can_compute_validator_set_diffs() {
+    let temp_producer = TemporaryBlockProducer::new();
+    for _ in 0..policy::BLOCKS_PER_EPOCH {
+        temp_producer.next_block(0, vec![]);
+    }
+
+    // The only validator stays in the set.
+    let blockchain = temp_producer.blockchain.read();
+    let diff = blockchain.validator_set_diff(1, 2).unwrap();
+    assert!(diff.is_empty());
+
+    // The validators of future epochs are unknown.
+    assert_eq!(blockchain.validator_set_diff(2, 3), None);
+
+    // One validator joins, one leaves and one gains stake.
+    let validators = |slots: &[(u8, u16)]| {
+        let mut builder = ValidatorsBuilder::new();
+        for (address, num_slots) in slots {
+            for _ in 0..*num_slots {
+                builder.push(
+                    Address::from([*address; 20]),
+                    voting_key().public_key,
+                    signing_key().public,
+                );
+            }
+        }
+        builder.build()
+    };
+    let diff = validators(&[(1, 256), (2, 256)]).diff(&validators(&[(2, 312), (3, 200)]));
+    assert_eq!(
+        diff,
+        ValidatorSetDiff {
+            added: vec![Address::from([3u8; 20])],
+            removed: vec![Address::from([1u8; 20])],
+            changed: vec![(Address::from([2u8; 20]), 256, 312)],
+        }
+    );
+}
+
+#[test]
+fn it_computes_validator_set_diffs_for_staking_transactions() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let key_pair = KeyPair::from(PrivateKey::from_str(UNIT_KEY).unwrap());
+    let genesis_validator =
+        Address::from_any_str("NQ20 TSB0 DFSM UH9C 15GQ GAGJ TTE4 D3MA 859E").unwrap();
+    let new_validator_key = KeyPair::from(
+        PrivateKey::from_str("b410a7a583cbc13ef4f1cbddace30928bcb4f9c13722414bc4a2faaba3f4e187")
+            .unwrap(),
+    );
+    let new_validator = Address::from(&new_validator_key);
+    let fee = Coin::from_u64_unchecked(100);
+
+    // The new validator joins and the genesis validator leaves. The new validator uses the same
+    // signing and voting keys, so the temporary producer can still sign the blocks of epoch 2.
+    let validity_start_height = temp_producer.blockchain.read().block_number();
+    push_micro_block_with_transactions(
+        &temp_producer,
+        vec![
+            TransactionBuilder::new_create_validator(
+                &key_pair,
+                &new_validator_key,
+                signing_key().public,
+                &voting_key(),
+                Address::from(&key_pair),
+                None,
+                fee,
+                validity_start_height,
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap(),
+            TransactionBuilder::new_inactivate_validator(
+                &key_pair,
+                genesis_validator.clone(),
+                &signing_key(),
+                fee,
+                validity_start_height,
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap(),
+        ],
+    );
+    produce_blocks_until_election(&temp_producer);
+
+    assert_eq!(
+        temp_producer.blockchain.read().validator_set_diff(1, 2),
+        Some(ValidatorSetDiff {
+            added: vec![new_validator.clone()],
+            removed: vec![genesis_validator.clone()],
+            changed: vec![],
+        })
+    );
+
+    // The genesis validator rejoins and the new validator leaves.
+    let validity_start_height = temp_producer.blockchain.read().block_number();
+    push_micro_block_with_transactions(
+        &temp_producer,
+        vec![
+            TransactionBuilder::new_reactivate_validator(
+                &key_pair,
+                genesis_validator.clone(),
+                &signing_key(),
+                fee,
+                validity_start_height,
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap(),
+            TransactionBuilder::new_inactivate_validator(
+                &key_pair,
+                new_validator.clone(),
+                &signing_key(),
+                fee,
+                validity_start_height,
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap(),
+        ],
+    );
+    produce_blocks_until_election(&temp_producer);
+
+    assert_eq!(
+        temp_producer.blockchain.read().validator_set_diff(2, 3),
+        Some(ValidatorSetDiff {
+            added: vec![genesis_validator],
+            removed: vec![new_validator],
+            changed: vec![],
+        })
+    );
+}
+
+fn push_micro_block_with_transactions(
+    temp_producer: &TemporaryBlockProducer,
+    transactions: Vec<Transaction>,
+) {
+    let num_transactions = transactions.len();
+    let micro_block = {
+        let blockchain = temp_producer.blockchain.read();
+        temp_producer.producer.next_micro_block(
+            &blockchain,
+            blockchain.time.now() + (blockchain.block_number() + 1) as u64 * 1000,
+            0,
+            None,
+            vec![],
+            transactions,
+            vec![0x42],
+        )
+    };
+    assert_eq!(
+        micro_block.body.as_ref().unwrap().transactions.len(),
+        num_transactions
+    );
+    assert_eq!(
+        temp_producer.push(Block::Micro(micro_block)),
+        Ok(PushResult::Extended)
+    );
+}
+
+fn produce_blocks_until_election(temp_producer: &TemporaryBlockProducer) {
+    while !policy::is_election_block_at(temp_producer.blockchain.read().block_number()) {
+        temp_producer.next_block(0, vec![]);
+    }
+}
+
+#[test]
+fn it_validates_macro_block_proposals() {
+    let temp_producer = TemporaryBlockProducer::new();
+    for _ in 0..policy::BLOCKS_PER_BATCH - 1 {
+        temp_producer.next_block(0, vec![]);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let proposal = temp_producer.producer.next_macro_block_proposal(
+        &blockchain,
+        blockchain.time.now() + policy::BLOCKS_PER_BATCH as u64 * 1000,
+        0,
+        vec![],
+    );
+    assert_eq!(blockchain.validate_macro_proposal(&proposal), Ok(()));
+
+    let mut wrong_state_root = proposal.clone();
+    wrong_state_root.header.state_root = Blake2bHash::default();
+    assert_eq!(
+        blockchain.validate_macro_proposal(&wrong_state_root),
+        Err(BlockError::AccountsHashMismatch)
+    );
+
+    let mut wrong_block_number = proposal;
+    wrong_block_number.header.block_number += 1;
+    assert_eq!(
+        blockchain.validate_macro_proposal(&wrong_block_number),
+        Err(BlockError::InvalidBlockNumber)
+    );
+}
+
+#[test]
+fn it_verifies_the_validators_of_election_blocks() {
+    let temp_producer = TemporaryBlockProducer::new();
+    for _ in 0..policy::BLOCKS_PER_EPOCH - 1 {
+        temp_producer.next_block(0, vec![]);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let proposal = temp_producer.producer.next_macro_block_proposal(
+        &blockchain,
+        blockchain.time.now() + policy::BLOCKS_PER_EPOCH as u64 * 1000,
+        0,
+        vec![],
+    );
+    assert!(proposal.is_election_block());
+    assert_eq!(blockchain.verify_election_commitment(&proposal), Ok(()));
+
+    // All slots are given to a validator that isn't staking.
+    let mut tampered = proposal.clone();
+    let mut builder = ValidatorsBuilder::new();
+    for _ in 0..policy::SLOTS {
+        builder.push(
+            Address::from([1u8; 20]),
+            voting_key().public_key,
+            signing_key().public,
+        );
+    }
+    tampered.body.as_mut().unwrap().validators = Some(builder.build());
+    assert_eq!(
+        blockchain.verify_election_commitment(&tampered),
+        Err(BlockError::InvalidValidators)
+    );
+
+    // Election blocks must commit to the validators.
+    let mut missing = proposal;
+    missing.body.as_mut().unwrap().validators = None;
+    assert_eq!(
+        blockchain.verify_election_commitment(&missing),
+        Err(BlockError::InvalidValidators)
+    );
+}
+
 #[test]
 fn it_can_rebranch_at_macro_block() {
     // Build forks using two producers.
@@ -347,3 +818,226 @@ fn create_fork_proof() {
     // Verify that the fork proof was generated
     assert!(*event1_rc1.read().unwrap());
 }
+
+#[test]
+fn it_can_prune_micro_block_bodies() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    // Produce a full batch, including its macro block.
+    let mut blocks = vec![];
+    for _ in 0..policy::BLOCKS_PER_BATCH {
+        blocks.push(temp_producer.next_block(0, vec![]));
+    }
+
+    // Produce a micro block in the next batch, which is not finalized yet.
+    let unfinalized_block = temp_producer.next_block(0, vec![]);
+
+    let blockchain = temp_producer.blockchain.read();
+    let macro_block_number = policy::macro_block_of(1);
+    assert_eq!(
+        blockchain.macro_head().header.block_number,
+        macro_block_number
+    );
+
+    // It refuses to prune unfinalized blocks.
+    assert!(blockchain
+        .prune_micro_blocks_before(unfinalized_block.block_number())
+        .is_err());
+
+    blockchain
+        .prune_micro_blocks_before(macro_block_number)
+        .unwrap();
+
+    // The micro block bodies are gone, but the headers are still there.
+    for block in blocks.iter().filter(|block| block.is_micro()) {
+        assert_eq!(blockchain.get_block(&block.hash(), true, None), None);
+        assert_eq!(
+            blockchain
+                .get_block(&block.hash(), false, None)
+                .map(|block| block.header()),
+            Some(block.header())
+        );
+    }
+
+    // The macro block and the unfinalized micro block are untouched.
+    let macro_block = blocks.last().unwrap();
+    assert!(macro_block.is_macro());
+    assert_eq!(
+        blockchain.get_block(&macro_block.hash(), true, None),
+        Some(macro_block.clone())
+    );
+    assert_eq!(
+        blockchain.get_block(&unfinalized_block.hash(), true, None),
+        Some(unfinalized_block)
+    );
+}
+
+#[test]
+fn slot_owner_matches_selected_validators() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    for _ in 0..3 {
+        temp_producer.next_block(0, vec![]);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+
+    // The validators of the first epoch are selected from the genesis seed.
+    let election_head = blockchain.election_head();
+    let validators = blockchain.next_validators(&election_head.header.seed);
+    assert_eq!(blockchain.current_validators(), Some(validators.clone()));
+
+    let block_number = blockchain.block_number() + 1;
+    for view_number in 0..3 {
+        let (validator, slot) = blockchain
+            .get_slot_owner_at(block_number, view_number, None)
+            .unwrap();
+
+        assert_eq!(&validator, validators.get_validator_by_slot_number(slot));
+        assert_eq!(
+            validator.signing_key,
+            temp_producer.producer.signing_key.public
+        );
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn push_async_does_not_block_the_executor() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let block = temp_producer.next_block_no_push(0, vec![]);
+
+    // Hold a read lock on the blockchain from another thread. The push can do all of its work,
+    // but it can't upgrade its lock to write the block until the read lock is released.
+    let (locked_tx, locked_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let blockchain = Arc::clone(&temp_producer.blockchain);
+    let reader = thread::spawn(move || {
+        let _guard = blockchain.read();
+        locked_tx.send(()).unwrap();
+        release_rx.recv().unwrap();
+    });
+    locked_rx.recv().unwrap();
+
+    let push = tokio::spawn(Blockchain::push_async(
+        Arc::clone(&temp_producer.blockchain),
+        block,
+    ));
+
+    // While the push is in progress, the single worker thread must still be able to run other
+    // tasks.
+    let other = tokio::spawn(async { 42 });
+    assert_eq!(
+        tokio::time::timeout(Duration::from_secs(10), other)
+            .await
+            .expect("executor is blocked by the push")
+            .unwrap(),
+        42
+    );
+
+    release_tx.send(()).unwrap();
+    reader.join().unwrap();
+
+    assert_eq!(push.await.unwrap(), Ok(PushResult::Extended));
+}
+
+#[test]
+fn it_can_fetch_the_macro_blocks_of_epochs() {
+    let temp_producer = TemporaryBlockProducer::new();
+    for _ in 0..3 * policy::BLOCKS_PER_EPOCH {
+        temp_producer.next_block(0, vec![]);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let block_numbers = |start_epoch, count, election_only| {
+        blockchain
+            .get_macro_blocks_from_epoch(start_epoch, count, election_only)
+            .iter()
+            .map(|block| block.header.block_number)
+            .collect::<Vec<_>>()
+    };
+
+    // The genesis block and one election block per epoch, without any checkpoints.
+    let election_blocks: Vec<_> = (0..=3).map(policy::election_block_of).collect();
+    assert_eq!(block_numbers(0, 10, true), election_blocks);
+    assert_eq!(block_numbers(1, 2, true), election_blocks[1..3]);
+    assert_eq!(block_numbers(4, 10, true), vec![]);
+    assert_eq!(block_numbers(u32::MAX, 10, true), vec![]);
+    assert_eq!(block_numbers(u32::MAX, 10, false), vec![]);
+
+    // All macro blocks, starting with the first checkpoint of the epoch.
+    let macro_blocks: Vec<_> = (1..=3 * policy::BATCHES_PER_EPOCH as u32)
+        .map(policy::macro_block_of)
+        .collect();
+    assert_eq!(block_numbers(1, u32::MAX, false), macro_blocks);
+    assert_eq!(
+        block_numbers(2, 5, false),
+        macro_blocks[policy::BATCHES_PER_EPOCH as usize..][..5]
+    );
+    assert!(blockchain
+        .get_macro_blocks_from_epoch(1, 5, false)
+        .iter()
+        .any(|block| !block.is_election_block()));
+}
+
+#[test]
+fn it_can_rebuild_the_accounts_from_the_blocks() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let time = Arc::new(OffsetTime::new());
+    let blockchain = Arc::new(RwLock::new(
+        Blockchain::new(env.clone(), NetworkId::UnitAlbatross, time).unwrap(),
+    ));
+
+    let producer = BlockProducer::new(signing_key(), voting_key());
+    produce_macro_blocks_with_txns(&producer, &blockchain, 2, 2, 0);
+    for _ in 0..3 {
+        let block = {
+            let blockchain = blockchain.read();
+            let timestamp = blockchain.head().timestamp() + 1000;
+            Block::Micro(producer.next_micro_block(
+                &blockchain,
+                timestamp,
+                0,
+                None,
+                vec![],
+                vec![],
+                vec![0x42],
+            ))
+        };
+        assert_eq!(
+            Blockchain::push(blockchain.upgradable_read(), block),
+            Ok(PushResult::Extended)
+        );
+    }
+
+    let state_root = blockchain.read().head().state_root().clone();
+
+    // Wipe the accounts.
+    {
+        let blockchain = blockchain.read();
+        let tree = &blockchain.state.accounts.tree;
+        let mut txn = blockchain.write_transaction();
+        for (key, _) in tree.get_all(&txn) {
+            tree.remove(&mut txn, &key);
+        }
+        tree.update_root(&mut txn);
+        txn.commit();
+    }
+    drop(blockchain);
+
+    assert!(matches!(
+        Blockchain::new(
+            env.clone(),
+            NetworkId::UnitAlbatross,
+            Arc::new(OffsetTime::new())
+        ),
+        Err(BlockchainError::InconsistentState)
+    ));
+
+    let accounts = Blockchain::rebuild_accounts_from_blocks(
+        VolatileEnvironment::new(10).unwrap(),
+        env,
+        NetworkId::UnitAlbatross,
+    )
+    .unwrap();
+    assert_eq!(accounts.get_root(None), state_root);
+}
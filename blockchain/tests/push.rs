@@ -1,13 +1,18 @@
+use std::str::FromStr;
+
 use nimiq_block::{Block, ViewChangeProof};
 use nimiq_block::{BlockError, MultiSignature};
 use nimiq_block_production::test_custom_block::{next_macro_block, next_micro_block, BlockConfig};
 use nimiq_block_production::test_utils::TemporaryBlockProducer;
 use nimiq_blockchain::PushError::InvalidBlock;
-use nimiq_blockchain::{PushError, PushResult};
+use nimiq_blockchain::{AbstractBlockchain, PushError, PushResult};
 use nimiq_bls::AggregateSignature;
 use nimiq_collections::BitSet;
+use nimiq_genesis::NetworkId;
 use nimiq_hash::Blake2bHash;
+use nimiq_keys::{KeyPair, PrivateKey};
 use nimiq_primitives::policy;
+use nimiq_test_utils::blockchain::{generate_transactions, UNIT_KEY};
 use nimiq_vrf::{VrfEntropy, VrfSeed};
 
 pub fn expect_push_micro_block(config: BlockConfig, expected_res: Result<PushResult, PushError>) {
@@ -483,3 +488,94 @@ fn it_validates_tendermint_round_number() {
         Err(InvalidBlock(BlockError::InvalidJustification)),
     );
 }
+
+#[test]
+fn it_rejects_duplicate_transactions() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    let key_pair = KeyPair::from(PrivateKey::from_str(UNIT_KEY).unwrap());
+    let tx = generate_transactions(&key_pair, 1, NetworkId::UnitAlbatross, 1, 1)
+        .pop()
+        .unwrap();
+
+    // The roots can't be computed for a block with duplicates, the body is rejected before they
+    // are checked anyway.
+    let config = BlockConfig {
+        micro_only: true,
+        transactions: vec![tx.clone(), tx],
+        state_root: Some(Blake2bHash::default()),
+        history_root: Some(Blake2bHash::default()),
+        ..Default::default()
+    };
+
+    let micro_block = {
+        let blockchain = &temp_producer.blockchain.read();
+        next_micro_block(
+            &temp_producer.producer.signing_key,
+            &temp_producer.producer.voting_key,
+            blockchain,
+            &config,
+        )
+    };
+
+    assert_eq!(
+        temp_producer.push(Block::Micro(micro_block)),
+        Err(InvalidBlock(BlockError::DuplicateTransaction))
+    );
+}
+
+fn validate_micro_header(config: &BlockConfig) -> Result<(), BlockError> {
+    let temp_producer = TemporaryBlockProducer::new();
+    temp_producer.next_block(0, vec![]);
+
+    let blockchain = &temp_producer.blockchain.read();
+    let micro_block = Block::Micro(next_micro_block(
+        &temp_producer.producer.signing_key,
+        &temp_producer.producer.voting_key,
+        blockchain,
+        config,
+    ));
+
+    blockchain.validate_header(
+        &micro_block.header(),
+        &micro_block.justification().unwrap(),
+        &blockchain.head().header(),
+    )
+}
+
+#[test]
+fn it_validates_headers_without_body() {
+    assert_eq!(validate_micro_header(&BlockConfig::default()), Ok(()));
+
+    assert_eq!(
+        validate_micro_header(&BlockConfig {
+            missing_body: true,
+            ..Default::default()
+        }),
+        Ok(())
+    );
+
+    assert_eq!(
+        validate_micro_header(&BlockConfig {
+            seed: Some(VrfSeed::default()),
+            ..Default::default()
+        }),
+        Err(BlockError::InvalidSeed)
+    );
+
+    assert_eq!(
+        validate_micro_header(&BlockConfig {
+            block_number_offset: 1,
+            ..Default::default()
+        }),
+        Err(BlockError::InvalidBlockNumber)
+    );
+
+    assert_eq!(
+        validate_micro_header(&BlockConfig {
+            timestamp_offset: -2,
+            ..Default::default()
+        }),
+        Err(BlockError::InvalidTimestamp)
+    );
+}
@@ -1,21 +1,24 @@
 use std::sync::Arc;
 
-use beserial::Deserialize;
+use beserial::{Deserialize, WriteBytesExt};
 use nimiq_block::{
-    MacroBlock, MultiSignature, SignedViewChange, TendermintIdentifier, TendermintProof,
-    TendermintStep, TendermintVote, ViewChange, ViewChangeProof,
+    view_change_message, MacroBlock, Message, MultiSignature, SignedViewChange,
+    TendermintIdentifier, TendermintProof, TendermintStep, TendermintVote, ViewChange,
+    ViewChangeProof, PREFIX_TENDERMINT_COMMIT, PREFIX_TENDERMINT_PREPARE,
+    PREFIX_TENDERMINT_PROPOSAL,
 };
 use nimiq_blockchain::{AbstractBlockchain, Blockchain};
 use nimiq_bls::{lazy::LazyPublicKey, AggregateSignature, KeyPair};
 use nimiq_collections::bitset::BitSet;
 use nimiq_database::volatile::VolatileEnvironment;
 use nimiq_genesis::NetworkId;
+use nimiq_hash::{Blake2sHasher, Hasher, SerializeContent};
 use nimiq_keys::{Address, PublicKey};
 use nimiq_primitives::policy;
 use nimiq_primitives::slots::{Validator, Validators};
 use nimiq_test_log::test;
 use nimiq_utils::time::OffsetTime;
-use nimiq_vrf::VrfEntropy;
+use nimiq_vrf::{VrfEntropy, VrfSeed};
 
 // /// Still in for future reference, in case this key is needed again
 // const SECRET_KEY: &str = "8e44b45f308dae1e2d4390a0f96cea993960d4178550c62aeaba88e9e168d165\
@@ -65,6 +68,29 @@ fn test_view_change_single_signature() {
     assert!(view_change_proof.verify(&view_change, &validators));
 }
 
+#[test]
+/// Tests that a view change signature doesn't verify for a block proposal or a vote with the same
+/// content, since view changes are signed with their own prefix byte.
+fn test_view_change_domain_separation() {
+    let key_pair = KeyPair::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap();
+
+    let view_change = view_change_message(1234, 42, &VrfSeed::default());
+    let signature = view_change.sign(&key_pair.secret_key);
+    assert!(key_pair.verify_hash(view_change.hash_with_prefix(), &signature));
+
+    for prefix in [
+        PREFIX_TENDERMINT_PROPOSAL,
+        PREFIX_TENDERMINT_PREPARE,
+        PREFIX_TENDERMINT_COMMIT,
+    ] {
+        let mut hasher = Blake2sHasher::new();
+        hasher.write_u8(prefix).unwrap();
+        view_change.serialize_content(&mut hasher).unwrap();
+
+        assert!(!key_pair.verify_hash(hasher.finish(), &signature));
+    }
+}
+
 #[test]
 /// Tests if an attacker can use the prepare signature to fake a commit signature. If we would
 /// only sign the `block_hash`, this would work, but `SignedMessage` adds a prefix byte.
@@ -146,3 +172,54 @@ fn test_replay() {
     // verify commit - this should not fail as this time it is the correct round
     assert!(TendermintProof::verify(&block, &validators));
 }
+
+#[test]
+fn it_verifies_macro_blocks_with_a_custom_threshold() {
+    let time = Arc::new(OffsetTime::new());
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross, time).unwrap());
+    let validators = blockchain.current_validators().unwrap();
+
+    let key_pair = KeyPair::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap();
+
+    let mut block = MacroBlock::default();
+    block.header.block_number = 1;
+
+    let vote = TendermintVote {
+        proposal_hash: Some(block.nano_zkp_hash()),
+        id: TendermintIdentifier {
+            block_number: 1u32,
+            step: TendermintStep::PreCommit,
+            round_number: 0,
+        },
+    };
+
+    let threshold = 100u16;
+
+    // Sign the block with the first `num_signers` slots.
+    let mut sign = |num_signers: u16| {
+        let signature = AggregateSignature::from_signatures(&[key_pair
+            .secret_key
+            .sign(&vote)
+            .multiply(num_signers)]);
+
+        let mut signers = BitSet::new();
+        for i in 0..num_signers {
+            signers.insert(i as usize);
+        }
+
+        block.justification = Some(TendermintProof {
+            round: 0,
+            sig: MultiSignature::new(signature, signers),
+        });
+        block.clone()
+    };
+
+    assert!(!sign(threshold - 1).verify_with_threshold(&validators, threshold));
+
+    let block = sign(threshold);
+    assert!(block.verify_with_threshold(&validators, threshold));
+
+    // The consensus threshold is still enforced by the regular verification.
+    assert!(!TendermintProof::verify(&block, &validators));
+}
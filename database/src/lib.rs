@@ -15,6 +15,7 @@ pub use crate::traits::{AsDatabaseBytes, FromDatabaseValue, IntoDatabaseValue};
 #[macro_use]
 pub mod cursor;
 pub mod mdbx;
+pub mod migrations;
 pub mod traits;
 pub mod volatile;
 
@@ -22,6 +23,25 @@ pub mod volatile;
 pub enum Error {
     CreateDirectory(io::Error),
     Mdbx(libmdbx::Error),
+    /// The environment was still locked after the given number of attempts to open it.
+    Locked {
+        attempts: usize,
+    },
+}
+
+impl Error {
+    /// Returns true if the error is caused by a lock that is likely to be released soon, e.g. by
+    /// a process that is shutting down. An environment that is held by another live process
+    /// (`libmdbx::Error::Busy`) is not considered transient.
+    pub fn is_transient_lock_error(&self) -> bool {
+        match self {
+            Error::Mdbx(libmdbx::Error::Other(code)) => matches!(
+                io::Error::from_raw_os_error(*code).kind(),
+                io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted
+            ),
+            _ => false,
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -30,6 +50,11 @@ impl fmt::Display for Error {
         match self {
             CreateDirectory(e) => write!(f, "couldn't create directory for DB: {}", e),
             Mdbx(e) => e.fmt(f),
+            Locked { attempts } => write!(
+                f,
+                "environment is still locked after {} attempts to open it",
+                attempts
+            ),
         }
     }
 }
@@ -37,10 +62,11 @@ impl fmt::Display for Error {
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         use Error::*;
-        Some(match self {
-            CreateDirectory(e) => e,
-            Mdbx(e) => e,
-        })
+        match self {
+            CreateDirectory(e) => Some(e),
+            Mdbx(e) => Some(e),
+            Locked { .. } => None,
+        }
     }
 }
 
@@ -91,6 +117,17 @@ impl Environment {
         }
     }
 
+    /// Flushes all committed write transactions to disk. Unless the environment is `Durable`,
+    /// commits are not (completely) flushed when they return, so this makes them durable at a
+    /// chosen point, e.g. before shutting down. If `force` is false, the data is only flushed if
+    /// the sync mode of the environment requires it.
+    pub fn sync(&self, force: bool) -> Result<(), Error> {
+        match *self {
+            Environment::Volatile(ref env) => env.sync(force),
+            Environment::Persistent(ref env) => env.sync(force),
+        }
+    }
+
     pub fn close(self) {}
 }
 
@@ -186,6 +223,10 @@ impl<'env> Deref for ReadTransaction<'env> {
     }
 }
 
+/// A transaction that can modify the database. Its changes only become visible to other
+/// transactions once it is committed. A write transaction that is dropped without being committed
+/// (e.g. because of an early return or a panic while unwinding) is aborted, so none of its
+/// changes are persisted. This holds for both the volatile and the persistent backend.
 #[derive(Debug)]
 pub struct WriteTransaction<'env>(Transaction<'env>);
 
@@ -291,6 +332,8 @@ impl<'env> WriteTransaction<'env> {
         }
     }
 
+    /// Discards all changes made in this transaction. Dropping the transaction has the same
+    /// effect, this just makes the intent explicit.
     pub fn abort(self) {}
 
     pub fn cursor<'txn, 'db>(&'txn self, db: &'db Database) -> Cursor<'txn> {
@@ -2,6 +2,8 @@ use std::fmt;
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 use libmdbx::{NoWriteMap, Transaction, WriteFlags, RO, RW};
 
@@ -11,6 +13,39 @@ use super::*;
 
 type DbKvPair<'a> = (Cow<'a, [u8]>, Cow<'a, [u8]>);
 
+/// How durably committed write transactions are flushed to disk. Weaker guarantees trade safety
+/// in case of a system crash for a higher write throughput.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Durability {
+    /// Every commit is flushed to disk before it returns.
+    Durable,
+    /// Data is flushed on every commit, but the meta page only lazily. A system crash may roll
+    /// back the last commit.
+    NoMetaSync,
+    /// Commits are not flushed explicitly, that is left to the operating system. A system crash
+    /// may roll back recent commits, but doesn't corrupt the database.
+    SafeNoSync,
+    /// Commits are not flushed explicitly and a system crash may corrupt the database.
+    UtterlyNoSync,
+}
+
+impl Default for Durability {
+    fn default() -> Self {
+        Durability::UtterlyNoSync
+    }
+}
+
+impl From<Durability> for libmdbx::SyncMode {
+    fn from(durability: Durability) -> Self {
+        match durability {
+            Durability::Durable => libmdbx::SyncMode::Durable,
+            Durability::NoMetaSync => libmdbx::SyncMode::NoMetaSync,
+            Durability::SafeNoSync => libmdbx::SyncMode::SafeNoSync,
+            Durability::UtterlyNoSync => libmdbx::SyncMode::UtterlyNoSync,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct MdbxEnvironment {
     env: Arc<libmdbx::Environment<NoWriteMap>>,
@@ -20,7 +55,14 @@ impl MdbxEnvironment {
     #[allow(clippy::new_ret_no_self)]
     pub fn new<P: AsRef<Path>>(path: P, size: usize, max_dbs: u32) -> Result<Environment, Error> {
         Ok(Environment::Persistent(
-            MdbxEnvironment::new_mdbx_environment(path.as_ref(), size, max_dbs, None)?,
+            MdbxEnvironment::new_mdbx_environment(
+                path.as_ref(),
+                size,
+                max_dbs,
+                None,
+                Durability::default(),
+                None,
+            )?,
         ))
     }
 
@@ -32,15 +74,102 @@ impl MdbxEnvironment {
         max_readers: u32,
     ) -> Result<Environment, Error> {
         Ok(Environment::Persistent(
-            MdbxEnvironment::new_mdbx_environment(path.as_ref(), size, max_dbs, Some(max_readers))?,
+            MdbxEnvironment::new_mdbx_environment(
+                path.as_ref(),
+                size,
+                max_dbs,
+                Some(max_readers),
+                Durability::default(),
+                None,
+            )?,
         ))
     }
 
+    /// Opens the environment like `new_with_max_readers`, but flushes commits to disk as
+    /// configured by `durability`.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new_with_durability<P: AsRef<Path>>(
+        path: P,
+        size: usize,
+        max_dbs: u32,
+        max_readers: u32,
+        durability: Durability,
+    ) -> Result<Environment, Error> {
+        Ok(Environment::Persistent(
+            MdbxEnvironment::new_mdbx_environment(
+                path.as_ref(),
+                size,
+                max_dbs,
+                Some(max_readers),
+                durability,
+                None,
+            )?,
+        ))
+    }
+
+    /// Opens the environment like `new_with_durability`, but with database pages of `page_size`
+    /// bytes instead of the default, which is the page size of the operating system (usually
+    /// 4 KiB). The page size must be a power of two between 256 bytes and 64 KiB. It is fixed when
+    /// the database is created and ignored when an existing database is opened.
+    ///
+    /// The default suits the small, random reads and writes of the accounts trie well. Larger
+    /// pages reduce the depth of the B-trees for databases with many large values (e.g. blocks and
+    /// history), at the cost of more bytes written per modified entry.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new_with_page_size<P: AsRef<Path>>(
+        path: P,
+        size: usize,
+        max_dbs: u32,
+        max_readers: u32,
+        durability: Durability,
+        page_size: Option<usize>,
+    ) -> Result<Environment, Error> {
+        Ok(Environment::Persistent(
+            MdbxEnvironment::new_mdbx_environment(
+                path.as_ref(),
+                size,
+                max_dbs,
+                Some(max_readers),
+                durability,
+                page_size,
+            )?,
+        ))
+    }
+
+    /// Opens the environment like `new`, but retries if it is only locked transiently, e.g. by a
+    /// process that is shutting down. It makes up to `attempts` attempts (at least one) and waits
+    /// `backoff` before the first retry, doubling the wait after each further one.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new_with_retry<P: AsRef<Path>>(
+        path: P,
+        size: usize,
+        max_dbs: u32,
+        attempts: usize,
+        backoff: Duration,
+    ) -> Result<Environment, Error> {
+        Ok(Environment::Persistent(retry_on_lock_contention(
+            attempts,
+            backoff,
+            || {
+                MdbxEnvironment::new_mdbx_environment(
+                    path.as_ref(),
+                    size,
+                    max_dbs,
+                    None,
+                    Durability::default(),
+                    None,
+                )
+            },
+        )?))
+    }
+
     pub(super) fn new_mdbx_environment(
         path: &Path,
         size: usize,
         max_dbs: u32,
         max_readers: Option<u32>,
+        durability: Durability,
+        page_size: Option<usize>,
     ) -> Result<Self, Error> {
         fs::create_dir_all(path).map_err(Error::CreateDirectory)?;
 
@@ -49,6 +178,7 @@ impl MdbxEnvironment {
         // Configure the environment flags
         let geo = libmdbx::Geometry::<std::ops::Range<usize>> {
             size: Some(0..size),
+            page_size: page_size.map(libmdbx::PageSize::Set),
             ..Default::default()
         };
 
@@ -57,7 +187,7 @@ impl MdbxEnvironment {
         let db_flags = libmdbx::EnvironmentFlags {
             no_rdahead: true,
             mode: libmdbx::Mode::ReadWrite {
-                sync_mode: libmdbx::SyncMode::UtterlyNoSync,
+                sync_mode: durability.into(),
             },
             ..Default::default()
         };
@@ -111,6 +241,11 @@ impl MdbxEnvironment {
         }
     }
 
+    pub(super) fn sync(&self, force: bool) -> Result<(), Error> {
+        self.env.sync(force)?;
+        Ok(())
+    }
+
     pub fn need_resize(&self, threshold_size: usize) -> bool {
         let info = self.env.info().unwrap();
         let stat = self.env.stat().unwrap();
@@ -880,12 +1015,103 @@ impl<'txn, 'db> WriteCursorTrait for MdbxWriteCursor<'txn> {
     }
 }
 
+/// Calls `open` until it succeeds or fails with an error that isn't a transient lock error, making
+/// at most `attempts` attempts.
+fn retry_on_lock_contention<T, F>(
+    attempts: usize,
+    backoff: Duration,
+    mut open: F,
+) -> Result<T, Error>
+where
+    F: FnMut() -> Result<T, Error>,
+{
+    let attempts = attempts.max(1);
+    let mut delay = backoff;
+
+    for attempt in 1..=attempts {
+        match open() {
+            Err(e) if e.is_transient_lock_error() => {
+                if attempt < attempts {
+                    warn!(
+                        "Environment is locked ({}), retrying in {:?} ({}/{})",
+                        e, delay, attempt, attempts
+                    );
+                    thread::sleep(delay);
+                    delay *= 2;
+                }
+            }
+            result => return result,
+        }
+    }
+
+    Err(Error::Locked { attempts })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use nimiq_test_log::test;
     use tempfile::tempdir;
 
+    #[test]
+    fn it_can_read_commits_with_any_durability() {
+        for durability in [
+            Durability::Durable,
+            Durability::NoMetaSync,
+            Durability::SafeNoSync,
+            Durability::UtterlyNoSync,
+        ] {
+            let tempdir = tempdir().unwrap();
+            let path = tempdir.path().join("test");
+            {
+                let env =
+                    MdbxEnvironment::new_with_durability(&path, 0, 1, 16, durability).unwrap();
+                let db = env.open_database("test".to_string());
+
+                let mut tx = WriteTransaction::new(&env);
+                tx.put_reserve(&db, "test", "one");
+                tx.commit();
+
+                let tx = ReadTransaction::new(&env);
+                assert_eq!(tx.get::<str, String>(&db, "test"), Some("one".to_string()));
+                tx.close();
+
+                env.close();
+            }
+
+            // The data is still there after reopening the environment.
+            let env = MdbxEnvironment::new_with_durability(&path, 0, 1, 16, durability).unwrap();
+            let db = env.open_database("test".to_string());
+            let tx = ReadTransaction::new(&env);
+            assert_eq!(tx.get::<str, String>(&db, "test"), Some("one".to_string()));
+        }
+    }
+
+    #[test]
+    fn it_keeps_synced_commits_after_reopening() {
+        let tempdir = tempdir().unwrap();
+        let path = tempdir.path().join("test");
+        {
+            let env =
+                MdbxEnvironment::new_with_durability(&path, 0, 1, 16, Durability::UtterlyNoSync)
+                    .unwrap();
+            let db = env.open_database("test".to_string());
+
+            let mut tx = WriteTransaction::new(&env);
+            tx.put_reserve(&db, "test", "one");
+            tx.commit();
+
+            env.sync(false).unwrap();
+            env.sync(true).unwrap();
+            env.close();
+        }
+
+        let env = MdbxEnvironment::new(&path, 0, 1).unwrap();
+        let db = env.open_database("test".to_string());
+        let tx = ReadTransaction::new(&env);
+        assert_eq!(tx.get::<str, String>(&db, "test"), Some("one".to_string()));
+    }
+
     #[test]
     fn it_can_save_basic_objects() {
         let tempdir = tempdir().unwrap();
@@ -939,6 +1165,78 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_can_use_a_custom_page_size() {
+        let tempdir = tempdir().unwrap();
+        let path = tempdir.path().join("test");
+        {
+            let env = MdbxEnvironment::new_with_page_size(
+                &path,
+                0,
+                1,
+                16,
+                Durability::default(),
+                Some(16 * 1024),
+            )
+            .unwrap();
+            let db = env.open_database("test".to_string());
+
+            let mut tx = WriteTransaction::new(&env);
+            tx.put_reserve(&db, "test", "one");
+            tx.commit();
+
+            let tx = ReadTransaction::new(&env);
+            assert_eq!(tx.get::<str, String>(&db, "test"), Some("one".to_string()));
+
+            if let Environment::Persistent(ref env) = env {
+                assert_eq!(env.env.stat().unwrap().page_size(), 16 * 1024);
+            }
+        }
+
+        // The page size is kept when the database is reopened with the default.
+        let env = MdbxEnvironment::new(&path, 0, 1).unwrap();
+        let db = env.open_database("test".to_string());
+        let tx = ReadTransaction::new(&env);
+        assert_eq!(tx.get::<str, String>(&db, "test"), Some("one".to_string()));
+    }
+
+    #[test]
+    fn it_discards_dropped_write_transactions() {
+        let tempdir = tempdir().unwrap();
+        let envs = [
+            crate::volatile::VolatileEnvironment::new(1).unwrap(),
+            MdbxEnvironment::new(tempdir.path().join("test"), 0, 1).unwrap(),
+        ];
+
+        for env in envs {
+            let db = env.open_database("test".to_string());
+
+            let mut tx = WriteTransaction::new(&env);
+            tx.put_reserve(&db, "test", "one");
+            tx.commit();
+
+            // Mutate and drop without committing.
+            {
+                let mut tx = WriteTransaction::new(&env);
+                tx.put_reserve(&db, "test", "two");
+                tx.put_reserve(&db, "other", "three");
+                assert_eq!(tx.get::<str, String>(&db, "test"), Some("two".to_string()));
+            }
+
+            // The same happens if the transaction is dropped while unwinding from a panic.
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                let mut tx = WriteTransaction::new(&env);
+                tx.remove(&db, "test");
+                panic!("panic in the middle of a write transaction");
+            }));
+            assert!(result.is_err());
+
+            let tx = ReadTransaction::new(&env);
+            assert_eq!(tx.get::<str, String>(&db, "test"), Some("one".to_string()));
+            assert!(tx.get::<str, String>(&db, "other").is_none());
+        }
+    }
+
     #[test]
     fn isolation_test() {
         let tempdir = tempdir().unwrap();
@@ -1093,4 +1391,67 @@ mod tests {
         }
         tempdir.close().unwrap();
     }
+
+    fn transient_lock_error() -> Error {
+        let would_block = (1..256)
+            .find(|code| io::Error::from_raw_os_error(*code).kind() == io::ErrorKind::WouldBlock)
+            .unwrap();
+        Error::Mdbx(libmdbx::Error::Other(would_block))
+    }
+
+    #[test]
+    fn it_retries_transient_lock_errors() {
+        let mut calls = 0;
+        let result: Result<(), Error> =
+            retry_on_lock_contention(3, Duration::from_millis(1), || {
+                calls += 1;
+                Err(transient_lock_error())
+            });
+        assert!(matches!(result, Err(Error::Locked { attempts: 3 })));
+        assert_eq!(calls, 3);
+
+        let mut calls = 0;
+        let result = retry_on_lock_contention(3, Duration::from_millis(1), || {
+            calls += 1;
+            if calls < 2 {
+                Err(transient_lock_error())
+            } else {
+                Ok(calls)
+            }
+        });
+        assert!(matches!(result, Ok(2)));
+    }
+
+    #[test]
+    fn it_does_not_retry_if_the_environment_is_in_use() {
+        let mut calls = 0;
+        let result: Result<(), Error> =
+            retry_on_lock_contention(3, Duration::from_millis(1), || {
+                calls += 1;
+                Err(Error::Mdbx(libmdbx::Error::Busy))
+            });
+        assert!(matches!(result, Err(Error::Mdbx(libmdbx::Error::Busy))));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn it_can_open_with_retry() {
+        let tempdir = tempdir().unwrap();
+        let env = MdbxEnvironment::new_with_retry(
+            tempdir.path().join("test"),
+            0,
+            1,
+            3,
+            Duration::from_millis(1),
+        )
+        .unwrap();
+        let db = env.open_database("test".to_string());
+
+        let mut tx = WriteTransaction::new(&env);
+        tx.put_reserve(&db, "test", "one");
+        tx.commit();
+
+        let tx = ReadTransaction::new(&env);
+        assert_eq!(tx.get::<str, String>(&db, "test"), Some("one".to_string()));
+    }
 }
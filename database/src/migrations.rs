@@ -0,0 +1,306 @@
+use std::error;
+use std::fmt;
+
+use crate::{Database, Environment, ReadTransaction, WriteTransaction};
+
+/// The name of the database in which the schema version of an environment is stored.
+pub const METADATA_DB_NAME: &str = "Metadata";
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// A single step in the evolution of the database schema.
+///
+/// Migrations are applied in ascending order of their version. Once a migration has been applied,
+/// its version is recorded in the metadata database and it will never be applied again.
+pub trait Migration {
+    /// The schema version the database is at after this migration has been applied.
+    fn version(&self) -> u32;
+
+    /// Opens the databases that the migration needs. Databases can't be opened while a write
+    /// transaction is open, so this is called before the transaction for `apply` is started.
+    fn open_databases(&self, _env: &Environment) -> Vec<Database> {
+        vec![]
+    }
+
+    /// Applies the migration within the given transaction, using the databases returned by
+    /// `open_databases`. The new schema version is recorded in the same transaction, so if this
+    /// fails, nothing is written.
+    fn apply(
+        &self,
+        txn: &mut WriteTransaction,
+        databases: &[Database],
+    ) -> Result<(), MigrationError>;
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MigrationError {
+    /// The database is at a newer schema version than the one requested.
+    Downgrade { current: u32, target: u32 },
+    /// There is no migration that brings the database to the given version.
+    MissingMigration(u32),
+    /// The migration to the given version failed.
+    Failed { version: u32, reason: String },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use MigrationError::*;
+        match self {
+            Downgrade { current, target } => write!(
+                f,
+                "database is at schema version {}, can't downgrade to {}",
+                current, target
+            ),
+            MissingMigration(version) => {
+                write!(f, "no migration to schema version {}", version)
+            }
+            Failed { version, reason } => {
+                write!(
+                    f,
+                    "migration to schema version {} failed: {}",
+                    version, reason
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for MigrationError {}
+
+/// The first schema version. It doesn't change any data, it only marks databases that were
+/// created before schema versions were tracked.
+pub struct InitialSchema;
+
+impl Migration for InitialSchema {
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn apply(
+        &self,
+        _txn: &mut WriteTransaction,
+        _databases: &[Database],
+    ) -> Result<(), MigrationError> {
+        Ok(())
+    }
+}
+
+/// Returns all known migrations, in the order in which they need to be applied.
+pub fn migrations() -> Vec<Box<dyn Migration>> {
+    vec![Box::new(InitialSchema)]
+}
+
+/// Returns the schema version recorded in the given environment. Environments without a recorded
+/// version are at version 0.
+pub fn schema_version(env: &Environment) -> u32 {
+    let db = env.open_database(METADATA_DB_NAME.to_string());
+    let txn = ReadTransaction::new(env);
+    txn.get(&db, SCHEMA_VERSION_KEY).unwrap_or(0)
+}
+
+/// Brings the given environment to `target_version` using the known migrations.
+/// Returns the schema version the environment is at afterwards.
+pub fn run_migrations(env: &Environment, target_version: u32) -> Result<u32, MigrationError> {
+    run_migrations_with(env, &migrations(), target_version)
+}
+
+/// Brings the given environment to `target_version` by applying all migrations in
+/// `migrations` whose version is above the recorded one. Re-running with the same target is a
+/// no-op. Each migration is applied in its own transaction together with the update of the schema
+/// version, so a failed migration leaves the environment at the version before it.
+/// Returns the schema version the environment is at afterwards.
+pub fn run_migrations_with(
+    env: &Environment,
+    migrations: &[Box<dyn Migration>],
+    target_version: u32,
+) -> Result<u32, MigrationError> {
+    let mut current = schema_version(env);
+    if current > target_version {
+        return Err(MigrationError::Downgrade {
+            current,
+            target: target_version,
+        });
+    }
+
+    let mut pending: Vec<&dyn Migration> = migrations
+        .iter()
+        .map(|migration| migration.as_ref())
+        .filter(|migration| migration.version() > current && migration.version() <= target_version)
+        .collect();
+    pending.sort_by_key(|migration| migration.version());
+
+    // Make sure that there are no gaps before touching the database.
+    for (i, migration) in pending.iter().enumerate() {
+        let expected = current + i as u32 + 1;
+        if migration.version() != expected {
+            return Err(MigrationError::MissingMigration(expected));
+        }
+    }
+    if current + (pending.len() as u32) < target_version {
+        return Err(MigrationError::MissingMigration(
+            current + pending.len() as u32 + 1,
+        ));
+    }
+
+    let db = env.open_database(METADATA_DB_NAME.to_string());
+    for migration in pending {
+        info!(
+            "Migrating database from schema version {} to {}",
+            current,
+            migration.version()
+        );
+        let databases = migration.open_databases(env);
+
+        let mut txn = WriteTransaction::new(env);
+        if let Err(e) = migration.apply(&mut txn, &databases) {
+            txn.abort();
+            return Err(e);
+        }
+
+        current = migration.version();
+        txn.put(&db, SCHEMA_VERSION_KEY, &current);
+        txn.commit();
+    }
+
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use nimiq_test_log::test;
+
+    use super::*;
+    use crate::volatile::VolatileEnvironment;
+
+    struct CountingMigration {
+        version: u32,
+        applied: Arc<AtomicUsize>,
+        fail: bool,
+    }
+
+    impl Migration for CountingMigration {
+        fn version(&self) -> u32 {
+            self.version
+        }
+
+        fn open_databases(&self, env: &Environment) -> Vec<Database> {
+            vec![env.open_database(format!("Migration{}", self.version))]
+        }
+
+        fn apply(
+            &self,
+            txn: &mut WriteTransaction,
+            databases: &[Database],
+        ) -> Result<(), MigrationError> {
+            self.applied.fetch_add(1, Ordering::SeqCst);
+
+            txn.put_reserve(&databases[0], "migrated", "yes");
+
+            if self.fail {
+                return Err(MigrationError::Failed {
+                    version: self.version,
+                    reason: "test".to_string(),
+                });
+            }
+            Ok(())
+        }
+    }
+
+    fn counting_migrations(applied: &Arc<AtomicUsize>) -> Vec<Box<dyn Migration>> {
+        (1..=3)
+            .map(|version| {
+                Box::new(CountingMigration {
+                    version,
+                    applied: Arc::clone(applied),
+                    fail: false,
+                }) as Box<dyn Migration>
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_runs_pending_migrations_once() {
+        let env = VolatileEnvironment::new(10).unwrap();
+        let applied = Arc::new(AtomicUsize::new(0));
+        let migrations = counting_migrations(&applied);
+
+        assert_eq!(schema_version(&env), 0);
+        assert_eq!(run_migrations_with(&env, &migrations, 2), Ok(2));
+        assert_eq!(applied.load(Ordering::SeqCst), 2);
+        assert_eq!(schema_version(&env), 2);
+
+        // Re-running is a no-op.
+        assert_eq!(run_migrations_with(&env, &migrations, 2), Ok(2));
+        assert_eq!(applied.load(Ordering::SeqCst), 2);
+
+        // Only the remaining migration is applied.
+        assert_eq!(run_migrations_with(&env, &migrations, 3), Ok(3));
+        assert_eq!(applied.load(Ordering::SeqCst), 3);
+
+        let db = env.open_database("Migration3".to_string());
+        let txn = ReadTransaction::new(&env);
+        assert_eq!(
+            txn.get::<str, String>(&db, "migrated"),
+            Some("yes".to_string())
+        );
+    }
+
+    #[test]
+    fn it_rejects_downgrades_and_gaps() {
+        let env = VolatileEnvironment::new(10).unwrap();
+        let applied = Arc::new(AtomicUsize::new(0));
+        let migrations = counting_migrations(&applied);
+
+        assert_eq!(
+            run_migrations_with(&env, &migrations, 4),
+            Err(MigrationError::MissingMigration(4))
+        );
+        assert_eq!(applied.load(Ordering::SeqCst), 0);
+
+        assert_eq!(run_migrations_with(&env, &migrations, 3), Ok(3));
+        assert_eq!(
+            run_migrations_with(&env, &migrations, 1),
+            Err(MigrationError::Downgrade {
+                current: 3,
+                target: 1
+            })
+        );
+    }
+
+    #[test]
+    fn it_does_not_record_failed_migrations() {
+        let env = VolatileEnvironment::new(10).unwrap();
+        let applied = Arc::new(AtomicUsize::new(0));
+        let mut migrations = counting_migrations(&applied);
+        migrations[1] = Box::new(CountingMigration {
+            version: 2,
+            applied: Arc::clone(&applied),
+            fail: true,
+        });
+
+        assert_eq!(
+            run_migrations_with(&env, &migrations, 3),
+            Err(MigrationError::Failed {
+                version: 2,
+                reason: "test".to_string()
+            })
+        );
+        assert_eq!(applied.load(Ordering::SeqCst), 2);
+
+        // The first migration was recorded, but nothing of the failed one was written.
+        assert_eq!(schema_version(&env), 1);
+        let db = env.open_database("Migration2".to_string());
+        let txn = ReadTransaction::new(&env);
+        assert_eq!(txn.get::<str, String>(&db, "migrated"), None);
+    }
+
+    #[test]
+    fn it_runs_the_known_migrations() {
+        let env = VolatileEnvironment::new(10).unwrap();
+        assert_eq!(run_migrations(&env, 1), Ok(1));
+        assert_eq!(run_migrations(&env, 1), Ok(1));
+        assert_eq!(schema_version(&env), 1);
+    }
+}
@@ -2,6 +2,7 @@ use std::convert::TryFrom;
 
 use serde::de::Error;
 use serde::{Deserialize, Deserializer};
+use thiserror::Error;
 use time::OffsetDateTime;
 
 use beserial::Deserialize as BDeserialize;
@@ -10,6 +11,27 @@ use nimiq_keys::{Address, PublicKey as SchnorrPublicKey};
 use nimiq_primitives::coin::Coin;
 use nimiq_vrf::VrfSeed;
 
+/// A problem found while validating a genesis config file.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("Failed to read config file: {0}")]
+    Io(String),
+    #[error("Failed to parse TOML file: {0}")]
+    Toml(String),
+    #[error("Invalid seed message length: {0}")]
+    InvalidSeedMessage(usize),
+    #[error("Duplicate validator {0}")]
+    DuplicateValidator(Address),
+    #[error("Duplicate staker {0}")]
+    DuplicateStaker(Address),
+    #[error("Duplicate account {0}")]
+    DuplicateAccount(Address),
+    #[error("Staker {staker} delegates to unknown validator {validator}")]
+    UnknownDelegation { staker: Address, validator: Address },
+    #[error("Total supply of the genesis accounts overflows")]
+    SupplyOverflow,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct GenesisConfig {
     #[serde(default)]
@@ -72,7 +94,7 @@ where
     D: Deserializer<'de>,
 {
     let s: String = Deserialize::deserialize(deserializer)?;
-    Address::from_user_friendly_address(&s).map_err(|e| Error::custom(format!("{:?}", e)))
+    Address::from_user_friendly_address(&s).map_err(Error::custom)
 }
 
 #[allow(dead_code)]
@@ -83,8 +105,7 @@ where
     let opt: Option<String> = Deserialize::deserialize(deserializer)?;
     if let Some(s) = opt {
         Ok(Some(
-            Address::from_user_friendly_address(&s)
-                .map_err(|e| Error::custom(format!("{:?}", e)))?,
+            Address::from_user_friendly_address(&s).map_err(Error::custom)?,
         ))
     } else {
         Ok(None)
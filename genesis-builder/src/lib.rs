@@ -1,13 +1,16 @@
 #[macro_use]
 extern crate log;
 
+use std::collections::{BTreeMap, HashSet};
 use std::convert::TryFrom;
+use std::fmt;
 use std::fs::{read_to_string, OpenOptions};
 use std::io::Error as IoError;
+use std::io::Write;
 use std::path::Path;
 
 use thiserror::Error;
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
 use toml::de::Error as TomlError;
 
 use beserial::{Serialize, SerializingError};
@@ -17,18 +20,26 @@ use nimiq_bls::PublicKey as BlsPublicKey;
 use nimiq_database::{Environment, WriteTransaction};
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::{Address, PublicKey as SchnorrPublicKey};
+use nimiq_primitives::account::AccountType;
 use nimiq_primitives::coin::Coin;
+use nimiq_primitives::policy;
 use nimiq_trie::key_nibbles::KeyNibbles;
 use nimiq_vrf::VrfSeed;
 
 mod config;
 
+pub use config::ConfigError;
+
 #[derive(Debug, Error)]
 pub enum GenesisBuilderError {
     #[error("No VRF seed to generate genesis block")]
     NoVrfSeed,
     #[error("Invalid timestamp: {0}")]
     InvalidTimestamp(OffsetDateTime),
+    #[error("Invalid seed message length: {0}")]
+    InvalidSeedMessage(usize),
+    #[error("Invalid extra data length: {0}")]
+    InvalidExtraData(usize),
     #[error("Serialization failed")]
     SerializingError(#[from] SerializingError),
     #[error("I/O error")]
@@ -37,6 +48,22 @@ pub enum GenesisBuilderError {
     TomlError(#[from] TomlError),
     #[error("Failed to stake")]
     StakingError(#[from] AccountError),
+    #[error("Total supply of the genesis accounts overflows")]
+    SupplyOverflow,
+    #[error("Default (all-zero) key or VRF seed in a production genesis")]
+    DefaultKeyInProduction,
+    #[error("Invalid genesis config: {0}")]
+    InvalidConfig(ConfigError),
+}
+
+impl From<ConfigError> for GenesisBuilderError {
+    fn from(e: ConfigError) -> Self {
+        match e {
+            ConfigError::InvalidSeedMessage(len) => GenesisBuilderError::InvalidSeedMessage(len),
+            ConfigError::SupplyOverflow => GenesisBuilderError::SupplyOverflow,
+            e => GenesisBuilderError::InvalidConfig(e),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -46,24 +73,109 @@ pub struct GenesisInfo {
     pub accounts: Vec<(KeyNibbles, Account)>,
 }
 
+impl GenesisInfo {
+    /// Computes a summary of the genesis state from the genesis accounts.
+    pub fn summary(&self) -> GenesisSummary {
+        let mut total_supply = Coin::ZERO;
+        let mut accounts_by_type = BTreeMap::new();
+        let mut num_validators = 0;
+        let mut total_stake = Coin::ZERO;
+
+        for (_, account) in &self.accounts {
+            *accounts_by_type.entry(account.account_type()).or_insert(0) += 1;
+
+            match account {
+                // The staking contract balance already includes all stakes and validator deposits.
+                Account::Basic(_) | Account::Vesting(_) | Account::HTLC(_) => {
+                    total_supply += account.balance();
+                }
+                Account::Staking(contract) => {
+                    total_supply += contract.balance;
+                    total_stake += contract.balance;
+                }
+                Account::StakingValidator(_) => num_validators += 1,
+                Account::StakingValidatorsStaker(_) | Account::StakingStaker(_) => {}
+            }
+        }
+
+        GenesisSummary {
+            total_supply,
+            accounts_by_type,
+            num_validators,
+            total_stake,
+        }
+    }
+}
+
+/// A summary of a generated genesis, so that operators can double-check it before using it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GenesisSummary {
+    /// The sum of the balances of all accounts, including the staking contract.
+    pub total_supply: Coin,
+    /// The number of entries in the accounts tree, by account type.
+    pub accounts_by_type: BTreeMap<AccountType, usize>,
+    /// The number of genesis validators.
+    pub num_validators: usize,
+    /// The total amount of coins in the staking contract, including validator deposits.
+    pub total_stake: Coin,
+}
+
+impl fmt::Display for GenesisSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Total supply: {} NIM", self.total_supply)?;
+        writeln!(f, "Total stake: {} NIM", self.total_stake)?;
+        writeln!(f, "Validators: {}", self.num_validators)?;
+        writeln!(f, "Accounts:")?;
+        for (account_type, count) in &self.accounts_by_type {
+            writeln!(f, "  {}: {}", account_type, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// The default window around the current time in which the genesis timestamp has to be, if the
+/// timestamp is validated strictly.
+pub const DEFAULT_TIMESTAMP_WINDOW: Duration = Duration::days(5 * 365);
+
+/// The default maximum length of the seed message in bytes, if the seed message is validated
+/// strictly.
+pub const DEFAULT_MAX_SEED_MESSAGE_LENGTH: usize = 256;
+
+/// The maximum length of the extra data that can be added to the genesis block in bytes. The extra
+/// data of a block header is limited to 32 bytes, and the first 8 bytes of the genesis extra data
+/// hold the initial supply.
+pub const MAX_EXTRA_DATA_LENGTH: usize = 24;
+
 pub struct GenesisBuilder {
     pub seed_message: Option<String>,
+    pub max_seed_message_length: Option<usize>,
     pub timestamp: Option<OffsetDateTime>,
+    pub timestamp_window: Option<Duration>,
     pub vrf_seed: Option<VrfSeed>,
     pub validators: Vec<config::GenesisValidator>,
     pub stakers: Vec<config::GenesisStaker>,
     pub accounts: Vec<config::GenesisAccount>,
+    pub write_summary: bool,
+    pub reject_default_keys: bool,
+    pub version: u16,
+    pub extra_data: Vec<u8>,
 }
 
 impl GenesisBuilder {
     pub fn new() -> Self {
         GenesisBuilder {
             seed_message: None,
+            max_seed_message_length: None,
             timestamp: None,
+            timestamp_window: None,
             vrf_seed: None,
             validators: vec![],
             stakers: vec![],
             accounts: vec![],
+            write_summary: false,
+            reject_default_keys: false,
+            version: policy::VERSION,
+            extra_data: vec![],
         }
     }
 
@@ -83,16 +195,75 @@ impl GenesisBuilder {
         self
     }
 
+    /// Makes `generate` reject seed messages that are longer than
+    /// `DEFAULT_MAX_SEED_MESSAGE_LENGTH` bytes.
+    pub fn with_strict_seed_message(&mut self) -> &mut Self {
+        self.with_max_seed_message_length(DEFAULT_MAX_SEED_MESSAGE_LENGTH)
+    }
+
+    /// Makes `generate` reject seed messages that are longer than `max_length` bytes.
+    pub fn with_max_seed_message_length(&mut self, max_length: usize) -> &mut Self {
+        self.max_seed_message_length = Some(max_length);
+        self
+    }
+
     pub fn with_timestamp(&mut self, timestamp: OffsetDateTime) -> &mut Self {
         self.timestamp = Some(timestamp);
         self
     }
 
+    /// Makes `generate` reject timestamps that are more than `DEFAULT_TIMESTAMP_WINDOW` away from
+    /// the current time.
+    pub fn with_strict_timestamp(&mut self) -> &mut Self {
+        self.with_timestamp_window(DEFAULT_TIMESTAMP_WINDOW)
+    }
+
+    /// Makes `generate` reject timestamps that are more than `window` away from the current time.
+    pub fn with_timestamp_window(&mut self, window: Duration) -> &mut Self {
+        self.timestamp_window = Some(window);
+        self
+    }
+
+    /// Sets the version of the genesis block. Defaults to `policy::VERSION`. A blockchain only
+    /// accepts a genesis block whose version matches the `policy::VERSION` it was built with.
+    pub fn with_version(&mut self, version: u16) -> &mut Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the extra data of the genesis block, e.g. to tag test and production launches. It
+    /// must not be longer than `MAX_EXTRA_DATA_LENGTH` bytes. In the genesis header, it follows
+    /// the initial supply, which is read from the first 8 bytes when computing block rewards.
+    pub fn with_extra_data<B: Into<Vec<u8>>>(&mut self, extra_data: B) -> &mut Self {
+        self.extra_data = extra_data.into();
+        self
+    }
+
     pub fn with_vrf_seed(&mut self, vrf_seed: VrfSeed) -> &mut Self {
         self.vrf_seed = Some(vrf_seed);
         self
     }
 
+    /// Makes `generate` reject the defaults set by `with_defaults`, i.e. the all-zero VRF seed, as
+    /// well as validators with an all-zero signing key.
+    pub fn with_strict_keys(&mut self) -> &mut Self {
+        self.reject_default_keys = true;
+        self
+    }
+
+    /// Returns true if the genesis uses the all-zero VRF seed set by `with_defaults` or a
+    /// validator with an all-zero signing key. Those are fine for tests, but must never end up in
+    /// a production genesis.
+    pub fn is_using_defaults(&self) -> bool {
+        let zero_signing_key = SchnorrPublicKey::from([0u8; SchnorrPublicKey::SIZE]);
+
+        self.vrf_seed == Some(VrfSeed::default())
+            || self
+                .validators
+                .iter()
+                .any(|validator| validator.signing_key == zero_signing_key)
+    }
+
     pub fn with_genesis_validator(
         &mut self,
         validator_address: Address,
@@ -123,6 +294,12 @@ impl GenesisBuilder {
         self
     }
 
+    /// Makes `write_to_files` also write a human readable `summary.txt` of the genesis.
+    pub fn with_summary_file(&mut self) -> &mut Self {
+        self.write_summary = true;
+        self
+    }
+
     pub fn with_basic_account(&mut self, address: Address, balance: Coin) -> &mut Self {
         self.accounts
             .push(config::GenesisAccount { address, balance });
@@ -133,6 +310,11 @@ impl GenesisBuilder {
         &mut self,
         path: P,
     ) -> Result<&mut Self, GenesisBuilderError> {
+        let config = toml::from_str(&read_to_string(path)?)?;
+        Ok(self.with_config(config))
+    }
+
+    fn with_config(&mut self, config: config::GenesisConfig) -> &mut Self {
         let config::GenesisConfig {
             seed_message,
             timestamp,
@@ -140,21 +322,118 @@ impl GenesisBuilder {
             mut validators,
             mut stakers,
             mut accounts,
-        } = toml::from_str(&read_to_string(path)?)?;
+        } = config;
         vrf_seed.map(|vrf_seed| self.with_vrf_seed(vrf_seed));
         seed_message.map(|msg| self.with_seed_message(msg));
         timestamp.map(|t| self.with_timestamp(t));
         self.validators.append(&mut validators);
         self.stakers.append(&mut stakers);
         self.accounts.append(&mut accounts);
+        self
+    }
+
+    /// Checks the genesis config file at `path` without generating anything. The file is parsed
+    /// like in `with_config_file` and then checked like in `generate`, but all problems of the
+    /// parsed config are returned, like duplicate entries, stakers delegating to unknown
+    /// validators or a total supply that overflows.
+    pub fn validate_config_file<P: AsRef<Path>>(path: P) -> Result<(), Vec<ConfigError>> {
+        let contents = read_to_string(path).map_err(|e| vec![ConfigError::Io(e.to_string())])?;
+        let config =
+            toml::from_str(&contents).map_err(|e| vec![ConfigError::Toml(e.to_string())])?;
 
-        Ok(self)
+        let errors = GenesisBuilder::new().with_config(config).config_errors();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns every problem with the seed message and the genesis accounts, stakers and
+    /// validators, instead of stopping at the first one.
+    fn config_errors(&self) -> Vec<ConfigError> {
+        let mut errors = vec![];
+
+        // Make sure that the seed message isn't empty and, in strict mode, not too long.
+        if let Some(seed_message) = &self.seed_message {
+            let too_long = self
+                .max_seed_message_length
+                .map_or(false, |max_length| seed_message.len() > max_length);
+            if seed_message.is_empty() || too_long {
+                errors.push(ConfigError::InvalidSeedMessage(seed_message.len()));
+            }
+        }
+
+        let mut validators = HashSet::new();
+        for validator in &self.validators {
+            if !validators.insert(&validator.validator_address) {
+                errors.push(ConfigError::DuplicateValidator(
+                    validator.validator_address.clone(),
+                ));
+            }
+        }
+
+        let mut stakers = HashSet::new();
+        for staker in &self.stakers {
+            if !stakers.insert(&staker.staker_address) {
+                errors.push(ConfigError::DuplicateStaker(staker.staker_address.clone()));
+            }
+            if !validators.contains(&staker.delegation) {
+                errors.push(ConfigError::UnknownDelegation {
+                    staker: staker.staker_address.clone(),
+                    validator: staker.delegation.clone(),
+                });
+            }
+        }
+
+        let mut accounts = HashSet::new();
+        for account in &self.accounts {
+            if !accounts.insert(&account.address) {
+                errors.push(ConfigError::DuplicateAccount(account.address.clone()));
+            }
+        }
+
+        // Make sure that the sum of all balances is a valid amount of coins.
+        if self.total_supply().is_none() {
+            errors.push(ConfigError::SupplyOverflow);
+        }
+
+        errors
     }
 
     pub fn generate(&self, env: Environment) -> Result<GenesisInfo, GenesisBuilderError> {
+        if let Some(error) = self.config_errors().into_iter().next() {
+            return Err(error.into());
+        }
+
+        if self.extra_data.len() > MAX_EXTRA_DATA_LENGTH {
+            return Err(GenesisBuilderError::InvalidExtraData(self.extra_data.len()));
+        }
+
+        // In strict mode, make sure that none of the test defaults are used.
+        if self.reject_default_keys && self.is_using_defaults() {
+            return Err(GenesisBuilderError::DefaultKeyInProduction);
+        }
+
         // Initialize the environment.
         let timestamp = self.timestamp.unwrap_or_else(OffsetDateTime::now_utc);
 
+        // In strict mode, make sure that the timestamp is close to the current time. If the window
+        // reaches beyond the representable dates, it isn't bounded on that side.
+        if let Some(window) = self.timestamp_window {
+            let now = OffsetDateTime::now_utc();
+            let too_early = now.checked_sub(window).map_or(false, |min| timestamp < min);
+            let too_late = now.checked_add(window).map_or(false, |max| timestamp > max);
+            if too_early || too_late {
+                return Err(GenesisBuilderError::InvalidTimestamp(timestamp));
+            }
+        }
+
+        let supply = self
+            .total_supply()
+            .ok_or(GenesisBuilderError::SupplyOverflow)?;
+        debug!("Total supply: {}", supply);
+
         // Initialize the accounts.
         let accounts = Accounts::new(env.clone());
         let mut genesis_accounts: Vec<(KeyNibbles, Account)> = Vec::new();
@@ -216,6 +495,10 @@ impl GenesisBuilder {
             ));
         }
 
+        // Sort the accounts by key, so that the same configuration always produces the same list of
+        // genesis accounts, independently of the order in which the accounts were added.
+        genesis_accounts.sort_by(|(key1, _), (key2, _)| key1.cmp(key2));
+
         accounts.init(&mut txn, genesis_accounts.clone());
 
         // generate seeds
@@ -244,9 +527,14 @@ impl GenesisBuilder {
         debug!("State root: {}", &state_root);
         txn.abort();
 
+        // The extra data starts with the initial supply (a big-endian `u64`), followed by the
+        // configured extra data.
+        let mut extra_data = u64::from(supply).to_be_bytes().to_vec();
+        extra_data.extend_from_slice(&self.extra_data);
+
         // the header
         let header = MacroHeader {
-            version: 1,
+            version: self.version,
             block_number: 0,
             view_number: 0,
             timestamp: u64::try_from(timestamp.unix_timestamp())
@@ -254,7 +542,7 @@ impl GenesisBuilder {
             parent_hash: [0u8; 32].into(),
             parent_election_hash: [0u8; 32].into(),
             seed,
-            extra_data: vec![],
+            extra_data,
             state_root,
             body_root,
             history_root: Blake2bHash::default(),
@@ -274,6 +562,18 @@ impl GenesisBuilder {
         })
     }
 
+    /// Returns the sum of the balances of all genesis accounts, stakers and validator deposits.
+    fn total_supply(&self) -> Option<Coin> {
+        let deposit = Coin::from_u64_unchecked(policy::VALIDATOR_DEPOSIT);
+
+        self.accounts
+            .iter()
+            .map(|account| account.balance)
+            .chain(self.stakers.iter().map(|staker| staker.balance))
+            .chain(self.validators.iter().map(|_| deposit))
+            .try_fold(Coin::ZERO, |supply, balance| supply.checked_add(balance))
+    }
+
     fn generate_staking_contract(
         &self,
         accounts: &Accounts,
@@ -281,7 +581,15 @@ impl GenesisBuilder {
     ) -> Result<(), GenesisBuilderError> {
         StakingContract::create(&accounts.tree, txn);
 
-        for validator in &self.validators {
+        // Create the validators and stakers in a canonical order (sorted by address), so that the
+        // resulting staking contract doesn't depend on the order in which they were added.
+        let mut validators: Vec<&config::GenesisValidator> = self.validators.iter().collect();
+        validators.sort_by(|v1, v2| v1.validator_address.cmp(&v2.validator_address));
+
+        let mut stakers: Vec<&config::GenesisStaker> = self.stakers.iter().collect();
+        stakers.sort_by(|s1, s2| s1.staker_address.cmp(&s2.staker_address));
+
+        for validator in validators {
             StakingContract::create_validator(
                 &accounts.tree,
                 txn,
@@ -293,7 +601,7 @@ impl GenesisBuilder {
             )?;
         }
 
-        for staker in &self.stakers {
+        for staker in stakers {
             StakingContract::create_staker(
                 &accounts.tree,
                 txn,
@@ -311,11 +619,24 @@ impl GenesisBuilder {
         env: Environment,
         directory: P,
     ) -> Result<Blake2bHash, GenesisBuilderError> {
+        let genesis_info = self.generate(env)?;
+
+        if self.write_summary {
+            let summary_path = directory.as_ref().join("summary.txt");
+            info!("Writing summary to {}", summary_path.display());
+            let mut file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(&summary_path)?;
+            write!(file, "{}", genesis_info.summary())?;
+        }
+
         let GenesisInfo {
             block,
             hash,
             accounts,
-        } = self.generate(env)?;
+        } = genesis_info;
 
         debug!("Genesis block: {}", &hash);
         debug!("{:#?}", &block);
@@ -347,3 +668,408 @@ impl Default for GenesisBuilder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::StdRng;
+    use rand::SeedableRng;
+
+    use nimiq_bls::KeyPair as BlsKeyPair;
+    use nimiq_database::volatile::VolatileEnvironment;
+    use nimiq_database::ReadTransaction;
+    use nimiq_keys::SecureGenerate;
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn it_rejects_timestamps_outside_of_the_window_in_strict_mode() {
+        let now = OffsetDateTime::now_utc();
+        let past = OffsetDateTime::UNIX_EPOCH;
+        let future = now + Duration::days(100 * 365);
+
+        for timestamp in [past, future] {
+            let env = VolatileEnvironment::new(10).unwrap();
+            let result = GenesisBuilder::default()
+                .with_timestamp(timestamp)
+                .with_strict_timestamp()
+                .generate(env);
+            assert!(matches!(
+                result,
+                Err(GenesisBuilderError::InvalidTimestamp(t)) if t == timestamp
+            ));
+        }
+
+        // Without strict mode, the timestamps are accepted.
+        let env = VolatileEnvironment::new(10).unwrap();
+        assert!(GenesisBuilder::default()
+            .with_timestamp(past)
+            .generate(env)
+            .is_ok());
+
+        // A window that reaches beyond the representable dates doesn't overflow.
+        let env = VolatileEnvironment::new(10).unwrap();
+        assert!(GenesisBuilder::default()
+            .with_timestamp(past)
+            .with_timestamp_window(Duration::MAX)
+            .generate(env)
+            .is_ok());
+
+        // A timestamp close to the current time is accepted in strict mode.
+        let env = VolatileEnvironment::new(10).unwrap();
+        assert!(GenesisBuilder::default()
+            .with_timestamp(now - Duration::days(1))
+            .with_strict_timestamp()
+            .generate(env)
+            .is_ok());
+    }
+
+    #[test]
+    fn it_rejects_the_defaults_in_strict_mode() {
+        assert!(GenesisBuilder::default().is_using_defaults());
+        assert!(!GenesisBuilder::new().is_using_defaults());
+
+        let env = VolatileEnvironment::new(10).unwrap();
+        let result = GenesisBuilder::default().with_strict_keys().generate(env);
+        assert!(matches!(
+            result,
+            Err(GenesisBuilderError::DefaultKeyInProduction)
+        ));
+
+        // Without strict mode, the defaults are accepted.
+        let env = VolatileEnvironment::new(10).unwrap();
+        assert!(GenesisBuilder::default().generate(env).is_ok());
+    }
+
+    #[test]
+    fn it_reports_all_config_errors() {
+        let validator = "NQ20 TSB0 DFSM UH9C 15GQ GAGJ TTE4 D3MA 859E";
+        let unknown_validator = "NQ46 U66M JNLD 0DJ7 0E9P Q7XR V9KV H976 813A";
+        let staker = "NQ39 VBTN P2HX Q3MF KHF1 CCLA G6FS 9B8S VY28";
+        let account = "NQ09 VF5Y 1PKV MRM4 5LE1 55KV P6R2 GXYJ XYQF";
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let voting_key = hex::encode(BlsKeyPair::generate(&mut rng).public_key.serialize_to_vec());
+        let vrf_seed = hex::encode(VrfSeed::default().serialize_to_vec());
+
+        let validate = |contents: &str| {
+            let mut file = tempfile::NamedTempFile::new().unwrap();
+            file.write_all(contents.as_bytes()).unwrap();
+            GenesisBuilder::validate_config_file(file.path())
+        };
+
+        let header = format!(
+            r#"
+timestamp = "2021-10-30T02:04:20Z"
+vrf_seed = "{vrf_seed}"
+"#,
+            vrf_seed = vrf_seed,
+        );
+        let validators = format!(
+            r#"
+[[validators]]
+validator_address = "{validator}"
+signing_key = "7a4d16d80d0afab4af8dc38e4ebd20cf46f5b8aa8cd5baa45a52717ae976119c"
+voting_key = "{voting_key}"
+reward_address = "{validator}"
+"#,
+            validator = validator,
+            voting_key = voting_key,
+        );
+
+        let errors = validate(&format!(
+            r#"
+seed_message = ""
+{header}
+{validators}
+
+[[stakers]]
+staker_address = "{staker}"
+balance = 100_000
+delegation = "{unknown_validator}"
+
+[[stakers]]
+staker_address = "{staker}"
+balance = 100_000
+delegation = "{validator}"
+
+[[accounts]]
+address = "{account}"
+balance = 9_007_199_254_740_991
+
+[[accounts]]
+address = "{account}"
+balance = 9_007_199_254_740_991
+"#,
+            header = header,
+            validators = validators,
+            validator = validator,
+            unknown_validator = unknown_validator,
+            staker = staker,
+            account = account,
+        ))
+        .unwrap_err();
+
+        let staker = Address::from_user_friendly_address(staker).unwrap();
+        assert_eq!(
+            errors,
+            vec![
+                ConfigError::InvalidSeedMessage(0),
+                ConfigError::UnknownDelegation {
+                    staker: staker.clone(),
+                    validator: Address::from_user_friendly_address(unknown_validator).unwrap(),
+                },
+                ConfigError::DuplicateStaker(staker),
+                ConfigError::DuplicateAccount(
+                    Address::from_user_friendly_address(account).unwrap()
+                ),
+                ConfigError::SupplyOverflow,
+            ]
+        );
+
+        // A config that can't be parsed fails like in `with_config_file`, e.g. if a key is
+        // invalid or a required field is missing.
+        let invalid_key = validators.replace(&voting_key, "00");
+        assert!(matches!(
+            &validate(&format!("{}{}", header, invalid_key)).unwrap_err()[..],
+            [ConfigError::Toml(_)]
+        ));
+        assert!(matches!(
+            &validate(&validators).unwrap_err()[..],
+            [ConfigError::Toml(_)]
+        ));
+        assert_eq!(validate(&format!("{}{}", header, validators)), Ok(()));
+
+        // The config files that ship with the genesis crate are valid.
+        assert_eq!(
+            GenesisBuilder::validate_config_file(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../genesis/src/genesis/unit-albatross.toml"
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn it_validates_the_seed_message_length() {
+        let env = VolatileEnvironment::new(10).unwrap();
+        assert!(matches!(
+            GenesisBuilder::default()
+                .with_seed_message("")
+                .generate(env),
+            Err(GenesisBuilderError::InvalidSeedMessage(0))
+        ));
+
+        let env = VolatileEnvironment::new(10).unwrap();
+        assert!(GenesisBuilder::default()
+            .with_seed_message("Albatross Genesis")
+            .with_strict_seed_message()
+            .generate(env)
+            .is_ok());
+
+        // Overly long messages are only rejected in strict mode.
+        let long_message = "a".repeat(DEFAULT_MAX_SEED_MESSAGE_LENGTH + 1);
+
+        let env = VolatileEnvironment::new(10).unwrap();
+        assert!(matches!(
+            GenesisBuilder::default()
+                .with_seed_message(&long_message)
+                .with_strict_seed_message()
+                .generate(env),
+            Err(GenesisBuilderError::InvalidSeedMessage(len)) if len == long_message.len()
+        ));
+
+        let env = VolatileEnvironment::new(10).unwrap();
+        assert!(GenesisBuilder::default()
+            .with_seed_message(&long_message)
+            .generate(env)
+            .is_ok());
+    }
+
+    #[test]
+    fn it_generates_a_genesis_with_the_given_version() {
+        let env = VolatileEnvironment::new(10).unwrap();
+        let genesis = GenesisBuilder::default().generate(env).unwrap();
+        assert_eq!(genesis.block.version(), policy::VERSION);
+
+        let env = VolatileEnvironment::new(10).unwrap();
+        let genesis = GenesisBuilder::default()
+            .with_version(policy::VERSION + 1)
+            .generate(env)
+            .unwrap();
+        assert_eq!(genesis.block.version(), policy::VERSION + 1);
+
+        let serialized = genesis.block.serialize_to_vec();
+        let deserialized: Block = beserial::Deserialize::deserialize_from_vec(&serialized).unwrap();
+        assert_eq!(deserialized.version(), policy::VERSION + 1);
+        assert_eq!(deserialized.hash(), genesis.hash);
+    }
+
+    #[test]
+    fn it_generates_a_genesis_with_the_given_extra_data() {
+        let timestamp = OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap();
+
+        let env = VolatileEnvironment::new(10).unwrap();
+        let genesis = GenesisBuilder::default()
+            .with_timestamp(timestamp)
+            .generate(env)
+            .unwrap();
+
+        let env = VolatileEnvironment::new(10).unwrap();
+        let tagged = GenesisBuilder::default()
+            .with_timestamp(timestamp)
+            .with_extra_data(b"testnet".to_vec())
+            .generate(env)
+            .unwrap();
+        assert_ne!(tagged.hash, genesis.hash);
+
+        // Both start with the initial supply, which isn't changed by the tag.
+        let supply = GenesisBuilder::default().total_supply().unwrap();
+        let mut expected = u64::from(supply).to_be_bytes().to_vec();
+        assert_eq!(*genesis.block.extra_data(), expected);
+        expected.extend_from_slice(b"testnet");
+        assert_eq!(*tagged.block.extra_data(), expected);
+
+        let serialized = tagged.block.serialize_to_vec();
+        let deserialized: Block = beserial::Deserialize::deserialize_from_vec(&serialized).unwrap();
+        assert_eq!(*deserialized.extra_data(), expected);
+        assert_eq!(deserialized.hash(), tagged.hash);
+
+        // The longest tag still fits into the extra data of the header.
+        let env = VolatileEnvironment::new(10).unwrap();
+        let tagged = GenesisBuilder::default()
+            .with_extra_data(vec![0xff; MAX_EXTRA_DATA_LENGTH])
+            .generate(env)
+            .unwrap();
+        assert_eq!(tagged.block.extra_data().len(), 32);
+
+        let env = VolatileEnvironment::new(10).unwrap();
+        assert!(matches!(
+            GenesisBuilder::default()
+                .with_extra_data(vec![0u8; MAX_EXTRA_DATA_LENGTH + 1])
+                .generate(env),
+            Err(GenesisBuilderError::InvalidExtraData(len)) if len == MAX_EXTRA_DATA_LENGTH + 1
+        ));
+    }
+
+    #[test]
+    fn it_rejects_an_overflowing_supply() {
+        let env = VolatileEnvironment::new(10).unwrap();
+
+        let huge_balance = Coin::from_u64_unchecked(Coin::MAX_SAFE_VALUE / 2 + 1);
+
+        let mut genesis_builder = GenesisBuilder::default();
+        genesis_builder
+            .with_basic_account(Address::from([1u8; 20]), huge_balance)
+            .with_basic_account(Address::from([2u8; 20]), huge_balance);
+
+        assert!(matches!(
+            genesis_builder.generate(env),
+            Err(GenesisBuilderError::SupplyOverflow)
+        ));
+    }
+
+    #[test]
+    fn it_summarizes_the_accounts_and_supply() {
+        let env = VolatileEnvironment::new(10).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let validator_address = Address::from([3u8; 20]);
+
+        let mut genesis_builder = GenesisBuilder::default();
+        genesis_builder
+            .with_basic_account(Address::from([1u8; 20]), Coin::from_u64_unchecked(100))
+            .with_basic_account(Address::from([2u8; 20]), Coin::from_u64_unchecked(200))
+            .with_genesis_validator(
+                validator_address.clone(),
+                SchnorrPublicKey::from([0u8; 32]),
+                BlsKeyPair::generate(&mut rng).public_key,
+                Address::default(),
+            )
+            .with_genesis_staker(
+                Address::from([4u8; 20]),
+                validator_address,
+                Coin::from_u64_unchecked(50),
+            );
+
+        let summary = genesis_builder.generate(env).unwrap().summary();
+
+        let total_stake = Coin::from_u64_unchecked(policy::VALIDATOR_DEPOSIT + 50);
+        assert_eq!(summary.total_stake, total_stake);
+        assert_eq!(
+            summary.total_supply,
+            total_stake + Coin::from_u64_unchecked(300)
+        );
+        assert_eq!(summary.num_validators, 1);
+        assert_eq!(summary.accounts_by_type[&AccountType::Basic], 2);
+        assert_eq!(summary.accounts_by_type[&AccountType::Staking], 1);
+        assert_eq!(summary.accounts_by_type[&AccountType::StakingValidator], 1);
+        assert_eq!(summary.accounts_by_type[&AccountType::StakingStaker], 1);
+        assert_eq!(
+            summary.accounts_by_type[&AccountType::StakingValidatorsStaker],
+            1
+        );
+        assert_eq!(summary.accounts_by_type.get(&AccountType::HTLC), None);
+    }
+
+    #[test]
+    fn it_delegates_stakers_to_their_validators() {
+        let env = VolatileEnvironment::new(10).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let validator_1 = Address::from([10u8; 20]);
+        let validator_2 = Address::from([20u8; 20]);
+
+        let mut genesis_builder = GenesisBuilder::default();
+        for validator_address in [&validator_1, &validator_2] {
+            genesis_builder.with_genesis_validator(
+                validator_address.clone(),
+                SchnorrPublicKey::from([0u8; 32]),
+                BlsKeyPair::generate(&mut rng).public_key,
+                Address::default(),
+            );
+        }
+        genesis_builder
+            .with_genesis_staker(
+                Address::from([2u8; 20]),
+                validator_1.clone(),
+                Coin::from_u64_unchecked(200),
+            )
+            .with_genesis_staker(
+                Address::from([1u8; 20]),
+                validator_1.clone(),
+                Coin::from_u64_unchecked(100),
+            )
+            .with_genesis_staker(
+                Address::from([3u8; 20]),
+                validator_2.clone(),
+                Coin::from_u64_unchecked(300),
+            );
+
+        let genesis = genesis_builder.generate(env).unwrap();
+
+        // The balance of every genesis account can be queried.
+        for (_, account) in &genesis.accounts {
+            account.balance();
+        }
+
+        let env = VolatileEnvironment::new(10).unwrap();
+        let accounts = Accounts::new(env.clone());
+        let mut txn = WriteTransaction::new(&env);
+        accounts.init(&mut txn, genesis.accounts);
+        txn.commit();
+
+        let txn = ReadTransaction::new(&env);
+        assert_eq!(
+            StakingContract::stakers_of(&accounts.tree, &txn, &validator_1),
+            vec![
+                (Address::from([1u8; 20]), Coin::from_u64_unchecked(100)),
+                (Address::from([2u8; 20]), Coin::from_u64_unchecked(200)),
+            ]
+        );
+        assert_eq!(
+            StakingContract::stakers_of(&accounts.tree, &txn, &validator_2),
+            vec![(Address::from([3u8; 20]), Coin::from_u64_unchecked(300))]
+        );
+    }
+}
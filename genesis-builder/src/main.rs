@@ -21,21 +21,27 @@ fn main() {
     let args = env::args().collect::<Vec<String>>();
 
     if let Some(file) = args.get(1) {
-        let GenesisInfo {
-            block,
-            hash,
-            accounts,
-        } = GenesisBuilder::new()
+        let genesis_info = GenesisBuilder::new()
             .with_config_file(file)
             .unwrap()
             .generate(env)
             .unwrap();
+        let summary = genesis_info.summary();
+
+        let GenesisInfo {
+            block,
+            hash,
+            accounts,
+        } = genesis_info;
 
         println!("Genesis Block: {}", hash);
         println!("{:#?}", block);
         println!();
         println!("Genesis Accounts:");
         println!("{:#?}", accounts);
+        println!();
+        println!("Genesis Summary:");
+        print!("{}", summary);
     } else {
         usage(args);
     }
@@ -6,10 +6,12 @@ use std::iter::Iterator;
 use std::str::FromStr;
 
 use hex::FromHex;
+use rand_core::{CryptoRng, RngCore};
 use thiserror::Error;
 
 use hash::{hash_typed_array, Blake2bHash, Blake2bHasher, Hasher};
 use macros::create_typed_array;
+use utils::key_rng::SecureGenerate;
 
 use crate::key_pair::KeyPair;
 use crate::PublicKey;
@@ -22,8 +24,8 @@ hash_typed_array!(Address);
 #[derive(Debug, Error)]
 pub enum AddressParseError {
     // User-friendly
-    #[error("Wrong country code")]
-    WrongCountryCode,
+    #[error("Not a user-friendly address")]
+    WrongFormat,
     #[error("Wrong length")]
     WrongLength,
     #[error("Invalid checksum")]
@@ -36,6 +38,31 @@ pub enum AddressParseError {
     UnknownFormat,
 }
 
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AddressError {
+    #[error("Not a user-friendly address")]
+    BadFormat,
+    #[error("Valid address format but wrong checksum")]
+    BadChecksum,
+}
+
+impl From<AddressError> for AddressParseError {
+    fn from(e: AddressError) -> Self {
+        match e {
+            AddressError::BadFormat => AddressParseError::WrongFormat,
+            AddressError::BadChecksum => AddressParseError::InvalidChecksum,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AddressGenerationError {
+    #[error("Prefix contains characters outside of the address alphabet")]
+    InvalidPrefix,
+    #[error("No matching address found after {0} attempts")]
+    MaxAttemptsReached(usize),
+}
+
 impl Address {
     const CCODE: &'static str = "NQ";
     const NIMIQ_ALPHABET: &'static str = "0123456789ABCDEFGHJKLMNPQRSTUVXY";
@@ -45,33 +72,54 @@ impl Address {
     }
 
     pub fn from_user_friendly_address(friendly_addr: &str) -> Result<Address, AddressParseError> {
-        let friendly_addr_wospace = str::replace(friendly_addr, " ", "");
-
-        if friendly_addr_wospace.len() != 36 {
-            return Err(AddressParseError::WrongLength);
-        }
-        if friendly_addr_wospace[0..2].to_uppercase() != Address::CCODE {
-            return Err(AddressParseError::WrongCountryCode);
-        }
-        let mut twisted_str = String::with_capacity(friendly_addr_wospace.len());
-        twisted_str.push_str(&friendly_addr_wospace[4..]);
-        twisted_str.push_str(&friendly_addr_wospace[..4]);
-        if Address::iban_check(&twisted_str) != 1 {
-            return Err(AddressParseError::InvalidChecksum);
-        }
+        Address::validate_friendly(friendly_addr)?;
+        let friendly_addr = Address::normalize_friendly(friendly_addr);
 
         let mut spec = data_encoding::Specification::new();
         spec.symbols.push_str(Address::NIMIQ_ALPHABET);
         let encoding = spec.encoding().unwrap();
 
         let b_vec = encoding
-            .decode(friendly_addr_wospace[4..].as_bytes())
-            .unwrap();
+            .decode(friendly_addr[4..].as_bytes())
+            .map_err(|_| AddressParseError::WrongFormat)?;
         let mut b = [0; 20];
         b.copy_from_slice(&b_vec[..b_vec.len()]);
         Ok(Address(b))
     }
 
+    /// Checks that the given string is a user-friendly address with a valid checksum, without
+    /// decoding it. Like `from_user_friendly_address`, spaces are ignored. Strings that don't have
+    /// the shape of a user-friendly address fail with `BadFormat`, so a `BadChecksum` always points
+    /// to a typo in an otherwise valid address.
+    pub fn validate_friendly(s: &str) -> Result<(), AddressError> {
+        let friendly_addr = Address::normalize_friendly(s);
+
+        if !friendly_addr.is_ascii()
+            || friendly_addr.len() != 36
+            || !friendly_addr.starts_with(Address::CCODE)
+            || !friendly_addr[2..4].bytes().all(|c| c.is_ascii_digit())
+            || !friendly_addr[4..]
+                .chars()
+                .all(|c| Address::NIMIQ_ALPHABET.contains(c))
+        {
+            return Err(AddressError::BadFormat);
+        }
+
+        let mut twisted_str = String::with_capacity(friendly_addr.len());
+        twisted_str.push_str(&friendly_addr[4..]);
+        twisted_str.push_str(&friendly_addr[..4]);
+        if Address::iban_check(&twisted_str) != 1 {
+            return Err(AddressError::BadChecksum);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the spaces of a user-friendly address and converts it to upper case.
+    fn normalize_friendly(s: &str) -> String {
+        str::replace(s, " ", "").to_uppercase()
+    }
+
     pub fn to_user_friendly_address(&self) -> String {
         let mut spec = data_encoding::Specification::new();
         spec.symbols.push_str(Address::NIMIQ_ALPHABET);
@@ -126,6 +174,38 @@ impl Address {
         tmp.parse::<u32>().unwrap()
     }
 
+    /// Generates key pairs until the user-friendly form of the address (after the country code
+    /// and check digits) starts with the given prefix, e.g. `b"TEST"`. The prefix is matched case
+    /// insensitively against the characters of the address alphabet. Every additional prefix
+    /// character makes the search 32 times longer, so the number of attempts is bounded by
+    /// `max_attempts`.
+    pub fn generate_with_prefix<R: RngCore + CryptoRng>(
+        prefix: &[u8],
+        max_attempts: usize,
+        rng: &mut R,
+    ) -> Result<(KeyPair, Address), AddressGenerationError> {
+        let prefix = prefix.to_ascii_uppercase();
+        if prefix.len() > 32
+            || !prefix
+                .iter()
+                .all(|c| Address::NIMIQ_ALPHABET.as_bytes().contains(c))
+        {
+            return Err(AddressGenerationError::InvalidPrefix);
+        }
+
+        for _ in 0..max_attempts {
+            let key_pair = KeyPair::generate(rng);
+            let address = Address::from(&key_pair);
+
+            let friendly_addr = address.to_user_friendly_address().replace(' ', "");
+            if friendly_addr.as_bytes()[4..].starts_with(&prefix) {
+                return Ok((key_pair, address));
+            }
+        }
+
+        Err(AddressGenerationError::MaxAttemptsReached(max_attempts))
+    }
+
     pub fn from_any_str(s: &str) -> Result<Address, AddressParseError> {
         Address::from_user_friendly_address(&String::from(s))
             .or_else(|_| Address::from_hex(s))
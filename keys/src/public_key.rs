@@ -11,7 +11,7 @@ use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteByte
 use hash::{Hash, SerializeContent};
 
 use crate::errors::{KeysError, ParseError};
-use crate::{PrivateKey, Signature};
+use crate::{PrivateKey, SecureRng, Signature};
 
 #[derive(Clone, Copy)]
 pub struct PublicKey(pub ed25519_zebra::VerificationKeyBytes);
@@ -27,6 +27,19 @@ impl PublicKey {
         }
     }
 
+    /// Verifies all given signatures at once. This is significantly faster than verifying them
+    /// one by one, but it only tells whether all of the signatures are valid.
+    pub fn verify_batch<'a, I>(items: I) -> bool
+    where
+        I: IntoIterator<Item = (&'a PublicKey, &'a Signature, &'a [u8])>,
+    {
+        let mut verifier = ed25519_zebra::batch::Verifier::new();
+        for (public_key, signature, data) in items {
+            verifier.queue((*public_key.as_zebra(), *signature.as_zebra(), data));
+        }
+        verifier.verify(SecureRng::default()).is_ok()
+    }
+
     #[inline]
     pub fn as_bytes(&self) -> &[u8; PublicKey::SIZE] {
         self.0
@@ -1,5 +1,9 @@
-use nimiq_keys::{Address, KeyPair, PrivateKey, PublicKey, SecureGenerate, Signature};
+use nimiq_keys::{
+    Address, AddressError, AddressGenerationError, AddressParseError, KeyPair, PrivateKey,
+    PublicKey, SecureGenerate, Signature,
+};
 use nimiq_test_log::test;
+use rand::{rngs::StdRng, SeedableRng};
 
 mod multisig;
 
@@ -127,3 +131,81 @@ fn it_computes_friendly_addresses() {
         addr2.to_user_friendly_address()
     );
 }
+
+#[test]
+fn it_validates_friendly_addresses() {
+    assert_eq!(
+        Address::validate_friendly("NQ05 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PNR"),
+        Ok(())
+    );
+    assert_eq!(
+        Address::validate_friendly("nq05563u530yxdrtl7gqm6heyrnu20fe4pnr"),
+        Ok(())
+    );
+
+    // Well-formed addresses with a typo.
+    assert_eq!(
+        Address::validate_friendly("NQ05 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PNS"),
+        Err(AddressError::BadChecksum)
+    );
+    assert_eq!(
+        Address::validate_friendly("NQ06 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PNR"),
+        Err(AddressError::BadChecksum)
+    );
+
+    // Malformed addresses.
+    for s in [
+        "",
+        "NQ05 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PN",
+        "NQ05 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PNRR",
+        "XX05 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PNR",
+        "NQ0A 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PNR",
+        "NQ05 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PNO",
+        "NQ05 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PN-",
+        "NQ05 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PNÄ",
+        "e9910f2452419823dc2e5534633210074ae9527f",
+    ] {
+        assert_eq!(Address::validate_friendly(s), Err(AddressError::BadFormat));
+    }
+}
+
+#[test]
+fn it_parses_the_friendly_addresses_it_validates() {
+    let address =
+        Address::from_user_friendly_address("nq05563u530yxdrtl7gqm6heyrnu20fe4pnr").unwrap();
+    assert_eq!(
+        address.to_user_friendly_address(),
+        "NQ05 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PNR"
+    );
+
+    assert!(matches!(
+        Address::from_user_friendly_address("NQ05 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PNS"),
+        Err(AddressParseError::InvalidChecksum)
+    ));
+    assert!(matches!(
+        Address::from_user_friendly_address("NQ05 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PNO"),
+        Err(AddressParseError::WrongFormat)
+    ));
+    assert!(matches!(
+        Address::from_user_friendly_address("XX05 563U 530Y XDRT L7GQ M6HE YRNU 20FE 4PNR"),
+        Err(AddressParseError::WrongFormat)
+    ));
+}
+
+#[test]
+fn it_generates_addresses_with_a_prefix() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let (key_pair, address) = Address::generate_with_prefix(b"q3", 100_000, &mut rng).unwrap();
+    assert_eq!(address, Address::from(&key_pair));
+    assert!(address.to_user_friendly_address()[5..].starts_with("Q3"));
+
+    assert_eq!(
+        Address::generate_with_prefix(b"I", 100_000, &mut rng),
+        Err(AddressGenerationError::InvalidPrefix)
+    );
+    assert_eq!(
+        Address::generate_with_prefix(b"Q3", 0, &mut rng),
+        Err(AddressGenerationError::MaxAttemptsReached(0))
+    );
+}
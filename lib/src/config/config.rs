@@ -11,7 +11,11 @@ use strum_macros::Display;
 use beserial::Deserialize;
 #[cfg(feature = "validator")]
 use nimiq_bls::{KeyPair as BlsKeyPair, SecretKey as BlsSecretKey};
-use nimiq_database::{mdbx::MdbxEnvironment, volatile::VolatileEnvironment, Environment};
+use nimiq_database::{
+    mdbx::{Durability, MdbxEnvironment},
+    volatile::VolatileEnvironment,
+    Environment,
+};
 use nimiq_keys::{Address, KeyPair, PrivateKey};
 use nimiq_mempool::{config::MempoolConfig, filter::MempoolRules};
 use nimiq_network_libp2p::{Keypair as IdentityKeypair, Multiaddr};
@@ -251,6 +255,17 @@ pub struct DatabaseConfig {
     /// Recommended: 600
     #[builder(default = "600")]
     max_readers: u32,
+
+    /// How durably commits are flushed to disk. Default: Flushing is left to the operating system
+    /// and a system crash may corrupt the database.
+    #[builder(default)]
+    durability: Durability,
+
+    /// Size of the database pages in bytes. Only applies when the database is created.
+    /// Default: The page size of the operating system (usually 4 KiB), which suits the small,
+    /// random accesses of the accounts trie.
+    #[builder(default)]
+    page_size: Option<usize>,
 }
 
 impl Default for DatabaseConfig {
@@ -260,6 +275,8 @@ impl Default for DatabaseConfig {
             size: 1024 * 1024 * 1024 * 1024,
             max_dbs: 12,
             max_readers: 600,
+            durability: Durability::default(),
+            page_size: None,
         }
     }
 }
@@ -273,6 +290,11 @@ impl From<Option<config_file::DatabaseSettings>> for DatabaseConfig {
                 size: db_settings.size.unwrap_or(default.size),
                 max_dbs: db_settings.max_dbs.unwrap_or(default.max_dbs),
                 max_readers: db_settings.max_readers.unwrap_or(default.max_readers),
+                durability: db_settings
+                    .durability
+                    .map(Durability::from)
+                    .unwrap_or(default.durability),
+                page_size: db_settings.page_size.or(default.page_size),
             }
         } else {
             default
@@ -345,11 +367,13 @@ impl StorageConfig {
                         ))
                     })?
                     .to_string();
-                MdbxEnvironment::new_with_max_readers(
+                MdbxEnvironment::new_with_page_size(
                     &db_path,
                     db_config.size,
                     db_config.max_dbs,
                     db_config.max_readers,
+                    db_config.durability,
+                    db_config.page_size,
                 )?
             }
             _ => return Err(self.not_available()),
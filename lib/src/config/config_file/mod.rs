@@ -8,6 +8,7 @@ use serde_derive::Deserialize;
 use thiserror::Error;
 use url::Url;
 
+use nimiq_database::mdbx::Durability;
 use nimiq_mempool::mempool::Mempool;
 use nimiq_mempool::{
     config::MempoolConfig,
@@ -374,6 +375,28 @@ pub struct DatabaseSettings {
     pub size: Option<usize>,
     pub max_dbs: Option<u32>,
     pub max_readers: Option<u32>,
+    pub durability: Option<DatabaseDurability>,
+    pub page_size: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DatabaseDurability {
+    Durable,
+    NoMetaSync,
+    SafeNoSync,
+    UtterlyNoSync,
+}
+
+impl From<DatabaseDurability> for Durability {
+    fn from(durability: DatabaseDurability) -> Self {
+        match durability {
+            DatabaseDurability::Durable => Self::Durable,
+            DatabaseDurability::NoMetaSync => Self::NoMetaSync,
+            DatabaseDurability::SafeNoSync => Self::SafeNoSync,
+            DatabaseDurability::UtterlyNoSync => Self::UtterlyNoSync,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -458,55 +458,50 @@ impl Mempool {
     ///
     /// Returns the highest fee per byte up to max_bytes transactions and removes them from the mempool
     pub fn get_transactions_for_block(&self, max_bytes: usize) -> Vec<Transaction> {
-        let mut tx_vec = vec![];
-
-        let state = self.state.upgradable_read();
+        let mut state = self.state.write();
 
         if state.transactions.is_empty() {
             log::debug!("Requesting txns and there are no txns in the mempool ");
-            return tx_vec;
+            return vec![];
         }
 
-        let mut size = 0_usize;
-
-        let mut mempool_state_upgraded = RwLockUpgradableReadGuard::upgrade(state);
-
-        loop {
-            // Get the hash of the highest paying transaction.
-            let tx_hash = match mempool_state_upgraded.best_transactions.peek() {
-                None => {
-                    break;
-                }
-                Some((tx_hash, _)) => tx_hash.clone(),
-            };
-
-            // Get the transaction.
-            let tx = mempool_state_upgraded.get(&tx_hash).unwrap().clone();
-
-            // Calculate size. If we can't fit the transaction in the block, then we stop here.
-            // TODO: We can optimize this. There might be a smaller transaction that still fits.
-            size += tx.serialized_size();
-
-            if size > max_bytes {
-                break;
-            }
-
-            // Remove the transaction from the mempool.
-            mempool_state_upgraded.remove(&tx_hash);
-
-            // Push the transaction to our output vector.
-            tx_vec.push(tx);
-        }
+        let tx_vec: Vec<Transaction> = state
+            .best_transactions_for_block(max_bytes)
+            .iter()
+            .map(|tx_hash| state.remove(tx_hash).unwrap())
+            .collect();
 
         debug!(
             returned_txs = tx_vec.len(),
-            remaining_txs = mempool_state_upgraded.transactions.len(),
+            remaining_txs = state.transactions.len(),
             "Returned transactions from mempool"
         );
 
         tx_vec
     }
 
+    /// Returns the transactions that `get_transactions_for_block` would return for the same
+    /// `max_bytes`, but leaves them in the mempool.
+    pub fn peek_transactions_for_block(&self, max_bytes: usize) -> Vec<Transaction> {
+        let mut state = self.state.write();
+
+        state
+            .best_transactions_for_block(max_bytes)
+            .iter()
+            .map(|tx_hash| state.get(tx_hash).unwrap().clone())
+            .collect()
+    }
+
+    /// Removes the given transactions from the mempool. Transactions that aren't in the mempool
+    /// are ignored.
+    pub fn remove_transactions(&self, tx_hashes: &[Blake2bHash]) {
+        let mut state = self.state.write();
+
+        for tx_hash in tx_hashes {
+            state.remove(tx_hash);
+        }
+    }
+
     /// Adds a transaction to the Mempool.
     pub async fn add_transaction(&self, transaction: Transaction) -> Result<(), VerifyErr> {
         let blockchain = Arc::clone(&self.blockchain);
@@ -752,6 +747,38 @@ impl MempoolState {
         Some(tx)
     }
 
+    /// Returns the hashes of the highest paying transactions that fit into `max_bytes`, in the
+    /// order in which they are included in a block. The transactions are popped from
+    /// `best_transactions` and pushed back with the same priority afterwards, so that only the
+    /// selected transactions are visited, and the queue is left unchanged.
+    fn best_transactions_for_block(&mut self, max_bytes: usize) -> Vec<Blake2bHash> {
+        let mut popped = vec![];
+        let mut num_selected = 0;
+        let mut size = 0_usize;
+
+        while let Some((tx_hash, order)) = self.best_transactions.pop() {
+            // Calculate size. If we can't fit the transaction in the block, then we stop here.
+            // TODO: We can optimize this. There might be a smaller transaction that still fits.
+            size += self.transactions[&tx_hash].serialized_size();
+            popped.push((tx_hash, order));
+
+            if size > max_bytes {
+                break;
+            }
+            num_selected += 1;
+        }
+
+        let mut selected = Vec::with_capacity(num_selected);
+        for (tx_hash, order) in popped {
+            if selected.len() < num_selected {
+                selected.push(tx_hash.clone());
+            }
+            self.best_transactions.push(tx_hash, order);
+        }
+
+        selected
+    }
+
     // Removes all the transactions sent by some specific address
     pub(crate) fn remove_sender_txns(&mut self, sender_address: &Address) {
         if let Some(sender_state) = &self.state_by_sender.remove(sender_address) {
@@ -825,7 +852,7 @@ pub(crate) struct SenderPendingState {
 // TODO: Maybe use this wrapper to do more fine ordering. For example, we might prefer small size
 //       transactions over large size transactions (assuming they have the same fee per byte). Or
 //       we might prefer basic transactions over staking contract transactions, etc, etc.
-#[derive(PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct BestTxOrder {
     fee_per_byte: f64,
     insertion_order: u64,
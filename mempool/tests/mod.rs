@@ -8,7 +8,7 @@ use tokio_stream::wrappers::ReceiverStream;
 
 use beserial::{Deserialize, Serialize};
 use nimiq_block::{Block, MicroBlock, MicroBody, MicroHeader};
-use nimiq_block_production::BlockProducer;
+use nimiq_block_production::{BlockProducer, ProducerConfig};
 use nimiq_blockchain::{Blockchain, PushResult};
 use nimiq_bls::KeyPair as BlsKeyPair;
 use nimiq_database::volatile::VolatileEnvironment;
@@ -1504,3 +1504,135 @@ async fn applies_total_tx_size_limits() {
     }
     assert_eq!(mempool_txns.len(), (num_txns - 1) as usize);
 }
+
+/// Creates a blockchain and a mempool with three transactions from different senders, with fees
+/// 1, 2 and 3. Returns them together with the transactions and their total size.
+async fn mempool_with_three_transactions(
+) -> (Arc<RwLock<Blockchain>>, Mempool, Vec<Transaction>, usize) {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let mut genesis_builder = GenesisBuilder::default();
+
+    // Three senders that send one transaction each, with increasing fees.
+    let num_txns = 3;
+    let recipient_accounts = generate_accounts(vec![0; num_txns], &mut genesis_builder, false);
+    let sender_accounts = generate_accounts(vec![100; num_txns], &mut genesis_builder, true);
+
+    let mempool_transactions = (0..num_txns)
+        .map(|i| TestTransaction {
+            fee: (i + 1) as u64,
+            value: 10,
+            recipient: recipient_accounts[i].clone(),
+            sender: sender_accounts[i].clone(),
+        })
+        .collect();
+    let (txns, txns_len) = generate_transactions(mempool_transactions, true);
+
+    let mut rng = StdRng::seed_from_u64(0);
+    genesis_builder.with_genesis_validator(
+        Address::from(&SchnorrKeyPair::generate(&mut rng)),
+        SchnorrPublicKey::from([0u8; 32]),
+        BlsKeyPair::generate(&mut rng).public_key,
+        Address::default(),
+    );
+
+    let genesis_info = genesis_builder.generate(env.clone()).unwrap();
+
+    let blockchain = Arc::new(RwLock::new(
+        Blockchain::with_genesis(
+            env.clone(),
+            Arc::new(OffsetTime::new()),
+            NetworkId::UnitAlbatross,
+            genesis_info.block,
+            genesis_info.accounts,
+        )
+        .unwrap(),
+    ));
+
+    let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
+    for tx in txns.iter().cloned() {
+        mempool.add_transaction(tx).await.unwrap();
+    }
+
+    (blockchain, mempool, txns, txns_len)
+}
+
+#[test(tokio::test)]
+async fn it_produces_blocks_from_the_mempool() {
+    let (blockchain, mempool, txns, txns_len) = mempool_with_three_transactions().await;
+
+    // Only two of the three transactions fit into the block body.
+    let empty_body_size = MicroBody {
+        fork_proofs: vec![],
+        transactions: vec![],
+    }
+    .serialized_size();
+    let producer = BlockProducer::new_with_config(
+        signing_key(),
+        voting_key(),
+        ProducerConfig {
+            max_body_size: empty_body_size + 2 * txns_len / txns.len(),
+            ..Default::default()
+        },
+    );
+
+    let block = {
+        let blockchain = blockchain.read();
+        producer.next_micro_block_from_mempool(
+            &blockchain,
+            &mempool,
+            blockchain.time.now(),
+            0,
+            None,
+            vec![],
+            vec![],
+        )
+    };
+
+    // The two transactions with the highest fees are included and removed from the mempool. The
+    // one that didn't fit into the block stays there.
+    let mut expected = vec![txns[2].clone(), txns[1].clone()];
+    expected.sort_unstable();
+    assert_eq!(block.body.unwrap().transactions, expected);
+
+    assert_eq!(mempool.num_transactions(), 1);
+    assert!(mempool.contains_transaction_by_hash(&txns[0].hash()));
+    assert!(!mempool.contains_transaction_by_hash(&txns[1].hash()));
+    assert!(!mempool.contains_transaction_by_hash(&txns[2].hash()));
+}
+
+#[test(tokio::test)]
+async fn it_keeps_rejected_transactions_in_the_mempool() {
+    let (blockchain, mempool, txns, txns_len) = mempool_with_three_transactions().await;
+
+    // The transaction with the lowest fee pays too little to be included.
+    let tx_size = txns_len / txns.len();
+    let producer = BlockProducer::new_with_config(
+        signing_key(),
+        voting_key(),
+        ProducerConfig {
+            min_fee_per_byte: 1.5 / tx_size as f64,
+            ..Default::default()
+        },
+    );
+
+    let block = {
+        let blockchain = blockchain.read();
+        producer.next_micro_block_from_mempool(
+            &blockchain,
+            &mempool,
+            blockchain.time.now(),
+            0,
+            None,
+            vec![],
+            vec![],
+        )
+    };
+
+    let mut expected = vec![txns[2].clone(), txns[1].clone()];
+    expected.sort_unstable();
+    assert_eq!(block.body.unwrap().transactions, expected);
+
+    // The rejected transaction is still valid, so it isn't dropped from the mempool.
+    assert_eq!(mempool.num_transactions(), 1);
+    assert!(mempool.contains_transaction_by_hash(&txns[0].hash()));
+}
@@ -1,6 +1,6 @@
 use std::sync::{Arc, RwLock};
 
-use nimiq_block::{Block, MacroBlock};
+use nimiq_block::{AggPkCache, Block, MacroBlock};
 use nimiq_blockchain::ChainInfo;
 use nimiq_genesis::NetworkInfo;
 use nimiq_primitives::networks::NetworkId;
@@ -28,6 +28,8 @@ pub struct NanoBlockchain {
     pub genesis_block: Block,
     // The chain store is a database containing all of the chain infos in the current batch.
     pub chain_store: RwLock<ChainStore>,
+    // The aggregated public keys of the signers of the macro blocks in the current epoch.
+    pub(crate) agg_pk_cache: AggPkCache,
 }
 
 /// Implements methods to start a Blockchain.
@@ -58,6 +60,7 @@ impl NanoBlockchain {
             current_validators: genesis_block.validators(),
             genesis_block,
             chain_store: RwLock::new(chain_store),
+            agg_pk_cache: AggPkCache::new(),
         }
     }
 }
@@ -149,10 +149,14 @@ impl NanoBlockchain {
             }
         }
 
-        // Verify the justification.
-        if !TendermintProof::verify(
+        // Verify the justification. The signers of the macro blocks of an epoch often repeat, so
+        // their aggregated public keys are cached.
+        let validators = self.current_validators().unwrap();
+        if !TendermintProof::verify_cached(
             block.unwrap_macro_ref(),
-            &self.current_validators().unwrap(),
+            &validators,
+            policy::TWO_F_PLUS_ONE,
+            &mut self.agg_pk_cache,
         ) {
             return Err(PushError::InvalidBlock(BlockError::InvalidJustification));
         }
@@ -1,12 +1,46 @@
+use ark_crypto_primitives::prf::Blake2sWithParameterBlock;
 use ark_ec::ProjectiveCurve;
 use ark_ff::fields::PrimeField;
 use ark_mnt6_753::{Fr, G1Projective};
+use blake2_rfc::blake2s::Blake2s;
 use num_traits::identities::Zero;
 
 use nimiq_bls::Signature;
-use nimiq_hash::{Blake2sHash, Hash, HashOutput};
+use nimiq_hash::{Blake2sHash, HashOutput};
 use nimiq_primitives::policy::SLOTS;
 
+/// The Blake2s personalization of both hashes in `MacroBlock::hash`. It is shared with the macro
+/// block gadget, so that the off-circuit and the on-circuit hash can't diverge. Note that the
+/// validators sign macro blocks with a plain Blake2s hash, which has an all-zero personalization.
+pub const MACRO_BLOCK_HASH_PERSONALIZATION: [u8; 8] = [0; 8];
+
+/// Returns the Blake2s parameters of both hashes in `MacroBlock::hash`. Apart from the
+/// personalization, these are the parameters of a plain Blake2s hash with a 32 byte digest.
+pub fn macro_block_hash_parameters() -> Blake2sWithParameterBlock {
+    Blake2sWithParameterBlock {
+        digest_length: 32,
+        key_length: 0,
+        fan_out: 1,
+        depth: 1,
+        leaf_length: 0,
+        node_offset: 0,
+        xof_digest_length: 0,
+        node_depth: 0,
+        inner_length: 0,
+        salt: [0; 8],
+        personalization: MACRO_BLOCK_HASH_PERSONALIZATION,
+    }
+}
+
+/// Hashes the given bytes with the parameters of `macro_block_hash_parameters`.
+fn macro_block_blake2s(bytes: &[u8]) -> Blake2sHash {
+    let mut state = Blake2s::with_parameter_block(&macro_block_hash_parameters().parameters());
+
+    state.update(bytes);
+
+    Blake2sHash::from(state.finalize().as_bytes())
+}
+
 /// A struct representing an election macro block in Albatross.
 #[derive(Clone)]
 pub struct MacroBlock {
@@ -67,7 +101,7 @@ impl MacroBlock {
 
         first_bytes.extend(pk_tree_root);
 
-        let first_hash = first_bytes.hash::<Blake2sHash>();
+        let first_hash = macro_block_blake2s(&first_bytes);
 
         let mut second_bytes = vec![0x04];
 
@@ -79,7 +113,7 @@ impl MacroBlock {
 
         second_bytes.extend_from_slice(first_hash.as_bytes());
 
-        let second_hash = second_bytes.hash::<Blake2sHash>();
+        let second_hash = macro_block_blake2s(&second_bytes);
 
         Signature::hash_to_g1(second_hash)
     }
@@ -99,7 +133,7 @@ impl Default for MacroBlock {
 
 #[cfg(test)]
 mod tests {
-    use crate::pk_tree_construct;
+    use crate::{pk_tree_construct, MACRO_BLOCK_HASH_PERSONALIZATION};
     use nimiq_block::{MacroBlock as Block, MacroBody as Body, MultiSignature, TendermintProof};
     use nimiq_bls::{AggregateSignature, KeyPair};
     use nimiq_collections::BitSet;
@@ -190,4 +224,19 @@ mod tests {
 
         assert!(TendermintProof::verify(&block, &validators));
     }
+
+    #[test]
+    fn macro_block_hashes_match_the_signed_hashes() {
+        // Validators sign a plain Blake2s hash, so the personalization must stay all-zero for
+        // the nano sync circuits to accept real signatures.
+        use nimiq_hash::{Blake2sHash, Hash};
+
+        let bytes = b"macro block".to_vec();
+
+        assert_eq!(MACRO_BLOCK_HASH_PERSONALIZATION, [0; 8]);
+        assert_eq!(
+            super::macro_block_blake2s(&bytes),
+            bytes.hash::<Blake2sHash>()
+        );
+    }
 }
@@ -4,7 +4,6 @@ use std::borrow::Borrow;
 use ark_crypto_primitives::prf::blake2s::constraints::{
     evaluate_blake2s_with_parameters, OutputVar,
 };
-use ark_crypto_primitives::prf::Blake2sWithParameterBlock;
 use ark_ff::One;
 use ark_mnt4_753::Fr as MNT4Fr;
 use ark_mnt6_753::constraints::{FqVar, G1Var, G2Var};
@@ -15,7 +14,7 @@ use ark_r1cs_std::prelude::{
 };
 use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
 
-use nimiq_nano_primitives::MacroBlock;
+use nimiq_nano_primitives::{macro_block_hash_parameters, MacroBlock};
 use nimiq_primitives::policy::{SLOTS, TWO_F_PLUS_ONE};
 
 use crate::gadgets::mnt4::{CheckSigGadget, HashToCurve};
@@ -75,20 +74,8 @@ impl MacroBlockGadget {
         cs: ConstraintSystemRef<MNT4Fr>,
         pk_tree_root: &[Boolean<MNT4Fr>],
     ) -> Result<G1Var, SynthesisError> {
-        // Initialize Blake2s parameters.
-        let blake2s_parameters = Blake2sWithParameterBlock {
-            digest_length: 32,
-            key_length: 0,
-            fan_out: 1,
-            depth: 1,
-            leaf_length: 0,
-            node_offset: 0,
-            xof_digest_length: 0,
-            node_depth: 0,
-            inner_length: 0,
-            salt: [0; 8],
-            personalization: [0; 8],
-        };
+        // Initialize Blake2s parameters. They are shared with the off-circuit hash.
+        let blake2s_parameters = macro_block_hash_parameters();
 
         // Initialize Boolean vector.
         let mut first_bits = vec![];
@@ -322,13 +309,28 @@ mod tests {
     use ark_std::{test_rng, UniformRand};
     use rand::RngCore;
 
+    use nimiq_block::MacroBlock as Block;
     use nimiq_bls::utils::bytes_to_bits;
-    use nimiq_nano_primitives::MacroBlock;
-    use nimiq_primitives::policy::{SLOTS, TWO_F_PLUS_ONE};
+    use nimiq_hash::{Blake2sHash, Hash};
+    use nimiq_nano_primitives::{pk_tree_construct, MacroBlock};
+    use nimiq_primitives::policy::{BLOCKS_PER_EPOCH, SLOTS, TWO_F_PLUS_ONE};
     use nimiq_test_log::test;
 
     use super::*;
 
+    /// Creates a random public keys tree root and a distinct random header hash.
+    fn random_pk_tree_root_and_header_hash<R: RngCore>(rng: &mut R) -> (Vec<u8>, [u8; 32]) {
+        let mut pk_tree_root = [0u8; 95];
+        rng.fill_bytes(&mut pk_tree_root);
+
+        let mut header_hash = [0u8; 32];
+        rng.fill_bytes(&mut header_hash);
+
+        assert_ne!(header_hash[..], pk_tree_root[..32]);
+
+        (pk_tree_root.to_vec(), header_hash)
+    }
+
     #[test]
     fn block_hash_works() {
         // Initialize the constraint system.
@@ -338,12 +340,7 @@ mod tests {
         let rng = &mut test_rng();
 
         // Create block parameters.
-        let mut bytes = [1u8; 95];
-        rng.fill_bytes(&mut bytes);
-        let pk_tree_root = bytes.to_vec();
-
-        let mut header_hash = [2u8; 32];
-        rng.fill_bytes(&mut header_hash);
+        let (pk_tree_root, header_hash) = random_pk_tree_root_and_header_hash(rng);
 
         let mut bytes = [3u8; SLOTS as usize / 8];
         rng.fill_bytes(&mut bytes);
@@ -373,6 +370,49 @@ mod tests {
         assert_eq!(primitive_hash, gadget_hash.value().unwrap())
     }
 
+    #[test]
+    fn blake2s_matches_the_native_hash_of_a_macro_block() {
+        // Initialize the constraint system.
+        let cs = ConstraintSystem::<MNT4Fr>::new_ref();
+
+        // Create random number generator.
+        let rng = &mut test_rng();
+
+        // The input of the first hash of an election block: its header hash and the public key
+        // tree root of its validators.
+        let mut block = Block::default();
+        block.header.block_number = BLOCKS_PER_EPOCH;
+        let header_hash: [u8; 32] = block.hash().into();
+
+        let public_keys = (0..SLOTS).map(|_| G2Projective::rand(rng)).collect();
+        let pk_tree_root = pk_tree_construct(public_keys);
+
+        let mut bytes = header_hash.to_vec();
+        bytes.extend(&pk_tree_root);
+
+        // Calculate the hash natively, the same way as the hash the validators sign.
+        let native_hash = bytes.hash::<Blake2sHash>();
+
+        // Calculate the hash in the circuit with the shared parameters.
+        let bits = Vec::<Boolean<MNT4Fr>>::new_witness(cs, || Ok(bytes_to_bits(&bytes))).unwrap();
+
+        let gadget_hash = evaluate_blake2s_with_parameters(
+            &reverse_inner_byte_order(&bits),
+            &macro_block_hash_parameters().parameters(),
+        )
+        .unwrap();
+
+        let gadget_hash_bits: Vec<Boolean<MNT4Fr>> = gadget_hash
+            .iter()
+            .flat_map(|int| int.to_bits_le())
+            .collect();
+
+        assert_eq!(
+            reverse_inner_byte_order(&gadget_hash_bits).value().unwrap(),
+            bytes_to_bits(native_hash.as_bytes())
+        );
+    }
+
     #[test]
     fn block_verify() {
         // Initialize the constraint system.
@@ -391,12 +431,7 @@ mod tests {
 
         let round_number = u32::rand(rng);
 
-        let mut bytes = [0u8; 95];
-        rng.fill_bytes(&mut bytes);
-        let pk_tree_root = bytes.to_vec();
-
-        let mut header_hash = [0u8; 32];
-        rng.fill_bytes(&mut header_hash);
+        let (pk_tree_root, header_hash) = random_pk_tree_root_and_header_hash(rng);
 
         let mut agg_pk = G2Projective::zero();
 
@@ -443,12 +478,7 @@ mod tests {
 
         let round_number = u32::rand(rng);
 
-        let mut bytes = [0u8; 95];
-        rng.fill_bytes(&mut bytes);
-        let pk_tree_root = bytes.to_vec();
-
-        let mut header_hash = [0u8; 32];
-        rng.fill_bytes(&mut header_hash);
+        let (pk_tree_root, header_hash) = random_pk_tree_root_and_header_hash(rng);
 
         let mut agg_pk = G2Projective::zero();
 
@@ -498,12 +528,7 @@ mod tests {
 
         let round_number = u32::rand(rng);
 
-        let mut bytes = [0u8; 95];
-        rng.fill_bytes(&mut bytes);
-        let pk_tree_root = bytes.to_vec();
-
-        let mut header_hash = [0u8; 32];
-        rng.fill_bytes(&mut header_hash);
+        let (pk_tree_root, header_hash) = random_pk_tree_root_and_header_hash(rng);
 
         let mut agg_pk = G2Projective::zero();
 
@@ -553,12 +578,7 @@ mod tests {
 
         let round_number = u32::rand(rng);
 
-        let mut bytes = [0u8; 95];
-        rng.fill_bytes(&mut bytes);
-        let pk_tree_root = bytes.to_vec();
-
-        let mut header_hash = [0u8; 32];
-        rng.fill_bytes(&mut header_hash);
+        let (pk_tree_root, header_hash) = random_pk_tree_root_and_header_hash(rng);
 
         let mut agg_pk = G2Projective::zero();
 
@@ -610,12 +630,7 @@ mod tests {
 
         let round_number = u32::rand(rng);
 
-        let mut bytes = [0u8; 95];
-        rng.fill_bytes(&mut bytes);
-        let pk_tree_root = bytes.to_vec();
-
-        let mut header_hash = [0u8; 32];
-        rng.fill_bytes(&mut header_hash);
+        let (pk_tree_root, header_hash) = random_pk_tree_root_and_header_hash(rng);
 
         let mut agg_pk = G2Projective::zero();
 
@@ -665,12 +680,7 @@ mod tests {
 
         let round_number = u32::rand(rng);
 
-        let mut bytes = [0u8; 95];
-        rng.fill_bytes(&mut bytes);
-        let pk_tree_root = bytes.to_vec();
-
-        let mut header_hash = [0u8; 32];
-        rng.fill_bytes(&mut header_hash);
+        let (pk_tree_root, header_hash) = random_pk_tree_root_and_header_hash(rng);
 
         let mut agg_pk = G2Projective::zero();
 
@@ -722,12 +732,7 @@ mod tests {
 
         let round_number = u32::rand(rng);
 
-        let mut bytes = [0u8; 95];
-        rng.fill_bytes(&mut bytes);
-        let pk_tree_root = bytes.to_vec();
-
-        let mut header_hash = [0u8; 32];
-        rng.fill_bytes(&mut header_hash);
+        let (pk_tree_root, header_hash) = random_pk_tree_root_and_header_hash(rng);
 
         let mut agg_pk = G2Projective::zero();
 
@@ -777,12 +782,7 @@ mod tests {
 
         let round_number = u32::rand(rng);
 
-        let mut bytes = [0u8; 95];
-        rng.fill_bytes(&mut bytes);
-        let pk_tree_root = bytes.to_vec();
-
-        let mut header_hash = [0u8; 32];
-        rng.fill_bytes(&mut header_hash);
+        let (pk_tree_root, header_hash) = random_pk_tree_root_and_header_hash(rng);
 
         let mut agg_pk = G2Projective::zero();
 
@@ -1,4 +1,5 @@
 use std::io;
+use std::ops::Range;
 
 use ark_groth16::Proof;
 use ark_mnt6_753::MNT6_753;
@@ -6,12 +7,19 @@ use ark_relations::r1cs::SynthesisError;
 use ark_serialize::SerializationError;
 use thiserror::Error;
 
+mod proof;
 #[cfg(feature = "prover")]
 mod prove;
 #[cfg(feature = "prover")]
 mod setup;
+#[cfg(feature = "prover")]
+mod thread_pool;
 mod verify;
 
+pub use proof::{PROOF_MAGIC, PROOF_VERSION};
+#[cfg(feature = "prover")]
+pub use thread_pool::NanoZKPThreadPool;
+
 /// This the main struct for the nano-zkp crate. It provides methods to setup (create the
 /// proving and verifying keys), create proofs and verify proofs for the nano sync circuit.
 pub struct NanoZKP;
@@ -19,6 +27,32 @@ pub struct NanoZKP;
 /// This is the proof type for the NanoZKP. It is just an alias, for convenience.
 pub type NanoProof = Proof<MNT6_753>;
 
+/// A proof for a segment of the chain between two checkpoints. The recursion of the nano sync
+/// program restarts at every checkpoint, so the proof only attests the transition from the state
+/// at the start of the segment to the state at its end. Both states are given as state
+/// commitments.
+#[derive(Clone, Debug)]
+pub struct NanoCheckpointProof {
+    pub initial_commitment: Vec<u8>,
+    pub final_commitment: Vec<u8>,
+    pub proof: NanoProof,
+}
+
+impl NanoCheckpointProof {
+    /// Splits a chain of `num_epochs` epochs into the segments between two checkpoints, which are
+    /// `interval` epochs apart. Returns the range of epoch indices of each segment, the last
+    /// segment may be shorter than `interval`. `NanoZKP::prove_checkpoint` creates one proof per
+    /// segment.
+    pub fn segments(interval: usize, num_epochs: usize) -> Vec<Range<usize>> {
+        assert!(interval > 0, "The checkpoint interval must be positive");
+
+        (0..num_epochs)
+            .step_by(interval)
+            .map(|start| start..usize::min(start + interval, num_epochs))
+            .collect()
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum NanoZKPError {
     #[error("filesystem error")]
@@ -27,4 +61,28 @@ pub enum NanoZKPError {
     Serialization(#[from] SerializationError),
     #[error("circuit error")]
     Circuit(#[from] SynthesisError),
+    #[error("invalid proof: {0}")]
+    InvalidProof(VerificationFailure),
+    #[error("invalid proof header")]
+    InvalidProofHeader,
+    #[error("incompatible proof version: expected {expected}, got {got}")]
+    IncompatibleProofVersion { expected: u8, got: u8 },
+    #[cfg(feature = "prover")]
+    #[error("thread pool error")]
+    ThreadPool(#[from] rayon::ThreadPoolBuildError),
+}
+
+/// The reason why a proof failed to verify.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum VerificationFailure {
+    /// The public inputs don't match the verifying key. Either the given verifying key commitment
+    /// is not the commitment of the verifying key being used or the number of inputs is wrong.
+    #[error("public input mismatch")]
+    PublicInputMismatch,
+    /// The public inputs are well-formed, but the Groth16 pairing check failed.
+    #[error("pairing check failed")]
+    PairingCheck,
+    /// The checkpoint proofs don't form a chain that starts at the trusted state.
+    #[error("broken checkpoint chain")]
+    BrokenCheckpointChain,
 }
@@ -0,0 +1,46 @@
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use crate::{NanoProof, NanoZKP, NanoZKPError};
+
+/// The magic bytes at the start of every serialized proof.
+pub const PROOF_MAGIC: [u8; 4] = *b"NZKP";
+
+/// The version of the nano sync circuits. It must be increased whenever the circuits change in a
+/// way that makes their proofs incompatible.
+pub const PROOF_VERSION: u8 = 1;
+
+impl NanoZKP {
+    /// Serializes a proof for the Merger Wrapper circuit. The proof is prefixed with a header
+    /// (`PROOF_MAGIC || PROOF_VERSION`), so that proofs of incompatible circuit versions are
+    /// rejected on deserialization instead of being read as garbage.
+    pub fn serialize_proof(proof: &NanoProof) -> Result<Vec<u8>, NanoZKPError> {
+        let mut bytes = Vec::with_capacity(PROOF_MAGIC.len() + 1 + proof.serialized_size());
+
+        bytes.extend_from_slice(&PROOF_MAGIC);
+        bytes.push(PROOF_VERSION);
+        proof.serialize(&mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    /// Deserializes a proof created by `serialize_proof`. Fails with `InvalidProofHeader` if the
+    /// bytes don't start with the magic bytes and with `IncompatibleProofVersion` if the proof was
+    /// created for another version of the circuits.
+    pub fn deserialize_proof(bytes: &[u8]) -> Result<NanoProof, NanoZKPError> {
+        if bytes.len() <= PROOF_MAGIC.len() || bytes[..PROOF_MAGIC.len()] != PROOF_MAGIC {
+            return Err(NanoZKPError::InvalidProofHeader);
+        }
+
+        let version = bytes[PROOF_MAGIC.len()];
+        if version != PROOF_VERSION {
+            return Err(NanoZKPError::IncompatibleProofVersion {
+                expected: PROOF_VERSION,
+                got: version,
+            });
+        }
+
+        let proof = NanoProof::deserialize(&mut &bytes[PROOF_MAGIC.len() + 1..])?;
+
+        Ok(proof)
+    }
+}
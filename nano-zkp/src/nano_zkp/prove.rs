@@ -27,7 +27,7 @@ use crate::circuits::mnt6::{
     MacroBlockWrapperCircuit, MergerWrapperCircuit, PKTreeNodeCircuit as NodeMNT6,
 };
 use crate::utils::pack_inputs;
-use crate::{NanoZKP, NanoZKPError};
+use crate::{NanoCheckpointProof, NanoZKP, NanoZKPError};
 
 impl NanoZKP {
     /// This function generates a proof for a new epoch, it uses the entire nano sync program. Note
@@ -280,6 +280,69 @@ impl NanoZKP {
         Ok(proof)
     }
 
+    /// This function generates checkpoint proofs for a chain of epochs. Instead of a single
+    /// recursive proof starting at the genesis block, the recursion restarts every `interval`
+    /// epochs, so that each proof only covers the epochs since the last checkpoint. Each of the
+    /// given blocks consists of the initial public keys, the initial header hash, the final public
+    /// keys and the election macro block of an epoch, like the inputs of `prove`.
+    /// Returns one proof per segment, the last segment may be shorter than `interval`. A light
+    /// client can verify them with `verify_checkpoints` starting from any checkpoint it trusts.
+    pub fn prove_checkpoint(
+        interval: usize,
+        blocks: Vec<(Vec<G2MNT6>, [u8; 32], Vec<G2MNT6>, MacroBlock)>,
+    ) -> Result<Vec<NanoCheckpointProof>, NanoZKPError> {
+        let segments = NanoCheckpointProof::segments(interval, blocks.len());
+        let mut blocks = blocks.into_iter().enumerate();
+        let mut proofs = vec![];
+
+        for segment in segments {
+            // The first epoch after a checkpoint is proven as if the checkpoint was the genesis
+            // block.
+            let mut previous: Option<(Proof<MNT6_753>, Vec<u8>)> = None;
+
+            for (i, (initial_pks, initial_header_hash, final_pks, block)) in
+                blocks.by_ref().take(segment.len())
+            {
+                let initial_commitment = match previous {
+                    Some((_, ref commitment)) => commitment.clone(),
+                    None => state_commitment(
+                        block.block_number - BLOCKS_PER_EPOCH,
+                        initial_header_hash,
+                        initial_pks.clone(),
+                    ),
+                };
+
+                let final_commitment =
+                    state_commitment(block.block_number, block.header_hash, final_pks.clone());
+
+                println!("Proving epoch {} for checkpoint proofs", i + 1);
+
+                let proof = NanoZKP::prove(
+                    initial_pks,
+                    initial_header_hash,
+                    final_pks,
+                    block,
+                    previous.take(),
+                    false,
+                    false,
+                )?;
+
+                // Close the segment at its last epoch.
+                if i + 1 == segment.end {
+                    proofs.push(NanoCheckpointProof {
+                        initial_commitment,
+                        final_commitment,
+                        proof,
+                    });
+                } else {
+                    previous = Some((proof, initial_commitment));
+                }
+            }
+        }
+
+        Ok(proofs)
+    }
+
     fn prove_pk_tree_leaf<R: CryptoRng + Rng>(
         rng: &mut R,
         name: &str,
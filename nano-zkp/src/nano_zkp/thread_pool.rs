@@ -0,0 +1,71 @@
+use ark_groth16::Proof;
+use ark_mnt6_753::{G2Projective as G2MNT6, MNT6_753};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use nimiq_nano_primitives::MacroBlock;
+
+use crate::{NanoZKP, NanoZKPError};
+
+/// A prover that runs the proof generation on its own rayon thread pool instead of the global one.
+///
+/// The proof generation is embarrassingly parallel, so fewer threads mean proportionally longer
+/// proving times. A validator can use this to cap the number of cores spent on proving, so that
+/// the remaining cores stay responsive for block production and RPC, at the cost of a higher
+/// proving latency.
+pub struct NanoZKPThreadPool {
+    pool: ThreadPool,
+}
+
+impl NanoZKP {
+    /// Creates a prover that uses a dedicated thread pool with the given number of threads.
+    pub fn with_thread_pool(num_threads: usize) -> Result<NanoZKPThreadPool, NanoZKPError> {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name(|i| format!("nano-zkp-prover-{}", i))
+            .build()?;
+
+        Ok(NanoZKPThreadPool { pool })
+    }
+}
+
+impl NanoZKPThreadPool {
+    /// Returns the number of threads in the pool.
+    pub fn num_threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Runs the given operation inside the thread pool. Any parallel work it does, including the
+    /// parallel arithmetic in the arkworks crates, is confined to the threads of this pool.
+    pub fn install<OP, R>(&self, op: OP) -> R
+    where
+        OP: FnOnce() -> R + Send,
+        R: Send,
+    {
+        self.pool.install(op)
+    }
+
+    /// Same as `NanoZKP::prove`, but runs on the threads of this pool.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove(
+        &self,
+        initial_pks: Vec<G2MNT6>,
+        initial_header_hash: [u8; 32],
+        final_pks: Vec<G2MNT6>,
+        block: MacroBlock,
+        genesis_data: Option<(Proof<MNT6_753>, Vec<u8>)>,
+        proof_caching: bool,
+        debug_mode: bool,
+    ) -> Result<Proof<MNT6_753>, NanoZKPError> {
+        self.install(|| {
+            NanoZKP::prove(
+                initial_pks,
+                initial_header_hash,
+                final_pks,
+                block,
+                genesis_data,
+                proof_caching,
+                debug_mode,
+            )
+        })
+    }
+}
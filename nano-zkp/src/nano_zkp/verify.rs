@@ -9,7 +9,7 @@ use nimiq_bls::utils::bytes_to_bits;
 use nimiq_nano_primitives::{state_commitment, vk_commitment};
 
 use crate::utils::pack_inputs;
-use crate::{NanoZKP, NanoZKPError};
+use crate::{NanoCheckpointProof, NanoProof, NanoZKP, NanoZKPError, VerificationFailure};
 
 impl NanoZKP {
     /// This function verifies a proof for the Merger Wrapper circuit, which implicitly is a proof for
@@ -64,4 +64,133 @@ impl NanoZKP {
         // Return result.
         Ok(result)
     }
+
+    /// This function verifies a proof for the Merger Wrapper circuit against the given state
+    /// commitments and verifying key commitment. Contrary to `verify`, it doesn't just return `false`
+    /// for an invalid proof, instead it returns a `NanoZKPError::InvalidProof` that indicates
+    /// whether the public inputs didn't match the verifying key or the pairing check failed.
+    pub fn verify_commitments(
+        // The SNARK proof for this circuit.
+        proof: NanoProof,
+        // The state commitment of the initial block. Most likely, it will be the genesis block.
+        initial_commitment: &[u8],
+        // The state commitment of the final block.
+        final_commitment: &[u8],
+        // The commitment to the verifying key of the Merger Wrapper circuit.
+        vk_commitment: &[u8],
+    ) -> Result<(), NanoZKPError> {
+        // Load the verifying key from file.
+        let mut file = File::open("verifying_keys/merger_wrapper.bin")?;
+
+        let vk = VerifyingKey::deserialize_unchecked(&mut file)?;
+
+        Self::verify_commitments_with_vk(
+            &vk,
+            proof,
+            initial_commitment,
+            final_commitment,
+            vk_commitment,
+        )
+    }
+
+    /// Same as `verify_commitments`, but uses the given verifying key instead of loading it from
+    /// file.
+    pub fn verify_commitments_with_vk(
+        vk: &VerifyingKey<MNT6_753>,
+        proof: NanoProof,
+        initial_commitment: &[u8],
+        final_commitment: &[u8],
+        vk_commitment: &[u8],
+    ) -> Result<(), NanoZKPError> {
+        // The verifying key commitment must be the commitment of the verifying key that we use.
+        if vk_commitment != self::vk_commitment(vk.clone()).as_slice() {
+            return Err(NanoZKPError::InvalidProof(
+                VerificationFailure::PublicInputMismatch,
+            ));
+        }
+
+        // Prepare the inputs.
+        let mut inputs = vec![];
+
+        inputs.append(&mut pack_inputs(bytes_to_bits(initial_commitment)));
+
+        inputs.append(&mut pack_inputs(bytes_to_bits(final_commitment)));
+
+        inputs.append(&mut pack_inputs(bytes_to_bits(vk_commitment)));
+
+        // The verifying key expects exactly one more element than the number of inputs.
+        if inputs.len() + 1 != vk.gamma_abc_g1.len() {
+            return Err(NanoZKPError::InvalidProof(
+                VerificationFailure::PublicInputMismatch,
+            ));
+        }
+
+        // Verify proof.
+        if !Groth16::<MNT6_753>::verify(vk, &inputs, &proof)? {
+            return Err(NanoZKPError::InvalidProof(
+                VerificationFailure::PairingCheck,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// This function verifies a chain of checkpoint proofs, as created by `prove_checkpoint`. The
+    /// verification doesn't need to start at the genesis block, instead it starts at the proof
+    /// for the segment that begins at the given trusted state commitment, which can be the genesis
+    /// state or any checkpoint. All following proofs must continue where the previous one ended.
+    /// Returns the state commitment at the end of the chain.
+    pub fn verify_checkpoints(
+        // The state commitment of the trusted checkpoint or genesis block.
+        trusted_commitment: &[u8],
+        // The checkpoint proofs, in chain order.
+        proofs: &[NanoCheckpointProof],
+    ) -> Result<Vec<u8>, NanoZKPError> {
+        // Load the verifying key from file.
+        let mut file = File::open("verifying_keys/merger_wrapper.bin")?;
+
+        let vk = VerifyingKey::deserialize_unchecked(&mut file)?;
+
+        Self::verify_checkpoints_with_vk(&vk, trusted_commitment, proofs)
+    }
+
+    /// Same as `verify_checkpoints`, but uses the given verifying key instead of loading it from
+    /// file.
+    pub fn verify_checkpoints_with_vk(
+        vk: &VerifyingKey<MNT6_753>,
+        trusted_commitment: &[u8],
+        proofs: &[NanoCheckpointProof],
+    ) -> Result<Vec<u8>, NanoZKPError> {
+        // Find the segment that starts at the trusted state.
+        let start = proofs
+            .iter()
+            .position(|proof| proof.initial_commitment == trusted_commitment)
+            .ok_or(NanoZKPError::InvalidProof(
+                VerificationFailure::BrokenCheckpointChain,
+            ))?;
+
+        let vk_commitment = vk_commitment(vk.clone());
+
+        let mut current_commitment = trusted_commitment;
+
+        for proof in &proofs[start..] {
+            if proof.initial_commitment != current_commitment {
+                return Err(NanoZKPError::InvalidProof(
+                    VerificationFailure::BrokenCheckpointChain,
+                ));
+            }
+
+            Self::verify_commitments_with_vk(
+                vk,
+                proof.proof.clone(),
+                &proof.initial_commitment,
+                &proof.final_commitment,
+                &vk_commitment,
+            )?;
+
+            current_commitment = &proof.final_commitment;
+        }
+
+        Ok(current_commitment.to_vec())
+    }
 }
@@ -0,0 +1,17 @@
+use nimiq_nano_zkp::NanoCheckpointProof;
+use nimiq_test_log::test;
+
+#[test]
+fn epochs_are_split_at_the_checkpoints() {
+    assert_eq!(NanoCheckpointProof::segments(3, 7), vec![0..3, 3..6, 6..7]);
+    assert_eq!(NanoCheckpointProof::segments(3, 6), vec![0..3, 3..6]);
+    assert_eq!(NanoCheckpointProof::segments(1, 2), vec![0..1, 1..2]);
+    assert_eq!(NanoCheckpointProof::segments(5, 2), vec![0..2]);
+    assert!(NanoCheckpointProof::segments(5, 0).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "The checkpoint interval must be positive")]
+fn the_checkpoint_interval_must_be_positive() {
+    NanoCheckpointProof::segments(0, 2);
+}
@@ -1,2 +1,4 @@
+mod checkpoint;
+mod proof;
 #[cfg(feature = "prover")]
 mod prover;
@@ -0,0 +1,43 @@
+use ark_ec::AffineCurve;
+use ark_groth16::Proof;
+use ark_mnt6_753::{G1Affine, G2Affine};
+
+use nimiq_nano_zkp::{NanoProof, NanoZKP, NanoZKPError, PROOF_MAGIC, PROOF_VERSION};
+use nimiq_test_log::test;
+
+fn proof() -> NanoProof {
+    Proof {
+        a: G1Affine::prime_subgroup_generator(),
+        b: G2Affine::prime_subgroup_generator(),
+        c: G1Affine::prime_subgroup_generator(),
+    }
+}
+
+#[test]
+fn proofs_can_be_serialized() {
+    let proof = proof();
+
+    let bytes = NanoZKP::serialize_proof(&proof).unwrap();
+    assert_eq!(bytes[..PROOF_MAGIC.len()], PROOF_MAGIC);
+    assert_eq!(bytes[PROOF_MAGIC.len()], PROOF_VERSION);
+
+    assert_eq!(NanoZKP::deserialize_proof(&bytes).unwrap(), proof);
+}
+
+#[test]
+fn proofs_of_other_versions_are_rejected() {
+    let mut bytes = NanoZKP::serialize_proof(&proof()).unwrap();
+
+    bytes[PROOF_MAGIC.len()] = PROOF_VERSION + 1;
+    assert!(matches!(
+        NanoZKP::deserialize_proof(&bytes),
+        Err(NanoZKPError::IncompatibleProofVersion { expected, got })
+            if expected == PROOF_VERSION && got == PROOF_VERSION + 1
+    ));
+
+    // Proofs without a header are rejected as well.
+    assert!(matches!(
+        NanoZKP::deserialize_proof(&bytes[PROOF_MAGIC.len() + 1..]),
+        Err(NanoZKPError::InvalidProofHeader)
+    ));
+}
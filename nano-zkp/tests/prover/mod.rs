@@ -1 +1,3 @@
 mod recursive_input;
+mod thread_pool;
+mod verify;
@@ -0,0 +1,52 @@
+use ark_crypto_primitives::{CircuitSpecificSetupSNARK, SNARK};
+use ark_groth16::Groth16;
+use ark_mnt6_753::{Fr as MNT6Fr, MNT6_753};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::{AllocVar, EqGadget};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::{test_rng, UniformRand};
+
+use nimiq_nano_zkp::NanoZKP;
+use nimiq_test_log::test;
+
+/// A circuit that proves knowledge of a square root of the public input.
+#[derive(Clone)]
+struct SquareCircuit {
+    // Witnesses (private)
+    root: MNT6Fr,
+    // Inputs (public)
+    square: MNT6Fr,
+}
+
+impl ConstraintSynthesizer<MNT6Fr> for SquareCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<MNT6Fr>) -> Result<(), SynthesisError> {
+        let root_var = FpVar::new_witness(cs.clone(), || Ok(self.root))?;
+        let square_var = FpVar::new_input(cs, || Ok(self.square))?;
+
+        (&root_var * &root_var).enforce_equal(&square_var)
+    }
+}
+
+#[test]
+fn proving_completes_on_a_bounded_thread_pool() {
+    let rng = &mut test_rng();
+
+    let root = MNT6Fr::rand(rng);
+    let circuit = SquareCircuit {
+        root,
+        square: root * root,
+    };
+
+    let (pk, vk) = Groth16::<MNT6_753>::setup(circuit.clone(), rng).unwrap();
+
+    for num_threads in [1, 4] {
+        let pool = NanoZKP::with_thread_pool(num_threads).unwrap();
+        assert_eq!(pool.num_threads(), num_threads);
+
+        let proof = pool
+            .install(|| Groth16::<MNT6_753>::prove(&pk, circuit.clone(), &mut test_rng()))
+            .unwrap();
+
+        assert!(Groth16::<MNT6_753>::verify(&vk, &[circuit.square], &proof).unwrap());
+    }
+}
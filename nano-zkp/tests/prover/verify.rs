@@ -0,0 +1,205 @@
+use ark_crypto_primitives::{CircuitSpecificSetupSNARK, SNARK};
+use ark_groth16::Groth16;
+use ark_mnt4_753::constraints::FqVar as FqVarMNT4;
+use ark_mnt4_753::Fq as FqMNT4;
+use ark_mnt6_753::{Fr as MNT6Fr, MNT6_753};
+use ark_r1cs_std::prelude::{AllocVar, Boolean, EqGadget};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::test_rng;
+use rand::RngCore;
+
+use nimiq_bls::utils::bytes_to_bits;
+use nimiq_nano_primitives::vk_commitment;
+use nimiq_nano_zkp::utils::{pack_inputs, unpack_inputs};
+use nimiq_nano_zkp::{NanoCheckpointProof, NanoZKP, NanoZKPError, VerificationFailure};
+use nimiq_test_log::test;
+
+// The size of a serialized MNT6 G1 point, which is what all the commitments are.
+const COMMITMENT_SIZE: usize = 95;
+
+/// A circuit that has the same public inputs as the Merger Wrapper circuit, but simply checks that
+/// they are equal to the witnesses.
+#[derive(Clone)]
+struct CommitmentsCircuit {
+    // Witnesses (private)
+    commitments_priv: Vec<Vec<bool>>,
+    // Inputs (public)
+    commitments_pub: Vec<Vec<FqMNT4>>,
+}
+
+impl CommitmentsCircuit {
+    fn new(commitments: &[&[u8]]) -> Self {
+        let commitments_priv: Vec<Vec<bool>> =
+            commitments.iter().map(|c| bytes_to_bits(c)).collect();
+
+        let commitments_pub = commitments_priv
+            .iter()
+            .map(|bits| pack_inputs(bits.clone()))
+            .collect();
+
+        CommitmentsCircuit {
+            commitments_priv,
+            commitments_pub,
+        }
+    }
+}
+
+impl ConstraintSynthesizer<MNT6Fr> for CommitmentsCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<MNT6Fr>) -> Result<(), SynthesisError> {
+        for (bits, inputs) in self
+            .commitments_priv
+            .iter()
+            .zip(self.commitments_pub.iter())
+        {
+            // Allocate the witnesses.
+            let priv_var = Vec::<Boolean<MNT6Fr>>::new_witness(cs.clone(), || Ok(&bits[..]))?;
+
+            // Allocate the inputs.
+            let pub_var = Vec::<FqVarMNT4>::new_input(cs.clone(), || Ok(&inputs[..]))?;
+
+            // Unpack the inputs and compare them to the witnesses.
+            let pub_bits = unpack_inputs(pub_var)?[..bits.len()].to_vec();
+
+            priv_var.enforce_equal(&pub_bits)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn verify_commitments_reports_failure_reasons() {
+    let rng = &mut test_rng();
+
+    // Create random state commitments.
+    let mut initial_commitment = [0u8; COMMITMENT_SIZE];
+    rng.fill_bytes(&mut initial_commitment);
+
+    let mut final_commitment = [0u8; COMMITMENT_SIZE];
+    rng.fill_bytes(&mut final_commitment);
+
+    // The keys don't depend on the values of the inputs, so we can use a placeholder for the
+    // verifying key commitment during the setup.
+    let placeholder = [0u8; COMMITMENT_SIZE];
+    let circuit = CommitmentsCircuit::new(&[&initial_commitment, &final_commitment, &placeholder]);
+
+    let (pk, vk) = Groth16::<MNT6_753>::setup(circuit, rng).unwrap();
+
+    let vk_commitment = vk_commitment(vk.clone());
+    assert_eq!(vk_commitment.len(), COMMITMENT_SIZE);
+
+    // Create the proof.
+    let circuit =
+        CommitmentsCircuit::new(&[&initial_commitment, &final_commitment, &vk_commitment]);
+
+    let proof = Groth16::<MNT6_753>::prove(&pk, circuit, rng).unwrap();
+
+    // A valid proof.
+    assert!(NanoZKP::verify_commitments_with_vk(
+        &vk,
+        proof.clone(),
+        &initial_commitment,
+        &final_commitment,
+        &vk_commitment,
+    )
+    .is_ok());
+
+    // Swapped initial and final commitments.
+    assert!(matches!(
+        NanoZKP::verify_commitments_with_vk(
+            &vk,
+            proof.clone(),
+            &final_commitment,
+            &initial_commitment,
+            &vk_commitment,
+        ),
+        Err(NanoZKPError::InvalidProof(
+            VerificationFailure::PairingCheck
+        ))
+    ));
+
+    // Wrong verifying key commitment.
+    assert!(matches!(
+        NanoZKP::verify_commitments_with_vk(
+            &vk,
+            proof,
+            &initial_commitment,
+            &final_commitment,
+            &placeholder,
+        ),
+        Err(NanoZKPError::InvalidProof(
+            VerificationFailure::PublicInputMismatch
+        ))
+    ));
+}
+
+#[test]
+fn verify_checkpoints_starts_at_a_trusted_checkpoint() {
+    let rng = &mut test_rng();
+
+    // Create random state commitments for the genesis block and three checkpoints.
+    let commitments: Vec<Vec<u8>> = (0..4)
+        .map(|_| {
+            let mut commitment = vec![0u8; COMMITMENT_SIZE];
+            rng.fill_bytes(&mut commitment);
+            commitment
+        })
+        .collect();
+
+    let placeholder = [0u8; COMMITMENT_SIZE];
+    let circuit = CommitmentsCircuit::new(&[&commitments[0], &commitments[1], &placeholder]);
+
+    let (pk, vk) = Groth16::<MNT6_753>::setup(circuit, rng).unwrap();
+
+    let vk_commitment = vk_commitment(vk.clone());
+
+    // Create one proof per segment between two checkpoints.
+    let proofs: Vec<NanoCheckpointProof> = commitments
+        .windows(2)
+        .map(|segment| {
+            let circuit = CommitmentsCircuit::new(&[&segment[0], &segment[1], &vk_commitment]);
+
+            NanoCheckpointProof {
+                initial_commitment: segment[0].clone(),
+                final_commitment: segment[1].clone(),
+                proof: Groth16::<MNT6_753>::prove(&pk, circuit, rng).unwrap(),
+            }
+        })
+        .collect();
+
+    // The chain can be verified from genesis and from any checkpoint.
+    for trusted_commitment in &commitments[..3] {
+        assert_eq!(
+            NanoZKP::verify_checkpoints_with_vk(&vk, trusted_commitment, &proofs).unwrap(),
+            commitments[3]
+        );
+    }
+
+    // An untrusted starting point.
+    assert!(matches!(
+        NanoZKP::verify_checkpoints_with_vk(&vk, &commitments[3], &proofs),
+        Err(NanoZKPError::InvalidProof(
+            VerificationFailure::BrokenCheckpointChain
+        ))
+    ));
+
+    // A missing segment.
+    let gapped_proofs = vec![proofs[0].clone(), proofs[2].clone()];
+    assert!(matches!(
+        NanoZKP::verify_checkpoints_with_vk(&vk, &commitments[0], &gapped_proofs),
+        Err(NanoZKPError::InvalidProof(
+            VerificationFailure::BrokenCheckpointChain
+        ))
+    ));
+
+    // A segment with a forged final state.
+    let mut forged_proofs = proofs.clone();
+    forged_proofs[1].final_commitment = commitments[0].clone();
+    forged_proofs[2].initial_commitment = commitments[0].clone();
+    assert!(matches!(
+        NanoZKP::verify_checkpoints_with_vk(&vk, &commitments[1], &forged_proofs),
+        Err(NanoZKPError::InvalidProof(
+            VerificationFailure::PairingCheck
+        ))
+    ));
+}
@@ -50,9 +50,9 @@ impl Account {
             Account::HTLC(ref account) => account.balance,
             Account::Staking(ref account) => account.balance,
             Account::StakingValidator(ref account) => account.balance,
-            Account::StakingValidatorsStaker(_) => {
-                unimplemented!()
-            }
+            // This is only a pointer from a validator to one of its stakers, the stake itself is
+            // part of the balance of the staker.
+            Account::StakingValidatorsStaker(_) => Coin::ZERO,
             Account::StakingStaker(ref account) => account.balance,
         }
     }
@@ -63,6 +63,14 @@ impl Account {
             .ok_or(AccountError::InvalidCoinValue)
     }
 
+    /// Multiplies a coin value by the given factor (e.g. a fee per byte by a size), failing with
+    /// `InvalidCoinValue` if the result exceeds `Coin::MAX`.
+    pub fn balance_mul(value: Coin, factor: u64) -> Result<Coin, AccountError> {
+        value
+            .checked_mul(factor)
+            .ok_or(AccountError::InvalidCoinValue)
+    }
+
     pub fn balance_sub(balance: Coin, value: Coin) -> Result<Coin, AccountError> {
         match balance.checked_sub(value) {
             Some(result) => Ok(result),
@@ -97,6 +105,7 @@ impl AccountTransactionInteraction for Account {
                 block_height,
                 block_time,
             ),
+            AccountType::Staking => Err(AccountError::StakingContractNotCreatable),
             _ => Err(AccountError::InvalidForRecipient),
         }
     }
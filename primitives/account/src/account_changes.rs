@@ -0,0 +1,74 @@
+use std::fmt::Debug;
+
+use parking_lot::Mutex;
+
+use nimiq_primitives::coin::Coin;
+use nimiq_trie::key_nibbles::KeyNibbles;
+
+use crate::Account;
+
+/// A change of a single account in the Accounts Trie, together with its balance before and/or
+/// after the change.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccountChange {
+    Created { balance: Coin },
+    Updated { before: Coin, after: Coin },
+    Removed { balance: Coin },
+}
+
+impl AccountChange {
+    /// Returns the change between the two given states of an account, or `None` if the account
+    /// didn't change.
+    pub fn between(before: Option<&Account>, after: Option<&Account>) -> Option<Self> {
+        match (before, after) {
+            (None, None) => None,
+            (None, Some(after)) => Some(AccountChange::Created {
+                balance: after.balance(),
+            }),
+            (Some(before), None) => Some(AccountChange::Removed {
+                balance: before.balance(),
+            }),
+            (Some(before), Some(after)) if before == after => None,
+            (Some(before), Some(after)) => Some(AccountChange::Updated {
+                before: before.balance(),
+                after: after.balance(),
+            }),
+        }
+    }
+}
+
+/// An observer for the accounts that are changed by committing or reverting a batch. It can be
+/// attached to the `Accounts` struct so that indexers don't have to diff the Accounts Trie.
+///
+/// The accounts that are observed are the senders and recipients of the transactions and the
+/// targets of the inherents. If the staking contract is touched, changes to all of its entries are
+/// reported, i.e. to the contract itself, to the validators and to the stakers.
+pub trait AccountsObserver: Debug + Send + Sync {
+    /// Records that the account at the given key changed at the given block height.
+    fn notify(&self, block_height: u32, key: KeyNibbles, change: AccountChange);
+}
+
+/// A simple `AccountsObserver` that keeps all changes in memory.
+#[derive(Debug, Default)]
+pub struct AccountChangeLog {
+    changes: Mutex<Vec<(u32, KeyNibbles, AccountChange)>>,
+}
+
+impl AccountChangeLog {
+    /// Returns all recorded changes together with their block heights, in the order they were
+    /// recorded.
+    pub fn changes(&self) -> Vec<(u32, KeyNibbles, AccountChange)> {
+        self.changes.lock().clone()
+    }
+
+    /// Removes and returns all recorded changes.
+    pub fn take(&self) -> Vec<(u32, KeyNibbles, AccountChange)> {
+        std::mem::take(&mut *self.changes.lock())
+    }
+}
+
+impl AccountsObserver for AccountChangeLog {
+    fn notify(&self, block_height: u32, key: KeyNibbles, change: AccountChange) {
+        self.changes.lock().push((block_height, key, change));
+    }
+}
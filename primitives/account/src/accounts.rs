@@ -1,15 +1,36 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+#[cfg(any(feature = "metrics", feature = "staking-events"))]
+use std::sync::Arc;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteBytesExt};
 use nimiq_database::{
     Environment, ReadTransaction, Transaction as DBTransaction, WriteTransaction,
 };
-use nimiq_hash::Blake2bHash;
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::Address;
+use nimiq_primitives::account::AccountType;
+use nimiq_primitives::coin::Coin;
+#[cfg(any(feature = "staking-events", feature = "account-changes"))]
+use nimiq_primitives::policy::STAKING_CONTRACT_ADDRESS;
 use nimiq_transaction::{Transaction, TransactionFlags};
+use nimiq_trie::error::MerkleRadixTrieError;
 use nimiq_trie::key_nibbles::KeyNibbles;
 use nimiq_trie::trie::MerkleRadixTrie;
+use rayon::prelude::*;
 
+#[cfg(feature = "metrics")]
+use crate::CommitHistogram;
 use crate::{
-    Account, AccountError, AccountInherentInteraction, AccountTransactionInteraction, Inherent,
-    Receipt, Receipts,
+    Account, AccountError, AccountInherentInteraction, AccountTransactionInteraction,
+    AccountsEvents, BasicAccount, Inherent, MultiProof, Receipt, ReceiptType, Receipts,
 };
+#[cfg(feature = "account-changes")]
+use crate::{AccountChange, AccountsObserver};
+#[cfg(feature = "staking-events")]
+use crate::{StakingContract, StakingEventSink};
 
 /// An alias for the accounts tree.
 pub type AccountsTrie = MerkleRadixTrie<Account>;
@@ -22,13 +43,79 @@ pub type AccountsTrie = MerkleRadixTrie<Account>;
 pub struct Accounts {
     pub env: Environment,
     pub tree: AccountsTrie,
+    #[cfg(feature = "metrics")]
+    commit_histogram: Option<Arc<dyn CommitHistogram>>,
+    #[cfg(feature = "staking-events")]
+    staking_events: Option<Arc<dyn StakingEventSink>>,
+    #[cfg(feature = "account-changes")]
+    observer: Option<Arc<dyn AccountsObserver>>,
 }
 
 impl Accounts {
     /// Creates a new, completely empty Accounts.
     pub fn new(env: Environment) -> Self {
-        let tree = AccountsTrie::new(env.clone(), "AccountsTrie");
-        Accounts { env, tree }
+        Self::new_with_prefix(env, "")
+    }
+
+    /// Creates a new, completely empty Accounts whose database names are prefixed with `prefix`.
+    /// This allows the accounts of several independent chains to coexist in one environment.
+    /// An empty prefix gives the same databases as `new`.
+    pub fn new_with_prefix(env: Environment, prefix: &str) -> Self {
+        let tree = AccountsTrie::new(env.clone(), &format!("{}AccountsTrie", prefix));
+        Accounts {
+            env,
+            tree,
+            #[cfg(feature = "metrics")]
+            commit_histogram: None,
+            #[cfg(feature = "staking-events")]
+            staking_events: None,
+            #[cfg(feature = "account-changes")]
+            observer: None,
+        }
+    }
+
+    /// Attaches a histogram that records the time spent committing transactions per account type.
+    #[cfg(feature = "metrics")]
+    pub fn with_commit_histogram(mut self, histogram: Arc<dyn CommitHistogram>) -> Self {
+        self.commit_histogram = Some(histogram);
+        self
+    }
+
+    /// Attaches a sink that receives an event for every state transition of the staking contract
+    /// that is committed or reverted. The events are delivered by `notify`.
+    #[cfg(feature = "staking-events")]
+    pub fn with_staking_event_sink(mut self, sink: Arc<dyn StakingEventSink>) -> Self {
+        self.staking_events = Some(sink);
+        self
+    }
+
+    /// Attaches an observer that is notified of every account that is created, updated or removed
+    /// by committing or reverting a batch. The changes are delivered by `notify`.
+    #[cfg(feature = "account-changes")]
+    pub fn with_observer(mut self, observer: Arc<dyn AccountsObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Delivers the events returned by committing or reverting a batch to the attached sinks. This
+    /// must only be called once the database transaction that produced them has been committed.
+    pub fn notify(&self, events: AccountsEvents) {
+        #[cfg(feature = "staking-events")]
+        if let Some(sink) = &self.staking_events {
+            for (block_height, event) in events.staking_events {
+                sink.emit(block_height, event);
+            }
+        }
+
+        #[cfg(feature = "account-changes")]
+        if let Some(observer) = &self.observer {
+            for (block_height, key, change) in events.account_changes {
+                observer.notify(block_height, key, change);
+            }
+        }
+
+        #[cfg(not(any(feature = "staking-events", feature = "account-changes")))]
+        let _ = events;
     }
 
     /// Initializes the Accounts struct with a given list of accounts.
@@ -53,6 +140,93 @@ impl Accounts {
         }
     }
 
+    /// Checks the integrity of the Accounts Trie, verifying that every stored hash matches the
+    /// node it references and that no node is missing. It will traverse the entire tree, so it is
+    /// only intended for debugging and maintenance.
+    pub fn check_integrity(
+        &self,
+        txn_option: Option<&DBTransaction>,
+    ) -> Result<(), MerkleRadixTrieError> {
+        match txn_option {
+            Some(txn) => self.tree.check_integrity(txn),
+            None => self.tree.check_integrity(&ReadTransaction::new(&self.env)),
+        }
+    }
+
+    /// Writes a snapshot of the Accounts Trie to the given writer. The snapshot contains the root
+    /// hash followed by all accounts with their keys, so that the state can be imported elsewhere
+    /// without replaying the blocks.
+    pub fn export_snapshot<W: WriteBytesExt>(
+        &self,
+        txn: &DBTransaction,
+        writer: &mut W,
+    ) -> Result<usize, AccountError> {
+        let mut size = 0;
+
+        size += Serialize::serialize(&self.tree.root_hash(txn), writer)?;
+
+        let accounts = self.tree.get_all(txn);
+        let count = u32::try_from(accounts.len()).map_err(|_| SerializingError::Overflow)?;
+        size += Serialize::serialize(&count, writer)?;
+
+        for (key, account) in &accounts {
+            size += Serialize::serialize(key, writer)?;
+            size += Serialize::serialize(account, writer)?;
+        }
+
+        Ok(size)
+    }
+
+    /// Rebuilds the Accounts Trie from a snapshot created by `export_snapshot`. Fails if the given
+    /// environment already contains accounts, or if the root hash of the rebuilt trie doesn't
+    /// match the one stored in the snapshot. In both cases nothing is written.
+    pub fn import_snapshot<R: ReadBytesExt>(
+        env: Environment,
+        reader: &mut R,
+    ) -> Result<Self, AccountError> {
+        let imported = Accounts::new(env.clone());
+        if imported.size(None) > 0 {
+            return Err(AccountError::SnapshotTargetNotEmpty);
+        }
+
+        let expected: Blake2bHash = Deserialize::deserialize(reader)?;
+
+        let count: u32 = Deserialize::deserialize(reader)?;
+        let mut accounts = Vec::new();
+        for _ in 0..count {
+            let key: KeyNibbles = Deserialize::deserialize(reader)?;
+            let account: Account = Deserialize::deserialize(reader)?;
+            accounts.push((key, account));
+        }
+
+        let mut txn = WriteTransaction::new(&env);
+
+        imported.init(&mut txn, accounts);
+
+        let got = imported.get_root(Some(&txn));
+        if got != expected {
+            txn.abort();
+            return Err(AccountError::InvalidSnapshot { expected, got });
+        }
+
+        txn.commit();
+
+        Ok(imported)
+    }
+
+    /// Returns a read-only handle to the Accounts Trie. The handle holds its own read transaction,
+    /// so it keeps seeing the state at the time it was created, even while new blocks are being
+    /// committed.
+    pub fn read_only_handle(&self) -> AccountsReader<'_> {
+        AccountsReader {
+            tree: &self.tree,
+            txn: ReadTransaction::new(&self.env),
+        }
+    }
+
+    /// Returns the account at the given key, if there is one. Nodes are stored under their keys, so
+    /// this is a single database read that doesn't traverse the trie, also for missing accounts.
+    /// It is therefore not cached, which keeps it correct for transactions that see older states.
     pub fn get(&self, key: &KeyNibbles, txn_option: Option<&DBTransaction>) -> Option<Account> {
         match txn_option {
             Some(txn) => self.tree.get(txn, key),
@@ -60,6 +234,61 @@ impl Accounts {
         }
     }
 
+    /// Returns the account at the given key or, if there is none, an empty basic account. This
+    /// matches how a transfer treats a recipient that doesn't exist yet.
+    pub fn get_or_default(&self, key: &KeyNibbles, txn_option: Option<&DBTransaction>) -> Account {
+        self.get(key, txn_option)
+            .unwrap_or(Account::Basic(BasicAccount {
+                balance: Coin::ZERO,
+            }))
+    }
+
+    /// Returns the accounts at the given keys, in the same order. Keys without an account are
+    /// returned as `None`. All lookups are done within the same database transaction.
+    pub fn get_multi(
+        &self,
+        keys: &[KeyNibbles],
+        txn_option: Option<&DBTransaction>,
+    ) -> Vec<Option<Account>> {
+        match txn_option {
+            Some(txn) => keys.iter().map(|key| self.tree.get(txn, key)).collect(),
+            None => self.get_multi(keys, Some(&ReadTransaction::new(&self.env))),
+        }
+    }
+
+    /// Returns the balances of the accounts at the given addresses, in the same order. Addresses
+    /// without an account have a balance of zero.
+    pub fn balances_of(
+        &self,
+        addresses: &[Address],
+        txn_option: Option<&DBTransaction>,
+    ) -> Vec<Coin> {
+        let keys: Vec<KeyNibbles> = addresses.iter().map(KeyNibbles::from).collect();
+
+        self.get_multi(&keys, txn_option)
+            .into_iter()
+            .map(|account| account.map_or(Coin::ZERO, |account| account.balance()))
+            .collect()
+    }
+
+    /// Returns the accounts at the given keys together with a single Merkle proof for all of them.
+    /// Keys without an account are returned as `None`, and the proof shows their absence.
+    pub fn get_many_with_proof(
+        &self,
+        keys: &[KeyNibbles],
+        txn_option: Option<&DBTransaction>,
+    ) -> (Vec<Option<Account>>, MultiProof) {
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => return self.get_many_with_proof(keys, Some(&ReadTransaction::new(&self.env))),
+        };
+
+        let accounts = keys.iter().map(|key| self.tree.get(txn, key)).collect();
+        let proof = self.tree.get_multi_proof(txn, keys);
+
+        (accounts, MultiProof { proof })
+    }
+
     pub fn get_root(&self, txn_option: Option<&DBTransaction>) -> Blake2bHash {
         match txn_option {
             Some(txn) => self.tree.root_hash(txn),
@@ -67,6 +296,29 @@ impl Accounts {
         }
     }
 
+    /// Returns the accounts that were added, removed or changed between two states of the Accounts
+    /// Trie, as `(key, account in state a, account in state b)` ordered by key.
+    ///
+    /// The trie only stores the latest state, older states are only retained by transactions that
+    /// were opened before they were overwritten (e.g. the one of a `read_only_handle`). Therefore
+    /// each root is given together with a transaction that sees it, and this fails with
+    /// `RootNotAvailable` if the transaction sees a different root.
+    pub fn diff(
+        &self,
+        txn_a: &DBTransaction,
+        root_a: &Blake2bHash,
+        txn_b: &DBTransaction,
+        root_b: &Blake2bHash,
+    ) -> Result<Vec<(KeyNibbles, Option<Account>, Option<Account>)>, AccountError> {
+        for (txn, root) in [(txn_a, root_a), (txn_b, root_b)] {
+            if self.tree.root_hash(txn) != *root {
+                return Err(AccountError::RootNotAvailable { root: root.clone() });
+            }
+        }
+
+        Ok(self.tree.diff(txn_a, txn_b))
+    }
+
     pub fn get_root_with(
         &self,
         transactions: &[Transaction],
@@ -76,9 +328,8 @@ impl Accounts {
     ) -> Result<Blake2bHash, AccountError> {
         let mut txn = WriteTransaction::new(&self.env);
 
-        self.commit(&mut txn, transactions, inherents, block_height, timestamp)?;
-
-        let hash = self.get_root(Some(&txn));
+        let (hash, _) =
+            self.commit_with_root(&mut txn, transactions, inherents, block_height, timestamp)?;
 
         txn.abort();
 
@@ -93,11 +344,183 @@ impl Accounts {
         block_height: u32,
         timestamp: u64,
     ) -> Result<Receipts, AccountError> {
-        let result = self.commit_batch(txn, transactions, inherents, block_height, timestamp);
+        self.commit_with_events(txn, transactions, inherents, block_height, timestamp)
+            .map(|(receipts, _)| receipts)
+    }
+
+    /// Commits the given transactions and inherents like `commit`, but also returns the events that
+    /// were produced for the attached sinks and observers. The events are not delivered, see
+    /// `notify`.
+    pub fn commit_with_events(
+        &self,
+        txn: &mut WriteTransaction,
+        transactions: &[Transaction],
+        inherents: &[Inherent],
+        block_height: u32,
+        timestamp: u64,
+    ) -> Result<(Receipts, AccountsEvents), AccountError> {
+        Self::check_expired(transactions, block_height)?;
+        Self::check_duplicates(transactions)?;
+
+        let result =
+            self.commit_batch_with_events(txn, transactions, inherents, block_height, timestamp);
         self.tree.update_root(txn);
         result
     }
 
+    /// Commits the given transactions and inherents like `commit`, but also returns the resulting
+    /// root hash of the Accounts Trie. The root is computed while the trie hashes are updated, so
+    /// this saves the extra traversal of a subsequent `get_root`.
+    pub fn commit_with_root(
+        &self,
+        txn: &mut WriteTransaction,
+        transactions: &[Transaction],
+        inherents: &[Inherent],
+        block_height: u32,
+        timestamp: u64,
+    ) -> Result<(Blake2bHash, Receipts), AccountError> {
+        Self::check_expired(transactions, block_height)?;
+        Self::check_duplicates(transactions)?;
+
+        let result = self.commit_batch(txn, transactions, inherents, block_height, timestamp);
+        let root = self.tree.update_root(txn);
+        result.map(|receipts| (root, receipts))
+    }
+
+    /// Commits the given transactions and inherents like `commit`, but applies transactions that
+    /// only move funds between basic accounts in parallel. Transactions are partitioned into sets
+    /// that don't share any sender or recipient; each set of basic transfers is applied on its own
+    /// thread and merged into the tree afterwards, while sets involving contracts fall back to the
+    /// sequential path. The resulting state and receipts are identical to `commit`.
+    pub fn commit_parallel(
+        &self,
+        txn: &mut WriteTransaction,
+        transactions: &[Transaction],
+        inherents: &[Inherent],
+        block_height: u32,
+        timestamp: u64,
+    ) -> Result<Receipts, AccountError> {
+        Self::check_expired(transactions, block_height)?;
+        Self::check_duplicates(transactions)?;
+
+        let (pre_inherents, post_inherents): (Vec<Inherent>, Vec<Inherent>) = inherents
+            .iter()
+            .cloned()
+            .partition(|i| i.is_pre_transactions());
+
+        // Like `commit`, this doesn't return any events.
+        let mut events = AccountsEvents::default();
+
+        let mut receipts =
+            self.commit_inherents(txn, &pre_inherents, block_height, timestamp, &mut events)?;
+
+        let (parallel, sequential): (Vec<Vec<usize>>, Vec<Vec<usize>>) =
+            Self::partition_transactions(transactions)
+                .into_iter()
+                .partition(|partition| {
+                    partition
+                        .iter()
+                        .all(|&i| Self::is_basic_transfer(&transactions[i]))
+                });
+
+        let mut sender_data = vec![None; transactions.len()];
+        let mut recipient_data = vec![None; transactions.len()];
+
+        // Partitions involving contracts are applied directly on the tree, in the same order as
+        // `commit_batch` would apply them.
+        for partition in sequential {
+            for &i in &partition {
+                sender_data[i] = self.commit_sender(
+                    txn,
+                    &transactions[i],
+                    block_height,
+                    timestamp,
+                    &mut events,
+                )?;
+            }
+            for j in Self::recipient_order(partition.iter().map(|&i| &transactions[i])) {
+                let i = partition[j];
+                if transactions[i]
+                    .flags
+                    .contains(TransactionFlags::CONTRACT_CREATION)
+                {
+                    self.create_contract(txn, &transactions[i], block_height, timestamp)?;
+                } else {
+                    recipient_data[i] = self.commit_recipient(
+                        txn,
+                        &transactions[i],
+                        block_height,
+                        timestamp,
+                        &mut events,
+                    )?;
+                }
+            }
+        }
+
+        // Basic transfers are staged in memory on the thread pool and merged afterwards. Since the
+        // partitions don't overlap, the order in which they are merged doesn't matter.
+        let db_txn: &DBTransaction = txn;
+        let staged: Vec<(Vec<usize>, HashMap<Address, Option<Account>>)> = parallel
+            .into_iter()
+            .map(|partition| {
+                let accounts = partition
+                    .iter()
+                    .flat_map(|&i| [&transactions[i].sender, &transactions[i].recipient])
+                    .map(|address| {
+                        let account = self.tree.get(db_txn, &KeyNibbles::from(address));
+                        (address.clone(), account)
+                    })
+                    .collect();
+                (partition, accounts)
+            })
+            .collect();
+
+        let staged = staged
+            .into_par_iter()
+            .map(|(partition, accounts)| {
+                self.apply_basic_transfers(transactions, &partition, accounts)
+            })
+            .collect::<Result<Vec<_>, AccountError>>()?;
+
+        for accounts in staged {
+            for (address, account) in accounts {
+                let key = KeyNibbles::from(&address);
+                match account {
+                    Some(account) => self.tree.put(txn, &key, account),
+                    None => self.tree.remove(txn, &key),
+                }
+            }
+        }
+
+        for (index, data) in sender_data.into_iter().enumerate() {
+            receipts.push(Receipt::transaction(index as u16, true, data));
+        }
+
+        for (index, (transaction, data)) in transactions.iter().zip(recipient_data).enumerate() {
+            if !transaction
+                .flags
+                .contains(TransactionFlags::CONTRACT_CREATION)
+            {
+                receipts.push(Receipt::transaction(index as u16, false, data));
+            }
+        }
+
+        receipts.append(&mut self.commit_inherents(
+            txn,
+            &post_inherents,
+            block_height,
+            timestamp,
+            &mut events,
+        )?);
+
+        self.tree.update_root(txn);
+
+        Ok(Receipts::from(receipts))
+    }
+
+    /// Commits the given transactions and inherents like `commit`, but doesn't check them for
+    /// expiry or duplicates and doesn't update the root of the Accounts Trie. The root is updated by
+    /// `finalize_batch`, which allows several blocks to be committed at once.
     pub fn commit_batch(
         &self,
         txn: &mut WriteTransaction,
@@ -106,7 +529,25 @@ impl Accounts {
         block_height: u32,
         timestamp: u64,
     ) -> Result<Receipts, AccountError> {
+        self.commit_batch_with_events(txn, transactions, inherents, block_height, timestamp)
+            .map(|(receipts, _)| receipts)
+    }
+
+    /// Commits the given transactions and inherents like `commit_batch`, but also returns the
+    /// events that were produced, see `commit_with_events`.
+    pub fn commit_batch_with_events(
+        &self,
+        txn: &mut WriteTransaction,
+        transactions: &[Transaction],
+        inherents: &[Inherent],
+        block_height: u32,
+        timestamp: u64,
+    ) -> Result<(Receipts, AccountsEvents), AccountError> {
+        #[cfg(feature = "account-changes")]
+        let touched = self.snapshot_touched_accounts(txn, transactions, inherents);
+
         let mut receipts = Vec::new();
+        let mut events = AccountsEvents::default();
 
         let pre_inherents: Vec<Inherent> = inherents
             .iter()
@@ -119,13 +560,24 @@ impl Accounts {
             &pre_inherents,
             block_height,
             timestamp,
+            &mut events,
         )?);
 
-        receipts.append(&mut self.commit_senders(txn, transactions, block_height, timestamp)?);
-
-        receipts.append(&mut self.commit_recipients(txn, transactions, block_height, timestamp)?);
+        receipts.append(&mut self.commit_senders(
+            txn,
+            transactions,
+            block_height,
+            timestamp,
+            &mut events,
+        )?);
 
-        self.create_contracts(txn, transactions, block_height, timestamp)?;
+        receipts.append(&mut self.commit_recipients(
+            txn,
+            transactions,
+            block_height,
+            timestamp,
+            &mut events,
+        )?);
 
         let post_inherents: Vec<Inherent> = inherents
             .iter()
@@ -138,9 +590,13 @@ impl Accounts {
             &post_inherents,
             block_height,
             timestamp,
+            &mut events,
         )?);
 
-        Ok(Receipts::from(receipts))
+        #[cfg(feature = "account-changes")]
+        self.record_account_changes(&mut events, txn, block_height, touched);
+
+        Ok((Receipts::from(receipts), events))
     }
 
     pub fn revert(
@@ -152,7 +608,29 @@ impl Accounts {
         timestamp: u64,
         receipts: &Receipts,
     ) -> Result<(), AccountError> {
-        self.revert_batch(
+        self.revert_with_events(
+            txn,
+            transactions,
+            inherents,
+            block_height,
+            timestamp,
+            receipts,
+        )
+        .map(|_| ())
+    }
+
+    /// Reverts the given transactions and inherents like `revert`, but also returns the events
+    /// that were produced, see `commit_with_events`.
+    pub fn revert_with_events(
+        &self,
+        txn: &mut WriteTransaction,
+        transactions: &[Transaction],
+        inherents: &[Inherent],
+        block_height: u32,
+        timestamp: u64,
+        receipts: &Receipts,
+    ) -> Result<AccountsEvents, AccountError> {
+        let events = self.revert_batch_with_events(
             txn,
             transactions,
             inherents,
@@ -161,7 +639,7 @@ impl Accounts {
             receipts,
         )?;
         self.tree.update_root(txn);
-        Ok(())
+        Ok(events)
     }
 
     pub fn revert_batch(
@@ -173,6 +651,31 @@ impl Accounts {
         timestamp: u64,
         receipts: &Receipts,
     ) -> Result<(), AccountError> {
+        self.revert_batch_with_events(
+            txn,
+            transactions,
+            inherents,
+            block_height,
+            timestamp,
+            receipts,
+        )
+        .map(|_| ())
+    }
+
+    fn revert_batch_with_events(
+        &self,
+        txn: &mut WriteTransaction,
+        transactions: &[Transaction],
+        inherents: &[Inherent],
+        block_height: u32,
+        timestamp: u64,
+        receipts: &Receipts,
+    ) -> Result<AccountsEvents, AccountError> {
+        #[cfg(feature = "account-changes")]
+        let touched = self.snapshot_touched_accounts(txn, transactions, inherents);
+
+        let mut events = AccountsEvents::default();
+
         let (
             sender_receipts,
             recipient_receipts,
@@ -186,6 +689,7 @@ impl Accounts {
             block_height,
             timestamp,
             post_tx_inherent_receipts,
+            &mut events,
         )?;
 
         self.revert_contracts(txn, transactions, block_height, timestamp)?;
@@ -196,9 +700,17 @@ impl Accounts {
             block_height,
             timestamp,
             recipient_receipts,
+            &mut events,
         )?;
 
-        self.revert_senders(txn, transactions, block_height, timestamp, sender_receipts)?;
+        self.revert_senders(
+            txn,
+            transactions,
+            block_height,
+            timestamp,
+            sender_receipts,
+            &mut events,
+        )?;
 
         self.revert_inherents(
             txn,
@@ -206,43 +718,112 @@ impl Accounts {
             block_height,
             timestamp,
             pre_tx_inherent_receipts,
+            &mut events,
         )?;
 
-        Ok(())
+        #[cfg(feature = "account-changes")]
+        self.record_account_changes(&mut events, txn, block_height, touched);
+
+        Ok(events)
     }
 
     pub fn finalize_batch(&self, txn: &mut WriteTransaction) {
         self.tree.update_root(txn);
     }
 
+    /// Returns the indices of the given transactions in the order in which their recipient side is
+    /// applied, once the senders of all of them have been committed: first the recipients of the
+    /// transactions that don't create a contract and then the contract creations, each in the
+    /// given order. Block production uses the same order to select the transactions of a block.
+    pub fn recipient_order<'a>(
+        transactions: impl IntoIterator<Item = &'a Transaction>,
+    ) -> Vec<usize> {
+        let (creations, transfers): (Vec<_>, Vec<_>) = transactions
+            .into_iter()
+            .enumerate()
+            .partition(|(_, transaction)| {
+                transaction
+                    .flags
+                    .contains(TransactionFlags::CONTRACT_CREATION)
+            });
+
+        transfers
+            .into_iter()
+            .chain(creations)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    #[cfg_attr(not(feature = "staking-events"), allow(unused_variables))]
     fn commit_senders(
         &self,
         txn: &mut WriteTransaction,
         transactions: &[Transaction],
         block_height: u32,
         timestamp: u64,
+        events: &mut AccountsEvents,
     ) -> Result<Vec<Receipt>, AccountError> {
         let mut receipts = Vec::new();
 
         for (index, transaction) in transactions.iter().enumerate() {
-            let data = Account::commit_outgoing_transaction(
-                &self.tree,
-                txn,
-                transaction,
-                block_height,
-                timestamp,
-            )?;
+            let data = self.commit_sender(txn, transaction, block_height, timestamp, events)?;
 
-            receipts.push(Receipt::Transaction {
-                index: index as u16,
-                sender: true,
-                data,
-            });
+            receipts.push(Receipt::transaction(index as u16, true, data));
         }
 
         Ok(receipts)
     }
 
+    #[cfg_attr(not(feature = "staking-events"), allow(unused_variables))]
+    fn commit_sender(
+        &self,
+        txn: &mut WriteTransaction,
+        transaction: &Transaction,
+        block_height: u32,
+        timestamp: u64,
+        events: &mut AccountsEvents,
+    ) -> Result<Option<Vec<u8>>, AccountError> {
+        #[cfg(feature = "metrics")]
+        let start = Instant::now();
+
+        #[cfg(feature = "staking-events")]
+        let data =
+            if self.staking_events.is_some() && transaction.sender_type == AccountType::Staking {
+                let (data, event) = StakingContract::commit_outgoing_transaction_with_event(
+                    &self.tree,
+                    txn,
+                    transaction,
+                    block_height,
+                    timestamp,
+                )?;
+                events.staking_events.push((block_height, event));
+                data
+            } else {
+                Account::commit_outgoing_transaction(
+                    &self.tree,
+                    txn,
+                    transaction,
+                    block_height,
+                    timestamp,
+                )?
+            };
+
+        #[cfg(not(feature = "staking-events"))]
+        let data = Account::commit_outgoing_transaction(
+            &self.tree,
+            txn,
+            transaction,
+            block_height,
+            timestamp,
+        )?;
+
+        #[cfg(feature = "metrics")]
+        self.observe_commit(transaction.sender_type, start);
+
+        Ok(data)
+    }
+
+    #[cfg_attr(not(feature = "staking-events"), allow(unused_variables))]
     fn revert_senders(
         &self,
         txn: &mut WriteTransaction,
@@ -250,6 +831,7 @@ impl Accounts {
         block_height: u32,
         timestamp: u64,
         receipts: Vec<Receipt>,
+        events: &mut AccountsEvents,
     ) -> Result<(), AccountError> {
         for receipt in receipts {
             match receipt {
@@ -260,14 +842,32 @@ impl Accounts {
                 } => {
                     assert!(sender);
 
+                    let transaction = &transactions[index as usize];
+
+                    #[cfg(feature = "staking-events")]
+                    if self.staking_events.is_some()
+                        && transaction.sender_type == AccountType::Staking
+                    {
+                        let event = StakingContract::revert_outgoing_transaction_with_event(
+                            &self.tree,
+                            txn,
+                            transaction,
+                            block_height,
+                            timestamp,
+                            data.as_ref(),
+                        )?;
+                        events.staking_events.push((block_height, event));
+                        continue;
+                    }
+
                     Account::revert_outgoing_transaction(
                         &self.tree,
                         txn,
-                        &transactions[index as usize],
+                        transaction,
                         block_height,
                         timestamp,
                         data.as_ref(),
-                    )?
+                    )?;
                 }
                 _ => {
                     unreachable!()
@@ -278,42 +878,88 @@ impl Accounts {
         Ok(())
     }
 
+    #[cfg_attr(not(feature = "staking-events"), allow(unused_variables))]
     fn commit_recipients(
         &self,
         txn: &mut WriteTransaction,
         transactions: &[Transaction],
         block_height: u32,
         timestamp: u64,
+        events: &mut AccountsEvents,
     ) -> Result<Vec<Receipt>, AccountError> {
         let mut receipts = Vec::new();
 
-        for (index, transaction) in transactions.iter().enumerate() {
-            // If this is a contract creation transaction, skip it.
+        for index in Self::recipient_order(transactions) {
+            let transaction = &transactions[index];
+
+            // Contract creations don't have a receipt.
             if transaction
                 .flags
                 .contains(TransactionFlags::CONTRACT_CREATION)
             {
+                self.create_contract(txn, transaction, block_height, timestamp)?;
                 continue;
             }
 
-            let data = Account::commit_incoming_transaction(
+            let data = self.commit_recipient(txn, transaction, block_height, timestamp, events)?;
+
+            receipts.push(Receipt::transaction(index as u16, false, data));
+        }
+
+        Ok(receipts)
+    }
+
+    #[cfg_attr(not(feature = "staking-events"), allow(unused_variables))]
+    fn commit_recipient(
+        &self,
+        txn: &mut WriteTransaction,
+        transaction: &Transaction,
+        block_height: u32,
+        timestamp: u64,
+        events: &mut AccountsEvents,
+    ) -> Result<Option<Vec<u8>>, AccountError> {
+        #[cfg(feature = "metrics")]
+        let start = Instant::now();
+
+        #[cfg(feature = "staking-events")]
+        let data = if self.staking_events.is_some()
+            && transaction.recipient_type == AccountType::Staking
+        {
+            let (data, event) = StakingContract::commit_incoming_transaction_with_event(
                 &self.tree,
                 txn,
                 transaction,
                 block_height,
                 timestamp,
             )?;
+            events.staking_events.push((block_height, event));
+            data
+        } else {
+            Account::commit_incoming_transaction(
+                &self.tree,
+                txn,
+                transaction,
+                block_height,
+                timestamp,
+            )?
+        };
 
-            receipts.push(Receipt::Transaction {
-                index: index as u16,
-                sender: false,
-                data,
-            });
-        }
+        #[cfg(not(feature = "staking-events"))]
+        let data = Account::commit_incoming_transaction(
+            &self.tree,
+            txn,
+            transaction,
+            block_height,
+            timestamp,
+        )?;
 
-        Ok(receipts)
+        #[cfg(feature = "metrics")]
+        self.observe_commit(transaction.recipient_type, start);
+
+        Ok(data)
     }
 
+    #[cfg_attr(not(feature = "staking-events"), allow(unused_variables))]
     fn revert_recipients(
         &self,
         txn: &mut WriteTransaction,
@@ -321,6 +967,7 @@ impl Accounts {
         block_height: u32,
         timestamp: u64,
         receipts: Vec<Receipt>,
+        events: &mut AccountsEvents,
     ) -> Result<(), AccountError> {
         for receipt in receipts {
             match receipt {
@@ -331,14 +978,32 @@ impl Accounts {
                 } => {
                     assert!(!sender);
 
+                    let transaction = &transactions[index as usize];
+
+                    #[cfg(feature = "staking-events")]
+                    if self.staking_events.is_some()
+                        && transaction.recipient_type == AccountType::Staking
+                    {
+                        let event = StakingContract::revert_incoming_transaction_with_event(
+                            &self.tree,
+                            txn,
+                            transaction,
+                            block_height,
+                            timestamp,
+                            data.as_ref(),
+                        )?;
+                        events.staking_events.push((block_height, event));
+                        continue;
+                    }
+
                     Account::revert_incoming_transaction(
                         &self.tree,
                         txn,
-                        &transactions[index as usize],
+                        transaction,
                         block_height,
                         timestamp,
                         data.as_ref(),
-                    )?
+                    )?;
                 }
                 _ => {
                     unreachable!()
@@ -349,21 +1014,21 @@ impl Accounts {
         Ok(())
     }
 
-    fn create_contracts(
+    fn create_contract(
         &self,
         txn: &mut WriteTransaction,
-        transactions: &[Transaction],
+        transaction: &Transaction,
         block_height: u32,
         timestamp: u64,
     ) -> Result<(), AccountError> {
-        for transaction in transactions {
-            if transaction
-                .flags
-                .contains(TransactionFlags::CONTRACT_CREATION)
-            {
-                Account::create(&self.tree, txn, transaction, block_height, timestamp)?
-            }
-        }
+        #[cfg(feature = "metrics")]
+        let start = Instant::now();
+
+        Account::create(&self.tree, txn, transaction, block_height, timestamp)?;
+
+        #[cfg(feature = "metrics")]
+        self.observe_commit(transaction.recipient_type, start);
+
         Ok(())
     }
 
@@ -387,29 +1052,49 @@ impl Accounts {
         Ok(())
     }
 
+    #[cfg_attr(not(feature = "staking-events"), allow(unused_variables))]
     fn commit_inherents(
         &self,
         txn: &mut WriteTransaction,
         inherents: &[Inherent],
         block_height: u32,
         timestamp: u64,
+        events: &mut AccountsEvents,
     ) -> Result<Vec<Receipt>, AccountError> {
         let mut receipts = Vec::new();
 
         for (index, inherent) in inherents.iter().enumerate() {
+            #[cfg(feature = "staking-events")]
+            let data =
+                if self.staking_events.is_some() && inherent.target == STAKING_CONTRACT_ADDRESS {
+                    let (data, event) = StakingContract::commit_inherent_with_event(
+                        &self.tree,
+                        txn,
+                        inherent,
+                        block_height,
+                        timestamp,
+                    )?;
+                    events.staking_events.push((block_height, event));
+                    data
+                } else {
+                    Account::commit_inherent(&self.tree, txn, inherent, block_height, timestamp)?
+                };
+
+            #[cfg(not(feature = "staking-events"))]
             let data =
                 Account::commit_inherent(&self.tree, txn, inherent, block_height, timestamp)?;
 
-            receipts.push(Receipt::Inherent {
-                index: index as u16,
-                pre_transactions: inherent.is_pre_transactions(),
+            receipts.push(Receipt::inherent(
+                index as u16,
+                inherent.is_pre_transactions(),
                 data,
-            });
+            ));
         }
 
         Ok(receipts)
     }
 
+    #[cfg_attr(not(feature = "staking-events"), allow(unused_variables))]
     fn revert_inherents(
         &self,
         txn: &mut WriteTransaction,
@@ -417,17 +1102,37 @@ impl Accounts {
         block_height: u32,
         timestamp: u64,
         receipts: Vec<Receipt>,
+        events: &mut AccountsEvents,
     ) -> Result<(), AccountError> {
         for receipt in receipts {
             match receipt {
-                Receipt::Inherent { index, data, .. } => Account::revert_inherent(
-                    &self.tree,
-                    txn,
-                    &inherents[index as usize],
-                    block_height,
-                    timestamp,
-                    data.as_ref(),
-                )?,
+                Receipt::Inherent { index, data, .. } => {
+                    let inherent = &inherents[index as usize];
+
+                    #[cfg(feature = "staking-events")]
+                    if self.staking_events.is_some() && inherent.target == STAKING_CONTRACT_ADDRESS
+                    {
+                        let event = StakingContract::revert_inherent_with_event(
+                            &self.tree,
+                            txn,
+                            inherent,
+                            block_height,
+                            timestamp,
+                            data.as_ref(),
+                        )?;
+                        events.staking_events.push((block_height, event));
+                        continue;
+                    }
+
+                    Account::revert_inherent(
+                        &self.tree,
+                        txn,
+                        inherent,
+                        block_height,
+                        timestamp,
+                        data.as_ref(),
+                    )?;
+                }
                 _ => {
                     unreachable!()
                 }
@@ -437,6 +1142,209 @@ impl Accounts {
         Ok(())
     }
 
+    /// Rejects transactions whose validity window has already passed.
+    fn check_expired(transactions: &[Transaction], block_height: u32) -> Result<(), AccountError> {
+        if let Some(tx) = transactions
+            .iter()
+            .find(|tx| tx.is_expired_at(block_height))
+        {
+            return Err(AccountError::Expired {
+                validity_start_height: tx.validity_start_height,
+                block_height,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Rejects transaction lists that contain the same transaction more than once.
+    fn check_duplicates(transactions: &[Transaction]) -> Result<(), AccountError> {
+        let mut hashes = HashSet::with_capacity(transactions.len());
+        for tx in transactions {
+            let hash: Blake2bHash = tx.hash();
+            if !hashes.insert(hash.clone()) {
+                return Err(AccountError::DuplicateTransaction { hash });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the transaction only moves funds from one basic account to another.
+    fn is_basic_transfer(transaction: &Transaction) -> bool {
+        transaction.sender_type == AccountType::Basic
+            && transaction.recipient_type == AccountType::Basic
+            && !transaction
+                .flags
+                .contains(TransactionFlags::CONTRACT_CREATION)
+    }
+
+    /// Groups the indices of the given transactions into partitions such that no two partitions
+    /// share a sender or recipient address. Indices within a partition keep their original order.
+    fn partition_transactions(transactions: &[Transaction]) -> Vec<Vec<usize>> {
+        fn find(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+
+        let mut parents: Vec<usize> = (0..transactions.len()).collect();
+        let mut owners: HashMap<&Address, usize> = HashMap::new();
+
+        for (i, transaction) in transactions.iter().enumerate() {
+            for address in [&transaction.sender, &transaction.recipient] {
+                match owners.get(address) {
+                    Some(&j) => {
+                        let root_i = find(&mut parents, i);
+                        let root_j = find(&mut parents, j);
+                        parents[root_i.max(root_j)] = root_i.min(root_j);
+                    }
+                    None => {
+                        owners.insert(address, i);
+                    }
+                }
+            }
+        }
+
+        let mut partitions: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for i in 0..transactions.len() {
+            let root = find(&mut parents, i);
+            partitions.entry(root).or_default().push(i);
+        }
+
+        partitions.into_values().collect()
+    }
+
+    /// Applies a partition of basic transfers to the given staged accounts, using the same logic as
+    /// `BasicAccount`: all senders are debited first, then all recipients are credited.
+    fn apply_basic_transfers(
+        &self,
+        transactions: &[Transaction],
+        partition: &[usize],
+        mut accounts: HashMap<Address, Option<Account>>,
+    ) -> Result<HashMap<Address, Option<Account>>, AccountError> {
+        for &i in partition {
+            #[cfg(feature = "metrics")]
+            let start = Instant::now();
+
+            let transaction = &transactions[i];
+            let account = accounts
+                .get_mut(&transaction.sender)
+                .expect("Sender account must be staged");
+
+            *account = BasicAccount::debit(account.as_ref(), transaction)?;
+
+            #[cfg(feature = "metrics")]
+            self.observe_commit(transaction.sender_type, start);
+        }
+
+        for &i in partition {
+            #[cfg(feature = "metrics")]
+            let start = Instant::now();
+
+            let transaction = &transactions[i];
+            let account = accounts
+                .get_mut(&transaction.recipient)
+                .expect("Recipient account must be staged");
+
+            *account = Some(BasicAccount::credit(account.as_ref(), transaction)?);
+
+            #[cfg(feature = "metrics")]
+            self.observe_commit(transaction.recipient_type, start);
+        }
+
+        Ok(accounts)
+    }
+
+    #[cfg(feature = "metrics")]
+    fn observe_commit(&self, account_type: AccountType, start: Instant) {
+        if let Some(histogram) = &self.commit_histogram {
+            histogram.observe(account_type, start.elapsed());
+        }
+    }
+
+    /// Returns the current state of all accounts that the given transactions and inherents touch,
+    /// or `None` if no observer is attached. If the staking contract is touched, all of its
+    /// entries (the contract itself, the validators and the stakers) are part of the snapshot.
+    #[cfg(feature = "account-changes")]
+    fn snapshot_touched_accounts(
+        &self,
+        txn: &DBTransaction,
+        transactions: &[Transaction],
+        inherents: &[Inherent],
+    ) -> Option<TouchedAccounts> {
+        self.observer.as_ref()?;
+
+        let addresses = transactions
+            .iter()
+            .flat_map(|tx| [&tx.sender, &tx.recipient])
+            .chain(inherents.iter().map(|inherent| &inherent.target));
+
+        let mut touched = TouchedAccounts::default();
+        for address in addresses {
+            if *address == STAKING_CONTRACT_ADDRESS {
+                touched.staking_contract = true;
+            } else {
+                let key = KeyNibbles::from(address);
+                let account = self.tree.get(txn, &key);
+                touched.accounts.insert(key, account);
+            }
+        }
+
+        if touched.staking_contract {
+            for (key, account) in self.staking_contract_entries(txn) {
+                touched.accounts.insert(key, Some(account));
+            }
+        }
+
+        Some(touched)
+    }
+
+    /// Adds every account in the snapshot that changed since it was taken to the changes to be
+    /// delivered to the observer. Entries of the staking contract that didn't exist when the
+    /// snapshot was taken are reported as created.
+    #[cfg(feature = "account-changes")]
+    fn record_account_changes(
+        &self,
+        events: &mut AccountsEvents,
+        txn: &DBTransaction,
+        block_height: u32,
+        touched: Option<TouchedAccounts>,
+    ) {
+        let touched = match touched {
+            Some(touched) => touched,
+            None => return,
+        };
+
+        let mut after: BTreeMap<KeyNibbles, Option<Account>> = touched
+            .accounts
+            .keys()
+            .map(|key| (key.clone(), self.tree.get(txn, key)))
+            .collect();
+
+        if touched.staking_contract {
+            for (key, account) in self.staking_contract_entries(txn) {
+                after.insert(key, Some(account));
+            }
+        }
+
+        for (key, after) in after {
+            let before = touched.accounts.get(&key).cloned().flatten();
+            if let Some(change) = AccountChange::between(before.as_ref(), after.as_ref()) {
+                events.account_changes.push((block_height, key, change));
+            }
+        }
+    }
+
+    /// Returns all entries of the staking contract in the Accounts Trie.
+    #[cfg(feature = "account-changes")]
+    fn staking_contract_entries(&self, txn: &DBTransaction) -> Vec<(KeyNibbles, Account)> {
+        self.tree
+            .get_with_prefix(txn, &KeyNibbles::from(&STAKING_CONTRACT_ADDRESS))
+    }
+
     fn prepare_receipts(
         receipts: &Receipts,
     ) -> (Vec<Receipt>, Vec<Receipt>, Vec<Receipt>, Vec<Receipt>) {
@@ -446,18 +1354,16 @@ impl Accounts {
         let mut post_tx_inherent_receipts = Vec::new();
 
         for receipt in &receipts.receipts {
-            match receipt {
-                Receipt::Transaction { sender, .. } => {
-                    if *sender {
+            match receipt.receipt_type() {
+                ReceiptType::Transaction => {
+                    if receipt.is_sender() {
                         sender_receipts.push(receipt.clone());
                     } else {
                         recipient_receipts.push(receipt.clone());
                     }
                 }
-                Receipt::Inherent {
-                    pre_transactions, ..
-                } => {
-                    if *pre_transactions {
+                ReceiptType::Inherent => {
+                    if receipt.is_pre_transactions() {
                         pre_tx_inherent_receipts.push(receipt.clone());
                     } else {
                         post_tx_inherent_receipts.push(receipt.clone());
@@ -486,3 +1392,39 @@ impl Accounts {
         )
     }
 }
+
+/// A read-only view of the Accounts Trie, obtained with `Accounts::read_only_handle`. All reads go
+/// through the same read transaction, so they are consistent with each other.
+#[derive(Debug)]
+pub struct AccountsReader<'env> {
+    tree: &'env AccountsTrie,
+    txn: ReadTransaction<'env>,
+}
+
+impl<'env> AccountsReader<'env> {
+    /// Returns the account at the given key, if there is one.
+    pub fn get(&self, key: &KeyNibbles) -> Option<Account> {
+        self.tree.get(&self.txn, key)
+    }
+
+    /// Returns the root hash of the Accounts Trie.
+    pub fn get_root(&self) -> Blake2bHash {
+        self.tree.root_hash(&self.txn)
+    }
+
+    /// Returns an iterator over all accounts whose key starts with the given prefix, ordered by key.
+    pub fn iter_prefix(&self, prefix: &KeyNibbles) -> impl Iterator<Item = (KeyNibbles, Account)> {
+        self.tree.get_with_prefix(&self.txn, prefix).into_iter()
+    }
+}
+
+/// The state of the accounts touched by a batch before it is applied, see
+/// `Accounts::snapshot_touched_accounts`.
+#[cfg(feature = "account-changes")]
+#[derive(Default)]
+struct TouchedAccounts {
+    accounts: BTreeMap<KeyNibbles, Option<Account>>,
+    /// Whether the batch touches the staking contract, in which case all of its entries are part
+    /// of the snapshot.
+    staking_contract: bool,
+}
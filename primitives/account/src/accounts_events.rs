@@ -0,0 +1,39 @@
+#[cfg(feature = "account-changes")]
+use nimiq_trie::key_nibbles::KeyNibbles;
+
+#[cfg(feature = "account-changes")]
+use crate::AccountChange;
+#[cfg(feature = "staking-events")]
+use crate::StakingEvent;
+
+/// The events that are produced by committing or reverting a batch. They are returned by the
+/// commit and revert methods of `Accounts` instead of being emitted right away, since the database
+/// transaction they were produced in might still be aborted. Once the transaction is committed,
+/// they can be delivered with `Accounts::notify`.
+///
+/// Events are only gathered for the sinks and observers that are attached to the `Accounts`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountsEvents {
+    #[cfg(feature = "staking-events")]
+    pub(crate) staking_events: Vec<(u32, StakingEvent)>,
+    #[cfg(feature = "account-changes")]
+    pub(crate) account_changes: Vec<(u32, KeyNibbles, AccountChange)>,
+}
+
+impl AccountsEvents {
+    /// Appends the events of `other` after the events of `self`.
+    pub fn append(&mut self, other: AccountsEvents) {
+        let AccountsEvents {
+            #[cfg(feature = "staking-events")]
+            staking_events,
+            #[cfg(feature = "account-changes")]
+            account_changes,
+        } = other;
+
+        #[cfg(feature = "staking-events")]
+        self.staking_events.extend(staking_events);
+
+        #[cfg(feature = "account-changes")]
+        self.account_changes.extend(account_changes);
+    }
+}
@@ -0,0 +1,48 @@
+use beserial::{Deserialize, Serialize};
+use nimiq_hash::Blake2bHash;
+use nimiq_trie::key_nibbles::KeyNibbles;
+use nimiq_trie::trie_proof::TrieProof;
+
+use crate::Account;
+
+/// A single Merkle proof for several accounts, as returned by `Accounts::get_many_with_proof`.
+/// It proves both the accounts that exist and the absence of the ones that don't. The nodes that
+/// the paths to the different accounts have in common are only included once.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultiProof {
+    pub proof: TrieProof<Account>,
+}
+
+impl MultiProof {
+    /// Checks that the proof is valid for the given root hash and that it shows exactly the given
+    /// accounts (or their absence, for `None`) at the given keys.
+    pub fn verify(
+        &self,
+        root_hash: &Blake2bHash,
+        keys: &[KeyNibbles],
+        accounts: &[Option<Account>],
+    ) -> bool {
+        if keys.len() != accounts.len() {
+            error!(
+                "Got {} keys but {} accounts to verify.",
+                keys.len(),
+                accounts.len()
+            );
+            return false;
+        }
+
+        if !self.proof.verify(root_hash) {
+            return false;
+        }
+
+        keys.iter()
+            .zip(accounts)
+            .all(|(key, account)| match self.proof.lookup(key) {
+                Some(proven) => &proven == account,
+                None => {
+                    error!("The proof doesn't cover the key {}.", key);
+                    false
+                }
+            })
+    }
+}
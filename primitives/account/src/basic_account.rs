@@ -15,6 +15,67 @@ pub struct BasicAccount {
     pub balance: Coin,
 }
 
+impl BasicAccount {
+    /// Returns the given sender account after the total value of the transaction was deducted from
+    /// it, or `None` if the account is emptied. A missing sender is only valid for transactions
+    /// with a total value of zero.
+    pub(crate) fn debit(
+        account: Option<&Account>,
+        transaction: &Transaction,
+    ) -> Result<Option<Account>, AccountError> {
+        let current_balance = match account {
+            Some(Account::Basic(account)) => account.balance,
+            None if transaction.total_value().is_zero() => Coin::ZERO,
+            None => {
+                return Err(AccountError::NonExistentAddress {
+                    address: transaction.sender.clone(),
+                })
+            }
+            Some(account) => {
+                return Err(AccountError::TypeMismatch {
+                    expected: AccountType::Basic,
+                    got: account.account_type(),
+                })
+            }
+        };
+
+        let new_balance = Account::balance_sub(current_balance, transaction.total_value())?;
+
+        if new_balance.is_zero() {
+            Ok(None)
+        } else {
+            Ok(Some(Account::Basic(BasicAccount {
+                balance: new_balance,
+            })))
+        }
+    }
+
+    /// Returns the given recipient account after the value of the transaction was added to it. A
+    /// missing recipient is treated as an empty basic account.
+    pub(crate) fn credit(
+        account: Option<&Account>,
+        transaction: &Transaction,
+    ) -> Result<Account, AccountError> {
+        // Implicitly also checks that the address is in fact from a basic account.
+        let current_balance = match account {
+            Some(Account::Basic(account)) => account.balance,
+            None => Coin::ZERO,
+            Some(account) => {
+                return Err(AccountError::TypeMismatch {
+                    expected: AccountType::Basic,
+                    got: account.account_type(),
+                })
+            }
+        };
+
+        let new_balance = Account::balance_add(current_balance, transaction.value)?;
+
+        Ok(Account::Basic(BasicAccount {
+            balance: new_balance,
+        }))
+    }
+}
+
 impl AccountTransactionInteraction for BasicAccount {
     fn create(
         _accounts_tree: &AccountsTrie,
@@ -35,29 +96,9 @@ impl AccountTransactionInteraction for BasicAccount {
     ) -> Result<Option<Vec<u8>>, AccountError> {
         let key = KeyNibbles::from(&transaction.recipient);
 
-        let leaf = accounts_tree.get(db_txn, &key);
-
-        // Implicitly also checks that the address is in fact from a basic account.
-        let current_balance = match leaf {
-            Some(Account::Basic(account)) => account.balance,
-            None => Coin::ZERO,
-            _ => {
-                return Err(AccountError::TypeMismatch {
-                    expected: AccountType::Basic,
-                    got: leaf.unwrap().account_type(),
-                })
-            }
-        };
-
-        let new_balance = Account::balance_add(current_balance, transaction.value)?;
+        let account = Self::credit(accounts_tree.get(db_txn, &key).as_ref(), transaction)?;
 
-        accounts_tree.put(
-            db_txn,
-            &key,
-            Account::Basic(BasicAccount {
-                balance: new_balance,
-            }),
-        );
+        accounts_tree.put(db_txn, &key, account);
 
         Ok(None)
     }
@@ -108,40 +149,9 @@ impl AccountTransactionInteraction for BasicAccount {
     ) -> Result<Option<Vec<u8>>, AccountError> {
         let key = KeyNibbles::from(&transaction.sender);
 
-        let account = accounts_tree
-            .get(db_txn, &key)
-            .or_else(|| {
-                if transaction.total_value() != Coin::ZERO {
-                    None
-                } else {
-                    Some(Account::Basic(BasicAccount {
-                        balance: Coin::ZERO,
-                    }))
-                }
-            })
-            .ok_or(AccountError::NonExistentAddress {
-                address: transaction.sender.clone(),
-            })?;
-
-        if account.account_type() != AccountType::Basic {
-            return Err(AccountError::TypeMismatch {
-                expected: AccountType::Basic,
-                got: account.account_type(),
-            });
-        }
-
-        let new_balance = Account::balance_sub(account.balance(), transaction.total_value())?;
-
-        if new_balance.is_zero() {
-            accounts_tree.remove(db_txn, &key);
-        } else {
-            accounts_tree.put(
-                db_txn,
-                &key,
-                Account::Basic(BasicAccount {
-                    balance: new_balance,
-                }),
-            );
+        match Self::debit(accounts_tree.get(db_txn, &key).as_ref(), transaction)? {
+            Some(account) => accounts_tree.put(db_txn, &key, account),
+            None => accounts_tree.remove(db_txn, &key),
         }
 
         Ok(None)
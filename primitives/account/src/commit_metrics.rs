@@ -0,0 +1,51 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use nimiq_primitives::account::AccountType;
+
+/// A sink for the time spent committing transactions, broken down by the type of the account that
+/// was touched. It can be attached to the `Accounts` struct to find out which account types
+/// dominate the commit time.
+pub trait CommitHistogram: Debug + Send + Sync {
+    /// Records that a commit operation on an account of the given type took `elapsed`.
+    fn observe(&self, account_type: AccountType, elapsed: Duration);
+}
+
+/// A simple `CommitHistogram` that accumulates the number of operations and the total time spent
+/// per account type.
+#[derive(Debug, Default)]
+pub struct CommitTimings {
+    timings: Mutex<BTreeMap<AccountType, (usize, Duration)>>,
+}
+
+impl CommitTimings {
+    /// Returns the number of recorded operations for the given account type.
+    pub fn count(&self, account_type: AccountType) -> usize {
+        self.timings
+            .lock()
+            .get(&account_type)
+            .map(|(count, _)| *count)
+            .unwrap_or(0)
+    }
+
+    /// Returns the total time recorded for the given account type.
+    pub fn total(&self, account_type: AccountType) -> Duration {
+        self.timings
+            .lock()
+            .get(&account_type)
+            .map(|(_, total)| *total)
+            .unwrap_or_default()
+    }
+}
+
+impl CommitHistogram for CommitTimings {
+    fn observe(&self, account_type: AccountType, elapsed: Duration) {
+        let mut timings = self.timings.lock();
+        let entry = timings.entry(account_type).or_default();
+        entry.0 += 1;
+        entry.1 += elapsed;
+    }
+}
@@ -1,6 +1,7 @@
 use thiserror::Error;
 
 use beserial::SerializingError;
+use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
 use nimiq_primitives::account::AccountType;
 use nimiq_primitives::coin::{Coin, CoinConvertError, CoinParseError};
@@ -21,6 +22,8 @@ pub enum AccountError {
     InvalidForSender,
     #[error("Invalid for recipient")]
     InvalidForRecipient,
+    #[error("The staking contract can't be created by a transaction, it only exists from genesis")]
+    StakingContractNotCreatable,
     #[error("Invalid for target")]
     InvalidForTarget,
     #[error("Invalid receipt")]
@@ -41,4 +44,20 @@ pub enum AccountError {
     NonExistentAddress { address: Address },
     #[error("There is already an account at address {address} in the Accounts Tree.")]
     AlreadyExistentAddress { address: Address },
+    #[error("Transaction expired: validity start height {validity_start_height}, block height {block_height}")]
+    Expired {
+        validity_start_height: u32,
+        block_height: u32,
+    },
+    #[error("Duplicate transaction {hash}")]
+    DuplicateTransaction { hash: Blake2bHash },
+    #[error("Can't import a snapshot into a non-empty Accounts Tree")]
+    SnapshotTargetNotEmpty,
+    #[error("Snapshot root mismatch: expected {expected}, but got {got}")]
+    InvalidSnapshot {
+        expected: Blake2bHash,
+        got: Blake2bHash,
+    },
+    #[error("The state with root {root} is not available")]
+    RootNotAvailable { root: Blake2bHash },
 }
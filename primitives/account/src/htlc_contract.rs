@@ -145,6 +145,8 @@ impl AccountTransactionInteraction for HashedTimeLockedContract {
             }
         };
 
+        // The contract can be redeemed partially, it is only pruned once its balance is drained.
+        // Redeeming more than the remaining balance fails with `InsufficientFunds`.
         let new_balance = Account::balance_sub(account.balance(), transaction.total_value())?;
 
         let proof_buf = &mut &transaction.proof[..];
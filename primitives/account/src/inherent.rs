@@ -4,6 +4,8 @@ use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteByte
 use nimiq_hash::{Hash, SerializeContent};
 use nimiq_keys::Address;
 use nimiq_primitives::coin::Coin;
+use nimiq_primitives::policy;
+use nimiq_primitives::slots::SlashedSlot;
 
 #[derive(Clone, Debug, Eq, PartialEq, Copy, Serialize, Deserialize)]
 #[repr(u8)]
@@ -35,6 +37,46 @@ pub struct Inherent {
 }
 
 impl Inherent {
+    /// Creates an inherent that pays the given reward to the target address.
+    pub fn reward(target: Address, value: Coin) -> Self {
+        Inherent {
+            ty: InherentType::Reward,
+            target,
+            value,
+            data: vec![],
+        }
+    }
+
+    /// Creates an inherent that slashes the given slot in the staking contract.
+    pub fn slash(slot: &SlashedSlot) -> Self {
+        Inherent {
+            ty: InherentType::Slash,
+            target: policy::STAKING_CONTRACT_ADDRESS,
+            value: Coin::ZERO,
+            data: slot.serialize_to_vec(),
+        }
+    }
+
+    /// Creates an inherent that finalizes the current batch in the staking contract.
+    pub fn finalize_batch() -> Self {
+        Inherent {
+            ty: InherentType::FinalizeBatch,
+            target: policy::STAKING_CONTRACT_ADDRESS,
+            value: Coin::ZERO,
+            data: vec![],
+        }
+    }
+
+    /// Creates an inherent that finalizes the current epoch in the staking contract.
+    pub fn finalize_epoch() -> Self {
+        Inherent {
+            ty: InherentType::FinalizeEpoch,
+            target: policy::STAKING_CONTRACT_ADDRESS,
+            value: Coin::ZERO,
+            data: vec![],
+        }
+    }
+
     #[inline]
     pub fn is_pre_transactions(&self) -> bool {
         self.ty.is_pre_transactions()
@@ -4,9 +4,15 @@ extern crate beserial_derive;
 extern crate log;
 
 pub use crate::account::Account;
-pub use crate::accounts::{Accounts, AccountsTrie};
+#[cfg(feature = "account-changes")]
+pub use crate::account_changes::{AccountChange, AccountChangeLog, AccountsObserver};
+pub use crate::accounts::{Accounts, AccountsReader, AccountsTrie};
+pub use crate::accounts_events::AccountsEvents;
 pub use crate::accounts_list::AccountsList;
+pub use crate::accounts_proof::MultiProof;
 pub use crate::basic_account::BasicAccount;
+#[cfg(feature = "metrics")]
+pub use crate::commit_metrics::{CommitHistogram, CommitTimings};
 pub use crate::error::AccountError;
 pub use crate::htlc_contract::*;
 pub use crate::inherent::{Inherent, InherentType};
@@ -16,9 +22,15 @@ pub use crate::staking_contract::*;
 pub use crate::vesting_contract::*;
 
 mod account;
+#[cfg(feature = "account-changes")]
+mod account_changes;
 mod accounts;
+mod accounts_events;
 mod accounts_list;
+mod accounts_proof;
 mod basic_account;
+#[cfg(feature = "metrics")]
+mod commit_metrics;
 mod error;
 mod htlc_contract;
 mod inherent;
@@ -29,6 +29,55 @@ pub enum Receipt {
 }
 
 impl Receipt {
+    /// Creates the receipt of the transaction at `index` in the block, for either its sender or its
+    /// recipient.
+    pub fn transaction(index: u16, sender: bool, data: Option<Vec<u8>>) -> Self {
+        Receipt::Transaction {
+            index,
+            sender,
+            data,
+        }
+    }
+
+    /// Creates the receipt of the inherent at `index` in the block.
+    pub fn inherent(index: u16, pre_transactions: bool, data: Option<Vec<u8>>) -> Self {
+        Receipt::Inherent {
+            index,
+            pre_transactions,
+            data,
+        }
+    }
+
+    /// Returns the index of the transaction or inherent in the block.
+    pub fn index(&self) -> u16 {
+        match self {
+            Receipt::Transaction { index, .. } | Receipt::Inherent { index, .. } => *index,
+        }
+    }
+
+    /// Returns true if this is the receipt of a transaction for its sender.
+    pub fn is_sender(&self) -> bool {
+        matches!(self, Receipt::Transaction { sender: true, .. })
+    }
+
+    /// Returns true if this is the receipt of an inherent that is applied before the transactions.
+    pub fn is_pre_transactions(&self) -> bool {
+        matches!(
+            self,
+            Receipt::Inherent {
+                pre_transactions: true,
+                ..
+            }
+        )
+    }
+
+    /// Returns the data needed to revert the transaction or inherent, if any.
+    pub fn data(&self) -> Option<&Vec<u8>> {
+        match self {
+            Receipt::Transaction { data, .. } | Receipt::Inherent { data, .. } => data.as_ref(),
+        }
+    }
+
     pub fn receipt_type(&self) -> ReceiptType {
         match self {
             Receipt::Transaction { .. } => ReceiptType::Transaction,
@@ -0,0 +1,313 @@
+use std::fmt::Debug;
+
+use parking_lot::Mutex;
+
+use beserial::Deserialize;
+use nimiq_database::WriteTransaction;
+use nimiq_keys::Address;
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::slots::SlashedSlot;
+use nimiq_transaction::account::staking_contract::{
+    IncomingStakingTransactionData, OutgoingStakingTransactionProof,
+};
+use nimiq_transaction::Transaction;
+
+use crate::interaction_traits::{AccountInherentInteraction, AccountTransactionInteraction};
+use crate::{AccountError, AccountsTrie, Inherent, InherentType, StakingContract};
+
+/// A structured description of a state transition of the staking contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StakingEvent {
+    ValidatorCreated {
+        validator_address: Address,
+    },
+    ValidatorUpdated {
+        validator_address: Address,
+    },
+    ValidatorInactivated {
+        validator_address: Address,
+    },
+    ValidatorReactivated {
+        validator_address: Address,
+    },
+    ValidatorUnparked {
+        validator_address: Address,
+    },
+    ValidatorDeleted {
+        validator_address: Address,
+    },
+    ValidatorSlashed {
+        validator_address: Address,
+        slot: u16,
+        event_block: u32,
+    },
+    StakerCreated {
+        staker_address: Address,
+        delegation: Option<Address>,
+        value: Coin,
+    },
+    StakeAdded {
+        staker_address: Address,
+        value: Coin,
+    },
+    StakerUpdated {
+        staker_address: Address,
+        new_delegation: Option<Address>,
+    },
+    /// Coins were unstaked. `value` is paid out to the recipient, and the `fee` is deducted from
+    /// the stake as well.
+    StakeRemoved {
+        staker_address: Address,
+        value: Coin,
+        fee: Coin,
+    },
+    BatchFinalized,
+    EpochFinalized,
+    /// A previously emitted event was reverted.
+    Reverted(Box<StakingEvent>),
+}
+
+impl StakingContract {
+    /// Commits an incoming transaction like `commit_incoming_transaction` and also returns the
+    /// event that describes the resulting state transition.
+    pub(crate) fn commit_incoming_transaction_with_event(
+        accounts_tree: &AccountsTrie,
+        db_txn: &mut WriteTransaction,
+        transaction: &Transaction,
+        block_height: u32,
+        block_time: u64,
+    ) -> Result<(Option<Vec<u8>>, StakingEvent), AccountError> {
+        let receipt =
+            <StakingContract as AccountTransactionInteraction>::commit_incoming_transaction(
+                accounts_tree,
+                db_txn,
+                transaction,
+                block_height,
+                block_time,
+            )?;
+
+        Ok((receipt, Self::incoming_event(transaction)?))
+    }
+
+    /// Reverts an incoming transaction like `revert_incoming_transaction` and returns the event
+    /// of the reverted state transition.
+    pub(crate) fn revert_incoming_transaction_with_event(
+        accounts_tree: &AccountsTrie,
+        db_txn: &mut WriteTransaction,
+        transaction: &Transaction,
+        block_height: u32,
+        block_time: u64,
+        receipt: Option<&Vec<u8>>,
+    ) -> Result<StakingEvent, AccountError> {
+        <StakingContract as AccountTransactionInteraction>::revert_incoming_transaction(
+            accounts_tree,
+            db_txn,
+            transaction,
+            block_height,
+            block_time,
+            receipt,
+        )?;
+
+        Ok(StakingEvent::Reverted(Box::new(Self::incoming_event(
+            transaction,
+        )?)))
+    }
+
+    /// Commits an outgoing transaction like `commit_outgoing_transaction` and also returns the
+    /// event that describes the resulting state transition.
+    pub(crate) fn commit_outgoing_transaction_with_event(
+        accounts_tree: &AccountsTrie,
+        db_txn: &mut WriteTransaction,
+        transaction: &Transaction,
+        block_height: u32,
+        block_time: u64,
+    ) -> Result<(Option<Vec<u8>>, StakingEvent), AccountError> {
+        let receipt =
+            <StakingContract as AccountTransactionInteraction>::commit_outgoing_transaction(
+                accounts_tree,
+                db_txn,
+                transaction,
+                block_height,
+                block_time,
+            )?;
+
+        Ok((receipt, Self::outgoing_event(transaction)?))
+    }
+
+    /// Reverts an outgoing transaction like `revert_outgoing_transaction` and returns the event
+    /// of the reverted state transition.
+    pub(crate) fn revert_outgoing_transaction_with_event(
+        accounts_tree: &AccountsTrie,
+        db_txn: &mut WriteTransaction,
+        transaction: &Transaction,
+        block_height: u32,
+        block_time: u64,
+        receipt: Option<&Vec<u8>>,
+    ) -> Result<StakingEvent, AccountError> {
+        <StakingContract as AccountTransactionInteraction>::revert_outgoing_transaction(
+            accounts_tree,
+            db_txn,
+            transaction,
+            block_height,
+            block_time,
+            receipt,
+        )?;
+
+        Ok(StakingEvent::Reverted(Box::new(Self::outgoing_event(
+            transaction,
+        )?)))
+    }
+
+    /// Commits an inherent like `commit_inherent` and also returns the event that describes the
+    /// resulting state transition.
+    pub(crate) fn commit_inherent_with_event(
+        accounts_tree: &AccountsTrie,
+        db_txn: &mut WriteTransaction,
+        inherent: &Inherent,
+        block_height: u32,
+        block_time: u64,
+    ) -> Result<(Option<Vec<u8>>, StakingEvent), AccountError> {
+        let receipt = <StakingContract as AccountInherentInteraction>::commit_inherent(
+            accounts_tree,
+            db_txn,
+            inherent,
+            block_height,
+            block_time,
+        )?;
+
+        Ok((receipt, Self::inherent_event(inherent)?))
+    }
+
+    /// Reverts an inherent like `revert_inherent` and returns the event of the reverted state
+    /// transition.
+    pub(crate) fn revert_inherent_with_event(
+        accounts_tree: &AccountsTrie,
+        db_txn: &mut WriteTransaction,
+        inherent: &Inherent,
+        block_height: u32,
+        block_time: u64,
+        receipt: Option<&Vec<u8>>,
+    ) -> Result<StakingEvent, AccountError> {
+        <StakingContract as AccountInherentInteraction>::revert_inherent(
+            accounts_tree,
+            db_txn,
+            inherent,
+            block_height,
+            block_time,
+            receipt,
+        )?;
+
+        Ok(StakingEvent::Reverted(Box::new(Self::inherent_event(
+            inherent,
+        )?)))
+    }
+
+    fn incoming_event(transaction: &Transaction) -> Result<StakingEvent, AccountError> {
+        let event = match IncomingStakingTransactionData::parse(transaction)? {
+            IncomingStakingTransactionData::CreateValidator { proof, .. } => {
+                StakingEvent::ValidatorCreated {
+                    validator_address: proof.compute_signer(),
+                }
+            }
+            IncomingStakingTransactionData::UpdateValidator { proof, .. } => {
+                StakingEvent::ValidatorUpdated {
+                    validator_address: proof.compute_signer(),
+                }
+            }
+            IncomingStakingTransactionData::InactivateValidator {
+                validator_address, ..
+            } => StakingEvent::ValidatorInactivated { validator_address },
+            IncomingStakingTransactionData::ReactivateValidator {
+                validator_address, ..
+            } => StakingEvent::ValidatorReactivated { validator_address },
+            IncomingStakingTransactionData::UnparkValidator {
+                validator_address, ..
+            } => StakingEvent::ValidatorUnparked { validator_address },
+            IncomingStakingTransactionData::CreateStaker { delegation, proof } => {
+                StakingEvent::StakerCreated {
+                    staker_address: proof.compute_signer(),
+                    delegation,
+                    value: transaction.value,
+                }
+            }
+            IncomingStakingTransactionData::Stake { staker_address } => StakingEvent::StakeAdded {
+                staker_address,
+                value: transaction.value,
+            },
+            IncomingStakingTransactionData::UpdateStaker {
+                new_delegation,
+                proof,
+            } => StakingEvent::StakerUpdated {
+                staker_address: proof.compute_signer(),
+                new_delegation,
+            },
+        };
+
+        Ok(event)
+    }
+
+    fn outgoing_event(transaction: &Transaction) -> Result<StakingEvent, AccountError> {
+        let event = match OutgoingStakingTransactionProof::parse(transaction)? {
+            OutgoingStakingTransactionProof::DeleteValidator { proof } => {
+                StakingEvent::ValidatorDeleted {
+                    validator_address: proof.compute_signer(),
+                }
+            }
+            OutgoingStakingTransactionProof::Unstake { proof } => StakingEvent::StakeRemoved {
+                staker_address: proof.compute_signer(),
+                value: transaction.value,
+                fee: transaction.fee,
+            },
+        };
+
+        Ok(event)
+    }
+
+    fn inherent_event(inherent: &Inherent) -> Result<StakingEvent, AccountError> {
+        match inherent.ty {
+            InherentType::Slash => {
+                let slot: SlashedSlot = Deserialize::deserialize(&mut &inherent.data[..])?;
+                Ok(StakingEvent::ValidatorSlashed {
+                    validator_address: slot.validator_address,
+                    slot: slot.slot,
+                    event_block: slot.event_block,
+                })
+            }
+            InherentType::FinalizeBatch => Ok(StakingEvent::BatchFinalized),
+            InherentType::FinalizeEpoch => Ok(StakingEvent::EpochFinalized),
+            InherentType::Reward => Err(AccountError::InvalidForTarget),
+        }
+    }
+}
+
+/// A sink for the events of the staking contract. It can be attached to the `Accounts` struct to
+/// trace the state transitions of the staking contract.
+pub trait StakingEventSink: Debug + Send + Sync {
+    /// Records that the given event happened at the given block height.
+    fn emit(&self, block_height: u32, event: StakingEvent);
+}
+
+/// A simple `StakingEventSink` that keeps all events in memory.
+#[derive(Debug, Default)]
+pub struct StakingEventLog {
+    events: Mutex<Vec<(u32, StakingEvent)>>,
+}
+
+impl StakingEventLog {
+    /// Returns all recorded events together with their block heights, in the order they were
+    /// recorded.
+    pub fn events(&self) -> Vec<(u32, StakingEvent)> {
+        self.events.lock().clone()
+    }
+
+    /// Removes and returns all recorded events.
+    pub fn take(&self) -> Vec<(u32, StakingEvent)> {
+        std::mem::take(&mut *self.events.lock())
+    }
+}
+
+impl StakingEventSink for StakingEventLog {
+    fn emit(&self, block_height: u32, event: StakingEvent) {
+        self.events.lock().push((block_height, event));
+    }
+}
@@ -5,6 +5,8 @@ use beserial::{
     Deserialize, DeserializeWithLength, ReadBytesExt, Serialize, SerializeWithLength,
     SerializingError, WriteBytesExt,
 };
+#[cfg(feature = "staking-events")]
+pub use events::*;
 use nimiq_collections::BitSet;
 use nimiq_database::{Transaction as DBTransaction, WriteTransaction};
 use nimiq_keys::Address;
@@ -21,6 +23,8 @@ pub use validator::Validator;
 
 use crate::{Account, AccountsTrie};
 
+#[cfg(feature = "staking-events")]
+mod events;
 mod receipts;
 mod staker;
 mod traits;
@@ -89,6 +93,10 @@ impl StakingContract {
     /// This is the byte path for the stakers list in the staking contract.
     pub const PATH_STAKERS_LIST: u8 = 2;
 
+    /// The current version of the serialized staking contract. Version 0 is the legacy format
+    /// without a version byte, which is still accepted when deserializing.
+    pub const VERSION: u8 = 1;
+
     /// This is the byte path for the main struct for a single validator (in the validators list).
     pub const PATH_VALIDATOR_MAIN: u8 = 0;
 
@@ -204,6 +212,50 @@ impl StakingContract {
         stakers
     }
 
+    /// Get a list containing the addresses and stakes of all the stakers that are delegating for a
+    /// given validator, ordered by the staker address.
+    pub fn stakers_of(
+        accounts_tree: &AccountsTrie,
+        db_txn: &DBTransaction,
+        validator_address: &Address,
+    ) -> Vec<(Address, Coin)> {
+        StakingContract::get_validator_stakers(accounts_tree, db_txn, validator_address)
+            .into_iter()
+            .map(|staker_address| {
+                let staker = StakingContract::get_staker(accounts_tree, db_txn, &staker_address)
+                    .expect("Staking contract is inconsistent: delegating staker is missing");
+                (staker_address, staker.balance)
+            })
+            .collect()
+    }
+
+    /// Get the addresses of all validators that pay their rewards to the given address, ordered by
+    /// the validator address. The reward addresses aren't indexed, so this traverses the validators
+    /// list of the staking contract, including the stakers delegating to each validator.
+    pub fn validators_by_reward_address(
+        accounts_tree: &AccountsTrie,
+        db_txn: &DBTransaction,
+        reward_address: &Address,
+    ) -> Vec<Address> {
+        let mut bytes = Vec::with_capacity(21);
+        bytes.extend(policy::STAKING_CONTRACT_ADDRESS.as_bytes());
+        bytes.push(StakingContract::PATH_VALIDATORS_LIST);
+        let prefix = KeyNibbles::from(bytes.as_slice());
+
+        accounts_tree
+            .get_with_prefix(db_txn, &prefix)
+            .into_iter()
+            .filter_map(|(_, account)| match account {
+                Account::StakingValidator(validator)
+                    if validator.reward_address == *reward_address =>
+                {
+                    Some(validator.address)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Get a staker information given its address, if it exists.
     pub fn get_staker(
         accounts_tree: &AccountsTrie,
@@ -268,6 +320,70 @@ impl StakingContract {
         slots_builder.build()
     }
 
+    /// Asserts that the staking contract is consistent with the validators and stakers it tracks:
+    /// - its balance is the sum of all validator deposits and staker balances,
+    /// - the balance of every validator is its deposit plus the balances of its stakers,
+    /// - every active validator exists and is listed with its current balance.
+    ///
+    /// It traverses the whole staking contract, so it is only meant for tests and debugging. With
+    /// the `staking-invariants` feature it is run after every commit and revert.
+    pub fn assert_invariants(accounts_tree: &AccountsTrie, db_txn: &DBTransaction) {
+        let staking_contract = StakingContract::get_staking_contract(accounts_tree, db_txn);
+
+        let prefix = KeyNibbles::from(policy::STAKING_CONTRACT_ADDRESS.as_bytes());
+
+        let mut validators = BTreeMap::new();
+        let mut stakers = vec![];
+
+        for (_, account) in accounts_tree.get_with_prefix(db_txn, &prefix) {
+            match account {
+                Account::StakingValidator(validator) => {
+                    validators.insert(validator.address.clone(), validator);
+                }
+                Account::StakingStaker(staker) => stakers.push(staker),
+                _ => {}
+            }
+        }
+
+        let mut total = validators.len() as u64 * policy::VALIDATOR_DEPOSIT;
+        let mut delegated_stake: BTreeMap<&Address, u64> = BTreeMap::new();
+
+        for staker in &stakers {
+            total += u64::from(staker.balance);
+
+            if let Some(validator_address) = &staker.delegation {
+                *delegated_stake.entry(validator_address).or_default() += u64::from(staker.balance);
+            }
+        }
+
+        assert_eq!(
+            u64::from(staking_contract.balance),
+            total,
+            "Staking contract balance doesn't match the validator deposits and staker balances"
+        );
+
+        for (address, validator) in &validators {
+            let expected = policy::VALIDATOR_DEPOSIT + delegated_stake.get(address).unwrap_or(&0);
+            assert_eq!(
+                u64::from(validator.balance),
+                expected,
+                "Balance of validator {} doesn't match its deposit and stakers",
+                address
+            );
+        }
+
+        for (address, balance) in &staking_contract.active_validators {
+            let validator = validators
+                .get(address)
+                .unwrap_or_else(|| panic!("Active validator {} doesn't exist", address));
+            assert_eq!(
+                *balance, validator.balance,
+                "Active validator {} is listed with an outdated balance",
+                address
+            );
+        }
+    }
+
     /// Returns a BitSet of slots that lost its rewards in the previous batch.
     pub fn previous_lost_rewards(&self) -> BitSet {
         self.previous_lost_rewards.clone()
@@ -432,6 +548,8 @@ impl Serialize for StakingContract {
     fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
         let mut size = 0;
 
+        size += Serialize::serialize(&Self::VERSION, writer)?;
+
         size += Serialize::serialize(&self.balance, writer)?;
 
         size += SerializeWithLength::serialize::<u32, _>(&self.active_validators, writer)?;
@@ -457,6 +575,8 @@ impl Serialize for StakingContract {
 
     fn serialized_size(&self) -> usize {
         let mut size = 0;
+        size += Serialize::serialized_size(&Self::VERSION);
+
         size += Serialize::serialized_size(&self.balance);
 
         size += SerializeWithLength::serialized_size::<u32>(&self.active_validators);
@@ -483,7 +603,21 @@ impl Serialize for StakingContract {
 
 impl Deserialize for StakingContract {
     fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
-        let balance = Deserialize::deserialize(reader)?;
+        // The legacy (version 0) format has no version byte and starts directly with the balance.
+        // Since the balance never exceeds `Coin::MAX_SAFE_VALUE`, the most significant byte of a
+        // legacy blob is always zero, which lets us tell both formats apart.
+        let version: u8 = Deserialize::deserialize(reader)?;
+        let balance = match version {
+            0 => {
+                let mut remaining = [0u8; 7];
+                reader.read_exact(&mut remaining)?;
+                let mut bytes = [0u8; 8];
+                bytes[1..].copy_from_slice(&remaining);
+                Deserialize::deserialize(&mut &bytes[..])?
+            }
+            Self::VERSION => Deserialize::deserialize(reader)?,
+            _ => return Err(SerializingError::InvalidValue),
+        };
 
         let active_validators = DeserializeWithLength::deserialize::<u32, _>(reader)?;
 
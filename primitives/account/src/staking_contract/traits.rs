@@ -46,7 +46,7 @@ impl AccountTransactionInteraction for StakingContract {
         _block_height: u32,
         _block_time: u64,
     ) -> Result<(), AccountError> {
-        Err(AccountError::InvalidForRecipient)
+        Err(AccountError::StakingContractNotCreatable)
     }
 
     /// Commits an incoming transaction to the accounts trie.
@@ -199,6 +199,9 @@ impl AccountTransactionInteraction for StakingContract {
             }
         }
 
+        #[cfg(feature = "staking-invariants")]
+        StakingContract::assert_invariants(accounts_tree, db_txn);
+
         Ok(receipt)
     }
 
@@ -318,6 +321,9 @@ impl AccountTransactionInteraction for StakingContract {
             }
         }
 
+        #[cfg(feature = "staking-invariants")]
+        StakingContract::assert_invariants(accounts_tree, db_txn);
+
         Ok(())
     }
 
@@ -366,6 +372,9 @@ impl AccountTransactionInteraction for StakingContract {
             }
         };
 
+        #[cfg(feature = "staking-invariants")]
+        StakingContract::assert_invariants(accounts_tree, db_txn);
+
         Ok(receipt)
     }
 
@@ -416,6 +425,9 @@ impl AccountTransactionInteraction for StakingContract {
             }
         }
 
+        #[cfg(feature = "staking-invariants")]
+        StakingContract::assert_invariants(accounts_tree, db_txn);
+
         Ok(())
     }
 }
@@ -587,6 +599,9 @@ impl AccountInherentInteraction for StakingContract {
             Account::Staking(staking_contract),
         );
 
+        #[cfg(feature = "staking-invariants")]
+        StakingContract::assert_invariants(accounts_tree, db_txn);
+
         Ok(receipt)
     }
 
@@ -677,6 +692,9 @@ impl AccountInherentInteraction for StakingContract {
             Account::Staking(staking_contract),
         );
 
+        #[cfg(feature = "staking-invariants")]
+        StakingContract::assert_invariants(accounts_tree, db_txn);
+
         Ok(())
     }
 }
@@ -269,6 +269,43 @@ impl StakingContract {
         Ok(())
     }
 
+    /// Rotates the voting (BLS) key of a validator, e.g. after the old key got compromised. This is
+    /// the same as an `update_validator` that only changes the voting key, but it fails if the
+    /// validator doesn't exist. The returned receipt can be used to revert the rotation with
+    /// `revert_update_validator_key`.
+    pub fn update_validator_key(
+        accounts_tree: &AccountsTrie,
+        db_txn: &mut WriteTransaction,
+        validator_address: &Address,
+        new_voting_key: BlsPublicKey,
+    ) -> Result<UpdateValidatorReceipt, AccountError> {
+        if StakingContract::get_validator(accounts_tree, db_txn, validator_address).is_none() {
+            return Err(AccountError::NonExistentAddress {
+                address: validator_address.clone(),
+            });
+        }
+
+        StakingContract::update_validator(
+            accounts_tree,
+            db_txn,
+            validator_address,
+            None,
+            Some(new_voting_key),
+            None,
+            None,
+        )
+    }
+
+    /// Reverts rotating the voting key of a validator, restoring the key stored in the receipt.
+    pub fn revert_update_validator_key(
+        accounts_tree: &AccountsTrie,
+        db_txn: &mut WriteTransaction,
+        validator_address: &Address,
+        receipt: UpdateValidatorReceipt,
+    ) -> Result<(), AccountError> {
+        StakingContract::revert_update_validator(accounts_tree, db_txn, validator_address, receipt)
+    }
+
     /// Inactivates a validator. It is necessary to retire a validator before dropping it. This also
     /// removes the validator from the parking set.
     pub(crate) fn inactivate_validator(
@@ -64,6 +64,15 @@ impl VestingContract {
             Coin::ZERO
         }
     }
+
+    /// Returns the amount that can be withdrawn from the contract at the given time. This is the
+    /// part of the balance above the vesting min cap, i.e. `min(balance, vested_so_far)` as long as
+    /// nothing has been withdrawn yet.
+    pub fn claimable_at(&self, time: u64) -> Coin {
+        self.balance
+            .checked_sub(self.min_cap(time))
+            .unwrap_or(Coin::ZERO)
+    }
 }
 
 impl AccountTransactionInteraction for VestingContract {
@@ -145,13 +154,11 @@ impl AccountTransactionInteraction for VestingContract {
 
         let new_balance = Account::balance_sub(account.balance(), transaction.total_value())?;
 
-        // Check vesting min cap.
-        let min_cap = vesting.min_cap(block_time);
-
-        if new_balance < min_cap {
+        // Check that the transaction doesn't withdraw more than the claimable amount.
+        if transaction.total_value() > vesting.claimable_at(block_time) {
             return Err(AccountError::InsufficientFunds {
                 balance: new_balance,
-                needed: min_cap,
+                needed: vesting.min_cap(block_time),
             });
         }
 
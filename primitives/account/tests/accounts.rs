@@ -4,16 +4,21 @@ use std::convert::TryFrom;
 use std::time::Instant;
 use tempfile::tempdir;
 
-use nimiq_account::{Accounts, Inherent, InherentType};
+use beserial::Serialize;
+use nimiq_account::{Account, AccountError, Accounts, BasicAccount, Inherent};
 use nimiq_account::{Receipt, Receipts};
 use nimiq_bls::KeyPair as BLSKeyPair;
-use nimiq_database::WriteTransaction;
 use nimiq_database::{mdbx::MdbxEnvironment, volatile::VolatileEnvironment};
+use nimiq_database::{ReadTransaction, WriteTransaction};
 use nimiq_genesis_builder::GenesisBuilder;
+use nimiq_hash::Hash;
 use nimiq_keys::{Address, KeyPair, PublicKey, SecureGenerate};
+use nimiq_primitives::account::AccountType;
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
+use nimiq_primitives::policy;
 use nimiq_test_log::test;
+use nimiq_test_utils::accounts_fuzzer::{AccountsFuzzer, Operation};
 use nimiq_test_utils::test_transaction::{
     generate_accounts, generate_transactions, TestTransaction,
 };
@@ -22,6 +27,35 @@ use nimiq_trie::key_nibbles::KeyNibbles;
 
 const VOLATILE_ENV: bool = true;
 
+#[test]
+fn it_returns_an_empty_basic_account_for_unknown_addresses() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts = Accounts::new(env.clone());
+
+    let key = KeyNibbles::from(&Address::from([1u8; Address::SIZE]));
+    let empty_account = Account::Basic(BasicAccount {
+        balance: Coin::ZERO,
+    });
+
+    assert_eq!(accounts.get_or_default(&key, None), empty_account);
+    // The account isn't created.
+    assert_eq!(accounts.get(&key, None), None);
+
+    let reward = Inherent::reward(
+        Address::from([1u8; Address::SIZE]),
+        Coin::from_u64_unchecked(10000),
+    );
+    let mut txn = WriteTransaction::new(&env);
+    accounts.commit(&mut txn, &[], &[reward], 1, 1).unwrap();
+
+    assert_eq!(
+        accounts.get_or_default(&key, Some(&txn)),
+        Account::Basic(BasicAccount {
+            balance: Coin::from_u64_unchecked(10000)
+        })
+    );
+}
+
 #[test]
 fn it_can_commit_and_revert_a_block_body() {
     let env = VolatileEnvironment::new(10).unwrap();
@@ -32,18 +66,9 @@ fn it_can_commit_and_revert_a_block_body() {
 
     let address_recipient = Address::from([2u8; Address::SIZE]);
 
-    let reward = Inherent {
-        ty: InherentType::Reward,
-        target: address_validator.clone(),
-        value: Coin::from_u64_unchecked(10000),
-        data: vec![],
-    };
+    let reward = Inherent::reward(address_validator.clone(), Coin::from_u64_unchecked(10000));
 
-    let mut receipts = vec![Receipt::Inherent {
-        index: 0,
-        pre_transactions: false,
-        data: None,
-    }];
+    let mut receipts = vec![Receipt::inherent(0, false, None)];
 
     assert_eq!(
         accounts.get(&KeyNibbles::from(&address_validator), None),
@@ -80,23 +105,9 @@ fn it_can_commit_and_revert_a_block_body() {
 
     let transactions = vec![tx];
 
-    receipts.insert(
-        0,
-        Receipt::Transaction {
-            index: 0,
-            sender: false,
-            data: None,
-        },
-    );
+    receipts.insert(0, Receipt::transaction(0, false, None));
 
-    receipts.insert(
-        0,
-        Receipt::Transaction {
-            index: 0,
-            sender: true,
-            data: None,
-        },
-    );
+    receipts.insert(0, Receipt::transaction(0, true, None));
 
     assert_eq!(
         accounts.get(&KeyNibbles::from(&address_recipient), None),
@@ -160,6 +171,8 @@ fn it_can_commit_and_revert_a_block_body() {
     );
 
     assert_eq!(hash1, accounts.get_root(None));
+
+    assert_eq!(accounts.check_integrity(None), Ok(()));
 }
 
 #[test]
@@ -182,12 +195,7 @@ fn it_correctly_rewards_validators() {
         None
     );
 
-    let reward = Inherent {
-        ty: InherentType::Reward,
-        target: address_validator_1.clone(),
-        value: Coin::from_u64_unchecked(10000),
-        data: vec![],
-    };
+    let reward = Inherent::reward(address_validator_1.clone(), Coin::from_u64_unchecked(10000));
 
     let mut txn = WriteTransaction::new(&env);
 
@@ -236,12 +244,10 @@ fn it_correctly_rewards_validators() {
         None
     );
 
-    let reward = Inherent {
-        ty: InherentType::Reward,
-        target: address_validator_2.clone(),
-        value: Coin::from_u64_unchecked(10000) + fee1 + fee2,
-        data: vec![],
-    };
+    let reward = Inherent::reward(
+        address_validator_2.clone(),
+        Coin::from_u64_unchecked(10000) + fee1 + fee2,
+    );
 
     let mut txn = WriteTransaction::new(&env);
 
@@ -303,12 +309,7 @@ fn it_checks_for_sufficient_funds() {
         NetworkId::Main,
     );
 
-    let reward = Inherent {
-        ty: InherentType::Reward,
-        target: address_sender.clone(),
-        value: Coin::from_u64_unchecked(10000),
-        data: vec![],
-    };
+    let reward = Inherent::reward(address_sender.clone(), Coin::from_u64_unchecked(10000));
 
     let hash1 = accounts.get_root(None);
 
@@ -447,12 +448,7 @@ fn accounts_performance() {
     let recipient_balances = vec![0; num_txns];
     let mut genesis_builder = GenesisBuilder::default();
     let address_validator = Address::from([1u8; Address::SIZE]);
-    let reward = Inherent {
-        ty: InherentType::Reward,
-        target: address_validator.clone(),
-        value: Coin::from_u64_unchecked(10000),
-        data: vec![],
-    };
+    let reward = Inherent::reward(address_validator.clone(), Coin::from_u64_unchecked(10000));
     let rewards = vec![reward; num_txns];
 
     // Generate recipient accounts
@@ -504,6 +500,22 @@ fn accounts_performance() {
 
     println!("Done adding accounts to genesis {}", txns.len());
 
+    let mut txn = WriteTransaction::new(&env);
+    let start = Instant::now();
+    let result = accounts.commit_parallel(&mut txn, &txns[..], &rewards[..], 1, 1);
+    match result {
+        Ok(_) => assert!(true),
+        Err(err) => assert!(false, "Received {}", err),
+    };
+    let duration = start.elapsed();
+    println!(
+        "Time elapsed after parallel account commit: {} ms, Accounts per second {}",
+        duration.as_millis(),
+        num_txns as f64 / (duration.as_millis() as f64 / 1000_f64),
+    );
+    let parallel_root = accounts.get_root(Some(&txn));
+    txn.abort();
+
     let mut txn = WriteTransaction::new(&env);
     let start = Instant::now();
     let result = accounts.commit(&mut txn, &txns[..], &rewards[..], 1, 1);
@@ -517,6 +529,7 @@ fn accounts_performance() {
         duration.as_millis(),
         num_txns as f64 / (duration.as_millis() as f64 / 1000_f64),
     );
+    assert_eq!(accounts.get_root(Some(&txn)), parallel_root);
     let start = Instant::now();
     txn.commit();
     let duration = start.elapsed();
@@ -526,3 +539,819 @@ fn accounts_performance() {
         num_txns as f64 / (duration.as_millis() as f64 / 1000_f64),
     );
 }
+
+#[test]
+#[cfg(feature = "metrics")]
+fn it_records_commit_timings_per_account_type() {
+    use std::sync::Arc;
+
+    use nimiq_account::CommitTimings;
+
+    let env = VolatileEnvironment::new(10).unwrap();
+
+    let timings = Arc::new(CommitTimings::default());
+
+    let accounts = Accounts::new(env.clone()).with_commit_histogram(timings.clone());
+
+    let address_validator = Address::from([1u8; Address::SIZE]);
+
+    let address_recipient = Address::from([2u8; Address::SIZE]);
+
+    let reward = Inherent::reward(address_validator.clone(), Coin::from_u64_unchecked(10000));
+
+    let mut txn = WriteTransaction::new(&env);
+
+    assert!(accounts.commit(&mut txn, &[], &[reward], 1, 1).is_ok());
+
+    let tx = Transaction::new_basic(
+        address_validator,
+        address_recipient,
+        Coin::from_u64_unchecked(10),
+        Coin::ZERO,
+        1,
+        NetworkId::Main,
+    );
+
+    assert!(accounts.commit(&mut txn, &[tx], &[], 2, 2).is_ok());
+
+    txn.commit();
+
+    // One operation for the sender and one for the recipient.
+    assert_eq!(timings.count(AccountType::Basic), 2);
+    assert_eq!(timings.count(AccountType::Staking), 0);
+    assert_eq!(timings.count(AccountType::HTLC), 0);
+}
+
+#[test]
+#[cfg(feature = "metrics")]
+fn it_records_commit_timings_of_parallel_commits() {
+    use std::sync::Arc;
+
+    use nimiq_account::CommitTimings;
+
+    let env = VolatileEnvironment::new(10).unwrap();
+
+    let timings = Arc::new(CommitTimings::default());
+
+    let accounts = Accounts::new(env.clone()).with_commit_histogram(timings.clone());
+
+    let address_validator = Address::from([1u8; Address::SIZE]);
+
+    let address_recipient = Address::from([2u8; Address::SIZE]);
+
+    let reward = Inherent::reward(address_validator.clone(), Coin::from_u64_unchecked(10000));
+
+    let mut txn = WriteTransaction::new(&env);
+
+    assert!(accounts
+        .commit_parallel(&mut txn, &[], &[reward], 1, 1)
+        .is_ok());
+
+    let tx = Transaction::new_basic(
+        address_validator,
+        address_recipient,
+        Coin::from_u64_unchecked(10),
+        Coin::ZERO,
+        1,
+        NetworkId::Main,
+    );
+
+    assert!(accounts.commit_parallel(&mut txn, &[tx], &[], 2, 2).is_ok());
+
+    txn.commit();
+
+    // The basic transfer is applied on the thread pool, but is recorded like in `commit`.
+    assert_eq!(timings.count(AccountType::Basic), 2);
+}
+
+#[test]
+#[cfg(feature = "account-changes")]
+fn it_notifies_the_observer_of_account_changes() {
+    use std::sync::Arc;
+
+    use nimiq_account::{AccountChange, AccountChangeLog};
+
+    let env = VolatileEnvironment::new(10).unwrap();
+
+    let log = Arc::new(AccountChangeLog::default());
+
+    let accounts = Accounts::new(env.clone()).with_observer(log.clone());
+
+    let address_sender = Address::from([1u8; Address::SIZE]);
+
+    let address_recipient = Address::from([2u8; Address::SIZE]);
+
+    let reward = Inherent::reward(address_sender.clone(), Coin::from_u64_unchecked(10000));
+
+    let mut txn = WriteTransaction::new(&env);
+
+    let (_, events) = accounts
+        .commit_with_events(&mut txn, &[], &[reward], 1, 1)
+        .unwrap();
+
+    // The changes are only reported once they are delivered.
+    assert!(log.take().is_empty());
+    accounts.notify(events);
+
+    assert_eq!(
+        log.take(),
+        vec![(
+            1,
+            KeyNibbles::from(&address_sender),
+            AccountChange::Created {
+                balance: Coin::from_u64_unchecked(10000)
+            }
+        )]
+    );
+
+    let tx = Transaction::new_basic(
+        address_sender.clone(),
+        address_recipient.clone(),
+        Coin::from_u64_unchecked(10),
+        Coin::from_u64_unchecked(1),
+        1,
+        NetworkId::Main,
+    );
+
+    let (receipts, events) = accounts
+        .commit_with_events(&mut txn, &[tx.clone()], &[], 2, 2)
+        .unwrap();
+    accounts.notify(events);
+
+    let sender_updated = AccountChange::Updated {
+        before: Coin::from_u64_unchecked(10000),
+        after: Coin::from_u64_unchecked(9989),
+    };
+    let recipient_created = AccountChange::Created {
+        balance: Coin::from_u64_unchecked(10),
+    };
+    assert_eq!(
+        log.take(),
+        vec![
+            (2, KeyNibbles::from(&address_sender), sender_updated),
+            (2, KeyNibbles::from(&address_recipient), recipient_created),
+        ]
+    );
+
+    // Reverting emits the inverse changes.
+    let events = accounts
+        .revert_with_events(&mut txn, &[tx], &[], 2, 2, &receipts)
+        .unwrap();
+    accounts.notify(events);
+
+    let sender_reverted = AccountChange::Updated {
+        before: Coin::from_u64_unchecked(9989),
+        after: Coin::from_u64_unchecked(10000),
+    };
+    let recipient_removed = AccountChange::Removed {
+        balance: Coin::from_u64_unchecked(10),
+    };
+    assert_eq!(
+        log.take(),
+        vec![
+            (2, KeyNibbles::from(&address_sender), sender_reverted),
+            (2, KeyNibbles::from(&address_recipient), recipient_removed),
+        ]
+    );
+}
+
+#[test]
+#[cfg(feature = "account-changes")]
+fn it_does_not_notify_the_observer_of_dry_runs() {
+    use std::sync::Arc;
+
+    use nimiq_account::AccountChangeLog;
+
+    let env = VolatileEnvironment::new(10).unwrap();
+
+    let log = Arc::new(AccountChangeLog::default());
+
+    let accounts = Accounts::new(env.clone()).with_observer(log.clone());
+
+    let address = Address::from([1u8; Address::SIZE]);
+
+    let reward = Inherent::reward(address, Coin::from_u64_unchecked(10000));
+
+    assert!(accounts.get_root_with(&[], &[reward], 1, 1).is_ok());
+
+    assert!(log.take().is_empty());
+}
+
+#[test]
+fn balance_mul_fails_on_overflow() {
+    assert_eq!(
+        Account::balance_mul(Coin::from_u64_unchecked(2), 500),
+        Ok(Coin::from_u64_unchecked(1000))
+    );
+    assert_eq!(Account::balance_mul(Coin::MAX, 1), Ok(Coin::MAX));
+    assert_eq!(
+        Account::balance_mul(Coin::MAX, 2),
+        Err(AccountError::InvalidCoinValue)
+    );
+    assert_eq!(
+        Account::balance_mul(Coin::from_u64_unchecked(2), u64::MAX),
+        Err(AccountError::InvalidCoinValue)
+    );
+}
+
+#[test]
+fn it_rejects_expired_transactions() {
+    let env = VolatileEnvironment::new(10).unwrap();
+
+    let accounts = Accounts::new(env.clone());
+
+    let address_sender = Address::from([1u8; Address::SIZE]);
+
+    let address_recipient = Address::from([2u8; Address::SIZE]);
+
+    let reward = Inherent::reward(address_sender.clone(), Coin::from_u64_unchecked(10000));
+
+    let mut txn = WriteTransaction::new(&env);
+
+    assert!(accounts.commit(&mut txn, &[], &[reward], 1, 1).is_ok());
+
+    // A transaction with a short validity window, which expires at block 10_010.
+    let validity_start_height = 10_010 - policy::TRANSACTION_VALIDITY_WINDOW;
+    let tx = Transaction::new_basic(
+        address_sender,
+        address_recipient.clone(),
+        Coin::from_u64_unchecked(10),
+        Coin::ZERO,
+        validity_start_height,
+        NetworkId::Main,
+    );
+
+    assert_eq!(
+        accounts.commit(&mut txn, &[tx.clone()], &[], 10_010, 2),
+        Err(AccountError::Expired {
+            validity_start_height,
+            block_height: 10_010,
+        })
+    );
+    assert_eq!(
+        accounts.get(&KeyNibbles::from(&address_recipient), Some(&txn)),
+        None
+    );
+
+    assert!(accounts.commit(&mut txn, &[tx], &[], 10_009, 2).is_ok());
+}
+
+#[test]
+fn commit_returns_the_new_root() {
+    let env = VolatileEnvironment::new(10).unwrap();
+
+    let accounts = Accounts::new(env.clone());
+
+    let address_sender = Address::from([1u8; Address::SIZE]);
+
+    let address_recipient = Address::from([2u8; Address::SIZE]);
+
+    let reward = Inherent::reward(address_sender.clone(), Coin::from_u64_unchecked(10000));
+
+    let tx = Transaction::new_basic(
+        address_sender,
+        address_recipient,
+        Coin::from_u64_unchecked(10),
+        Coin::ZERO,
+        1,
+        NetworkId::Main,
+    );
+
+    let expected_root = accounts.get_root_with(&[tx.clone()], &[reward.clone()], 1, 1);
+
+    let mut txn = WriteTransaction::new(&env);
+    let (root, _) = accounts
+        .commit_with_root(&mut txn, &[tx], &[reward], 1, 1)
+        .unwrap();
+
+    assert_eq!(root, accounts.get_root(Some(&txn)));
+    assert_eq!(Ok(root), expected_root);
+    assert_ne!(root, accounts.get_root(None));
+}
+
+#[test]
+fn it_rejects_duplicate_transactions() {
+    let env = VolatileEnvironment::new(10).unwrap();
+
+    let accounts = Accounts::new(env.clone());
+
+    let address_sender = Address::from([1u8; Address::SIZE]);
+
+    let address_recipient = Address::from([2u8; Address::SIZE]);
+
+    let reward = Inherent::reward(address_sender.clone(), Coin::from_u64_unchecked(10000));
+
+    let mut txn = WriteTransaction::new(&env);
+
+    assert!(accounts.commit(&mut txn, &[], &[reward], 1, 1).is_ok());
+
+    let tx = Transaction::new_basic(
+        address_sender,
+        address_recipient.clone(),
+        Coin::from_u64_unchecked(10),
+        Coin::ZERO,
+        1,
+        NetworkId::Main,
+    );
+
+    assert_eq!(
+        accounts.commit(&mut txn, &[tx.clone(), tx.clone()], &[], 2, 2),
+        Err(AccountError::DuplicateTransaction { hash: tx.hash() })
+    );
+    assert_eq!(
+        accounts.get(&KeyNibbles::from(&address_recipient), Some(&txn)),
+        None
+    );
+
+    assert!(accounts.commit(&mut txn, &[tx], &[], 2, 2).is_ok());
+}
+
+#[test]
+fn prefixed_accounts_are_isolated() {
+    let env = VolatileEnvironment::new(10).unwrap();
+
+    let main_accounts = Accounts::new_with_prefix(env.clone(), "Main");
+    let test_accounts = Accounts::new_with_prefix(env.clone(), "Test");
+
+    let address = Address::from([1u8; Address::SIZE]);
+    let key = KeyNibbles::from(&address);
+
+    let reward = Inherent::reward(address, Coin::from_u64_unchecked(10000));
+
+    let mut txn = WriteTransaction::new(&env);
+    assert!(main_accounts.commit(&mut txn, &[], &[reward], 1, 1).is_ok());
+    txn.commit();
+
+    assert_eq!(
+        main_accounts.get(&key, None),
+        Some(Account::Basic(BasicAccount {
+            balance: Coin::from_u64_unchecked(10000)
+        }))
+    );
+    assert_eq!(test_accounts.get(&key, None), None);
+    assert_ne!(main_accounts.get_root(None), test_accounts.get_root(None));
+
+    // Reopening with the same prefix gives the same state.
+    let main_accounts = Accounts::new_with_prefix(env.clone(), "Main");
+    assert_eq!(main_accounts.size(None), 1);
+    assert_eq!(test_accounts.size(None), 0);
+}
+
+#[test]
+fn it_applies_contract_creations_after_the_recipients() {
+    let sender = Address::from([1u8; Address::SIZE]);
+    let transfer = |value| {
+        Transaction::new_basic(
+            sender.clone(),
+            Address::from([2u8; Address::SIZE]),
+            Coin::from_u64_unchecked(value),
+            Coin::ZERO,
+            1,
+            NetworkId::Main,
+        )
+    };
+    let creation = Transaction::new_contract_creation(
+        vec![],
+        sender.clone(),
+        AccountType::Basic,
+        AccountType::HTLC,
+        Coin::from_u64_unchecked(1),
+        Coin::ZERO,
+        1,
+        NetworkId::Main,
+    );
+
+    let transactions = vec![transfer(1), creation, transfer(2)];
+
+    assert_eq!(Accounts::recipient_order(&transactions), vec![0, 2, 1]);
+}
+
+#[test]
+fn it_can_commit_transactions_in_parallel() {
+    let address = |byte: u8| Address::from([byte; Address::SIZE]);
+    let reward =
+        |target: Address, value: u64| Inherent::reward(target, Coin::from_u64_unchecked(value));
+    let transfer = |sender: u8, recipient: u8, value: u64| {
+        Transaction::new_basic(
+            address(sender),
+            address(recipient),
+            Coin::from_u64_unchecked(value),
+            Coin::from_u64_unchecked(1),
+            1,
+            NetworkId::Main,
+        )
+    };
+
+    let rewards = vec![
+        reward(address(1), 10000),
+        reward(address(2), 10000),
+        reward(address(6), 101),
+    ];
+
+    // The first two transactions share a sender and address 6 is drained completely.
+    let txs = vec![
+        transfer(1, 3, 10),
+        transfer(1, 4, 20),
+        transfer(2, 5, 30),
+        transfer(6, 7, 100),
+    ];
+
+    let env_serial = VolatileEnvironment::new(10).unwrap();
+    let accounts_serial = Accounts::new(env_serial.clone());
+    let mut txn = WriteTransaction::new(&env_serial);
+    accounts_serial
+        .commit(&mut txn, &[], &rewards, 1, 1)
+        .unwrap();
+    let receipts_serial = accounts_serial.commit(&mut txn, &txs, &[], 2, 2).unwrap();
+    txn.commit();
+
+    let env_parallel = VolatileEnvironment::new(10).unwrap();
+    let accounts_parallel = Accounts::new(env_parallel.clone());
+    let mut txn = WriteTransaction::new(&env_parallel);
+    accounts_parallel
+        .commit_parallel(&mut txn, &[], &rewards, 1, 1)
+        .unwrap();
+    let receipts_parallel = accounts_parallel
+        .commit_parallel(&mut txn, &txs, &[], 2, 2)
+        .unwrap();
+    txn.commit();
+
+    assert_eq!(receipts_parallel, receipts_serial);
+    assert_eq!(
+        accounts_parallel.get_root(None),
+        accounts_serial.get_root(None)
+    );
+    assert_eq!(
+        accounts_parallel.get(&KeyNibbles::from(&address(6)), None),
+        None
+    );
+    assert_eq!(
+        accounts_parallel
+            .get(&KeyNibbles::from(&address(1)), None)
+            .unwrap()
+            .balance(),
+        Coin::from_u64_unchecked(10000 - 32)
+    );
+
+    // Insufficient funds are detected within a partition.
+    let mut txn = WriteTransaction::new(&env_parallel);
+    assert!(accounts_parallel
+        .commit_parallel(&mut txn, &[transfer(3, 8, 10)], &[], 3, 3)
+        .is_err());
+}
+
+#[test]
+fn it_can_export_and_import_a_snapshot() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let validator_address = Address::from([3u8; 20]);
+
+    let mut genesis_builder = GenesisBuilder::default();
+    genesis_builder
+        .with_basic_account(Address::from([1u8; 20]), Coin::from_u64_unchecked(100))
+        .with_basic_account(Address::from([2u8; 20]), Coin::from_u64_unchecked(200))
+        .with_genesis_validator(
+            validator_address.clone(),
+            PublicKey::from([0u8; 32]),
+            BLSKeyPair::generate(&mut rng).public_key,
+            Address::default(),
+        )
+        .with_genesis_staker(
+            Address::from([4u8; 20]),
+            validator_address,
+            Coin::from_u64_unchecked(50),
+        );
+    let genesis_info = genesis_builder.generate(env.clone()).unwrap();
+
+    let accounts = Accounts::new(env.clone());
+    let mut txn = WriteTransaction::new(&env);
+    accounts.init(&mut txn, genesis_info.accounts);
+    txn.commit();
+
+    let mut snapshot = Vec::new();
+    let size = accounts
+        .export_snapshot(&ReadTransaction::new(&env), &mut snapshot)
+        .unwrap();
+    assert_eq!(size, snapshot.len());
+
+    // The imported tree has the same root and accounts.
+    let imported_env = VolatileEnvironment::new(10).unwrap();
+    let imported = Accounts::import_snapshot(imported_env, &mut &snapshot[..]).unwrap();
+
+    assert_eq!(imported.get_root(None), accounts.get_root(None));
+    assert_eq!(imported.size(None), accounts.size(None));
+    assert_eq!(
+        imported.get(&KeyNibbles::from(&Address::from([2u8; 20])), None),
+        accounts.get(&KeyNibbles::from(&Address::from([2u8; 20])), None)
+    );
+
+    // A snapshot can't be imported into an environment that already has accounts.
+    assert!(matches!(
+        Accounts::import_snapshot(env, &mut &snapshot[..]),
+        Err(AccountError::SnapshotTargetNotEmpty)
+    ));
+
+    // A snapshot whose root doesn't match its accounts is rejected.
+    snapshot[0] ^= 0xff;
+    let corrupted_env = VolatileEnvironment::new(10).unwrap();
+    assert!(matches!(
+        Accounts::import_snapshot(corrupted_env, &mut &snapshot[..]),
+        Err(AccountError::InvalidSnapshot { .. })
+    ));
+}
+
+#[test]
+fn it_can_read_a_consistent_snapshot() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts = Accounts::new(env.clone());
+
+    let address_1 = Address::from([1u8; Address::SIZE]);
+    let address_2 = Address::from([2u8; Address::SIZE]);
+
+    let mut txn = WriteTransaction::new(&env);
+    accounts.init(
+        &mut txn,
+        vec![(
+            KeyNibbles::from(&address_1),
+            Account::Basic(BasicAccount {
+                balance: Coin::from_u64_unchecked(1000),
+            }),
+        )],
+    );
+    txn.commit();
+
+    let reader = accounts.read_only_handle();
+    let root = reader.get_root();
+    assert_eq!(root, accounts.get_root(None));
+
+    // The writer commits a new block.
+    let mut txn = WriteTransaction::new(&env);
+    accounts
+        .commit(
+            &mut txn,
+            &[],
+            &[Inherent::reward(
+                address_2.clone(),
+                Coin::from_u64_unchecked(500),
+            )],
+            1,
+            1,
+        )
+        .unwrap();
+    txn.commit();
+
+    // The reader still sees the state at the time it was created.
+    assert_ne!(accounts.get_root(None), root);
+    assert_eq!(reader.get_root(), root);
+    assert!(reader.get(&KeyNibbles::from(&address_2)).is_none());
+    assert_eq!(
+        reader
+            .iter_prefix(&KeyNibbles::root())
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>(),
+        vec![KeyNibbles::from(&address_1)]
+    );
+
+    // A new reader sees the new state.
+    let reader = accounts.read_only_handle();
+    assert_eq!(reader.get_root(), accounts.get_root(None));
+    assert_eq!(
+        reader.get(&KeyNibbles::from(&address_2)),
+        Some(Account::Basic(BasicAccount {
+            balance: Coin::from_u64_unchecked(500)
+        }))
+    );
+    assert_eq!(reader.iter_prefix(&KeyNibbles::root()).count(), 2);
+}
+
+#[test]
+fn it_proves_several_accounts_at_once() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts = Accounts::new(env.clone());
+
+    let addresses: Vec<Address> = (1..=4u8)
+        .map(|i| Address::from([i; Address::SIZE]))
+        .collect();
+    let rewards: Vec<Inherent> = addresses
+        .iter()
+        .map(|address| Inherent::reward(address.clone(), Coin::from_u64_unchecked(1000)))
+        .collect();
+
+    let mut txn = WriteTransaction::new(&env);
+    accounts.commit(&mut txn, &[], &rewards, 1, 1).unwrap();
+    txn.commit();
+
+    let root = accounts.get_root(None);
+    let account = Some(Account::Basic(BasicAccount {
+        balance: Coin::from_u64_unchecked(1000),
+    }));
+
+    // Prove three existing accounts and one missing account together.
+    let keys = vec![
+        KeyNibbles::from(&addresses[0]),
+        KeyNibbles::from(&addresses[2]),
+        KeyNibbles::from(&addresses[3]),
+        KeyNibbles::from(&Address::from([9u8; Address::SIZE])),
+    ];
+    let (proven, proof) = accounts.get_many_with_proof(&keys, None);
+
+    assert_eq!(
+        proven,
+        vec![account.clone(), account.clone(), account.clone(), None]
+    );
+    assert!(proof.verify(&root, &keys, &proven));
+
+    // The root node is shared, so it is only included once.
+    let txn = ReadTransaction::new(&env);
+    let separate_nodes: usize = keys[..3]
+        .iter()
+        .map(|key| {
+            accounts
+                .tree
+                .get_proof(&txn, vec![key])
+                .unwrap()
+                .nodes
+                .len()
+        })
+        .sum();
+    assert!(proof.proof.nodes.len() < separate_nodes);
+
+    // The proof fails for other accounts, other keys or another root.
+    let mut wrong_accounts = proven.clone();
+    wrong_accounts[3] = account.clone();
+    assert!(!proof.verify(&root, &keys, &wrong_accounts));
+    assert!(!proof.verify(&root, &keys[..3], &proven[..2]));
+    assert!(!proof.verify(
+        &root,
+        &[KeyNibbles::from(&addresses[1])],
+        &[account.clone()]
+    ));
+    assert!(!proof.verify(&":-E".hash(), &keys, &proven));
+}
+
+#[test]
+fn it_reverts_random_blocks_to_the_initial_root() {
+    let mut fuzzer = AccountsFuzzer::new(42, 8);
+    let counts = fuzzer.run(200);
+
+    // Every commit path was exercised at least once.
+    for operation in [
+        Operation::Transfer,
+        Operation::CreateVesting,
+        Operation::RedeemVesting,
+        Operation::CreateHtlc,
+        Operation::TimeoutHtlc,
+        Operation::CreateStaker,
+        Operation::Stake,
+        Operation::Unstake,
+        Operation::Reward,
+    ] {
+        assert!(
+            counts.get(&operation).copied().unwrap_or(0) > 0,
+            "{:?} was never committed",
+            operation
+        );
+    }
+}
+
+#[test]
+fn it_lists_the_accounts_changed_between_two_roots() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts = Accounts::new(env.clone());
+
+    let addresses: Vec<Address> = (1..=5u8)
+        .map(|i| Address::from([i; Address::SIZE]))
+        .collect();
+    let basic = |balance| {
+        Account::Basic(BasicAccount {
+            balance: Coin::from_u64_unchecked(balance),
+        })
+    };
+
+    let mut txn = WriteTransaction::new(&env);
+    accounts.init(
+        &mut txn,
+        addresses[..4]
+            .iter()
+            .map(|address| (KeyNibbles::from(address), basic(1000)))
+            .collect(),
+    );
+    txn.commit();
+
+    let txn_a = ReadTransaction::new(&env);
+    let root_a = accounts.get_root(Some(&txn_a));
+
+    // Change the first account, remove the second, change the third and add the fifth.
+    let transactions = vec![
+        Transaction::new_basic(
+            addresses[0].clone(),
+            addresses[4].clone(),
+            Coin::from_u64_unchecked(100),
+            Coin::ZERO,
+            1,
+            NetworkId::Main,
+        ),
+        Transaction::new_basic(
+            addresses[1].clone(),
+            addresses[2].clone(),
+            Coin::from_u64_unchecked(1000),
+            Coin::ZERO,
+            1,
+            NetworkId::Main,
+        ),
+    ];
+
+    let mut txn = WriteTransaction::new(&env);
+    accounts.commit(&mut txn, &transactions, &[], 1, 1).unwrap();
+    txn.commit();
+
+    let txn_b = ReadTransaction::new(&env);
+    let root_b = accounts.get_root(Some(&txn_b));
+
+    let key = |i: usize| KeyNibbles::from(&addresses[i]);
+    assert_eq!(
+        accounts.diff(&txn_a, &root_a, &txn_b, &root_b),
+        Ok(vec![
+            (key(0), Some(basic(1000)), Some(basic(900))),
+            (key(1), Some(basic(1000)), None),
+            (key(2), Some(basic(1000)), Some(basic(2000))),
+            (key(4), None, Some(basic(100))),
+        ])
+    );
+    assert_eq!(accounts.diff(&txn_b, &root_b, &txn_b, &root_b), Ok(vec![]));
+
+    // The old root isn't visible to a new transaction anymore.
+    assert_eq!(
+        accounts.diff(&txn_b, &root_a, &txn_b, &root_b),
+        Err(AccountError::RootNotAvailable { root: root_a })
+    );
+}
+
+#[test]
+fn it_returns_accounts_that_were_missing_before() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts = Accounts::new(env.clone());
+
+    let key = KeyNibbles::from(&Address::from([1u8; Address::SIZE]));
+    for _ in 0..100 {
+        assert_eq!(accounts.get(&key, None), None);
+    }
+
+    let mut txn = WriteTransaction::new(&env);
+    let reward = Inherent::reward(
+        Address::from([1u8; Address::SIZE]),
+        Coin::from_u64_unchecked(1000),
+    );
+    accounts.commit(&mut txn, &[], &[reward], 1, 1).unwrap();
+
+    let account = Account::Basic(BasicAccount {
+        balance: Coin::from_u64_unchecked(1000),
+    });
+    assert_eq!(accounts.get(&key, Some(&txn)), Some(account.clone()));
+    assert_eq!(accounts.get(&key, None), None);
+
+    txn.commit();
+    assert_eq!(accounts.get(&key, None), Some(account));
+}
+
+#[test]
+fn it_returns_the_balances_of_existing_and_missing_accounts() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts = Accounts::new(env.clone());
+
+    let address_1 = Address::from([1u8; Address::SIZE]);
+    let address_2 = Address::from([2u8; Address::SIZE]);
+    let address_3 = Address::from([3u8; Address::SIZE]);
+
+    let mut txn = WriteTransaction::new(&env);
+    let rewards = vec![
+        Inherent::reward(address_1.clone(), Coin::from_u64_unchecked(1000)),
+        Inherent::reward(address_3.clone(), Coin::from_u64_unchecked(3000)),
+    ];
+    accounts.commit(&mut txn, &[], &rewards, 1, 1).unwrap();
+    txn.commit();
+
+    let addresses = vec![address_3, address_2.clone(), address_1, address_2];
+    assert_eq!(
+        accounts.balances_of(&addresses, None),
+        vec![
+            Coin::from_u64_unchecked(3000),
+            Coin::ZERO,
+            Coin::from_u64_unchecked(1000),
+            Coin::ZERO,
+        ]
+    );
+    assert_eq!(accounts.balances_of(&[], None), vec![]);
+}
+
+#[test]
+fn accounts_are_stored_with_their_type_tag() {
+    let basic = Account::Basic(BasicAccount {
+        balance: Coin::from_u64_unchecked(1),
+    });
+    let validators_staker = Account::StakingValidatorsStaker(Address::from([1u8; Address::SIZE]));
+
+    assert_eq!(basic.serialize_to_vec()[0], 0);
+    assert_eq!(validators_staker.serialize_to_vec()[0], 5);
+}
@@ -614,6 +614,110 @@ fn it_can_apply_and_revert_valid_transaction() {
     );
 }
 
+#[test]
+#[allow(unused_must_use)]
+fn it_can_partially_redeem_and_revert() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts_tree = AccountsTrie::new(env.clone(), "AccountsTree");
+    let mut db_txn = WriteTransaction::new(&env);
+    let key = KeyNibbles::from(&[0u8; 20][..]);
+
+    let (start_contract, mut tx, pre_image, _, recipient_signature_proof) =
+        prepare_outgoing_transaction();
+
+    let mut proof =
+        Vec::with_capacity(3 + 2 * AnyHash::SIZE + recipient_signature_proof.serialized_size());
+    Serialize::serialize(&ProofType::RegularTransfer, &mut proof);
+    Serialize::serialize(&HashAlgorithm::Blake2b, &mut proof);
+    Serialize::serialize(&2u8, &mut proof);
+    Serialize::serialize(&start_contract.hash_root, &mut proof);
+    Serialize::serialize(&pre_image, &mut proof);
+    Serialize::serialize(&recipient_signature_proof, &mut proof);
+    tx.proof = proof;
+    tx.value = 300.try_into().unwrap();
+
+    accounts_tree.put(&mut db_txn, &key, Account::HTLC(start_contract.clone()));
+
+    // Redeem twice, the contract stays around until it is drained.
+    let receipt1 = HashedTimeLockedContract::commit_outgoing_transaction(
+        &accounts_tree,
+        &mut db_txn,
+        &tx,
+        1,
+        1,
+    )
+    .unwrap();
+    assert_eq!(receipt1, None);
+    assert_eq!(
+        accounts_tree.get(&db_txn, &key).unwrap(),
+        Account::HTLC(start_contract.change_balance(700.try_into().unwrap()))
+    );
+
+    let receipt2 = HashedTimeLockedContract::commit_outgoing_transaction(
+        &accounts_tree,
+        &mut db_txn,
+        &tx,
+        1,
+        1,
+    )
+    .unwrap();
+    assert_eq!(receipt2, None);
+    assert_eq!(
+        accounts_tree.get(&db_txn, &key).unwrap(),
+        Account::HTLC(start_contract.change_balance(400.try_into().unwrap()))
+    );
+
+    // Redeeming more than the remaining balance fails and leaves the contract untouched.
+    let mut excess_tx = tx.clone();
+    excess_tx.value = 500.try_into().unwrap();
+    assert_eq!(
+        HashedTimeLockedContract::commit_outgoing_transaction(
+            &accounts_tree,
+            &mut db_txn,
+            &excess_tx,
+            1,
+            1
+        ),
+        Err(AccountError::InsufficientFunds {
+            needed: 500.try_into().unwrap(),
+            balance: 400.try_into().unwrap()
+        })
+    );
+    assert_eq!(
+        accounts_tree.get(&db_txn, &key).unwrap(),
+        Account::HTLC(start_contract.change_balance(400.try_into().unwrap()))
+    );
+
+    // Reverting restores the balances in reverse order.
+    HashedTimeLockedContract::revert_outgoing_transaction(
+        &accounts_tree,
+        &mut db_txn,
+        &tx,
+        1,
+        1,
+        receipt2.as_ref(),
+    )
+    .unwrap();
+    assert_eq!(
+        accounts_tree.get(&db_txn, &key).unwrap(),
+        Account::HTLC(start_contract.change_balance(700.try_into().unwrap()))
+    );
+
+    HashedTimeLockedContract::revert_outgoing_transaction(
+        &accounts_tree,
+        &mut db_txn,
+        &tx,
+        1,
+        1,
+        receipt1.as_ref(),
+    )
+    .unwrap();
+    assert_eq!(
+        accounts_tree.get(&db_txn, &key).unwrap(),
+        Account::HTLC(start_contract)
+    );
+}
+
 #[test]
 #[allow(unused_must_use)]
 fn it_refuses_invalid_transaction() {
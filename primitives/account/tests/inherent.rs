@@ -0,0 +1,53 @@
+use beserial::Deserialize;
+use nimiq_account::{Inherent, InherentType};
+use nimiq_keys::Address;
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::policy;
+use nimiq_primitives::slots::SlashedSlot;
+use nimiq_test_log::test;
+
+#[test]
+fn it_can_create_a_reward_inherent() {
+    let target = Address::from([1u8; Address::SIZE]);
+    let inherent = Inherent::reward(target.clone(), Coin::from_u64_unchecked(100));
+
+    assert_eq!(inherent.ty, InherentType::Reward);
+    assert_eq!(inherent.target, target);
+    assert_eq!(inherent.value, Coin::from_u64_unchecked(100));
+    assert!(inherent.data.is_empty());
+    assert!(!inherent.is_pre_transactions());
+}
+
+#[test]
+fn it_can_create_a_slash_inherent() {
+    let slot = SlashedSlot {
+        slot: 7,
+        validator_address: Address::from([2u8; Address::SIZE]),
+        event_block: 42,
+    };
+    let inherent = Inherent::slash(&slot);
+
+    assert_eq!(inherent.ty, InherentType::Slash);
+    assert_eq!(inherent.target, policy::STAKING_CONTRACT_ADDRESS);
+    assert_eq!(inherent.value, Coin::ZERO);
+    assert_eq!(
+        SlashedSlot::deserialize_from_vec(&inherent.data).unwrap(),
+        slot
+    );
+    assert!(inherent.is_pre_transactions());
+}
+
+#[test]
+fn it_can_create_finalize_inherents() {
+    let inherent = Inherent::finalize_batch();
+    assert_eq!(inherent.ty, InherentType::FinalizeBatch);
+    assert_eq!(inherent.target, policy::STAKING_CONTRACT_ADDRESS);
+    assert_eq!(inherent.value, Coin::ZERO);
+    assert!(inherent.data.is_empty());
+
+    let inherent = Inherent::finalize_epoch();
+    assert_eq!(inherent.ty, InherentType::FinalizeEpoch);
+    assert_eq!(inherent.target, policy::STAKING_CONTRACT_ADDRESS);
+    assert_eq!(inherent.value, Coin::ZERO);
+    assert!(inherent.data.is_empty());
+}
@@ -0,0 +1,55 @@
+use beserial::{Deserialize, Serialize};
+use nimiq_account::{Receipt, ReceiptType};
+use nimiq_test_log::test;
+
+#[test]
+fn it_can_create_a_sender_receipt() {
+    let receipt = Receipt::transaction(3, true, Some(vec![1, 2, 3]));
+
+    assert_eq!(receipt.receipt_type(), ReceiptType::Transaction);
+    assert_eq!(receipt.index(), 3);
+    assert!(receipt.is_sender());
+    assert!(!receipt.is_pre_transactions());
+    assert_eq!(receipt.data(), Some(&vec![1, 2, 3]));
+}
+
+#[test]
+fn it_can_create_a_recipient_receipt() {
+    let receipt = Receipt::transaction(5, false, None);
+
+    assert_eq!(receipt.receipt_type(), ReceiptType::Transaction);
+    assert_eq!(receipt.index(), 5);
+    assert!(!receipt.is_sender());
+    assert!(!receipt.is_pre_transactions());
+    assert_eq!(receipt.data(), None);
+}
+
+#[test]
+fn it_can_create_inherent_receipts() {
+    let receipt = Receipt::inherent(1, true, Some(vec![42]));
+
+    assert_eq!(receipt.receipt_type(), ReceiptType::Inherent);
+    assert_eq!(receipt.index(), 1);
+    assert!(!receipt.is_sender());
+    assert!(receipt.is_pre_transactions());
+    assert_eq!(receipt.data(), Some(&vec![42]));
+
+    let receipt = Receipt::inherent(2, false, None);
+
+    assert_eq!(receipt.index(), 2);
+    assert!(!receipt.is_pre_transactions());
+    assert_eq!(receipt.data(), None);
+}
+
+#[test]
+fn it_can_serialize_receipts() {
+    for receipt in [
+        Receipt::transaction(7, true, Some(vec![1, 2])),
+        Receipt::transaction(8, false, None),
+        Receipt::inherent(9, true, None),
+        Receipt::inherent(10, false, Some(vec![3])),
+    ] {
+        let bytes = receipt.serialize_to_vec();
+        assert_eq!(Receipt::deserialize_from_vec(&bytes).unwrap(), receipt);
+    }
+}
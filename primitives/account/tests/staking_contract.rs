@@ -1,14 +1,18 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryInto;
+#[cfg(any(feature = "staking-events", feature = "account-changes"))]
+use std::sync::Arc;
 
 use beserial::{Deserialize, Serialize};
 use nimiq_account::*;
+use nimiq_block::{MacroHeader, TendermintProof};
 use nimiq_bls::KeyPair as BlsKeyPair;
 use nimiq_bls::SecretKey as BlsSecretKey;
 use nimiq_bls::{CompressedPublicKey as BlsPublicKey, CompressedPublicKey};
 use nimiq_collections::BitSet;
 use nimiq_database::volatile::VolatileEnvironment;
 use nimiq_database::WriteTransaction;
+use nimiq_genesis_builder::GenesisBuilder;
 use nimiq_hash::Blake2bHash;
 use nimiq_keys::{Address, KeyPair, PrivateKey, PublicKey};
 use nimiq_primitives::account::AccountType;
@@ -20,11 +24,15 @@ use nimiq_primitives::policy::{
 };
 use nimiq_primitives::slots::SlashedSlot;
 use nimiq_test_log::test;
+use nimiq_test_utils::blockchain::sign_macro_block;
 use nimiq_transaction::account::staking_contract::{
     IncomingStakingTransactionData, OutgoingStakingTransactionProof,
 };
 use nimiq_transaction::{SignatureProof, Transaction};
+#[cfg(any(feature = "staking-events", feature = "account-changes"))]
+use nimiq_trie::key_nibbles::KeyNibbles;
 use nimiq_utils::key_rng::SecureGenerate;
+use nimiq_vrf::VrfSeed;
 
 const CONTRACT_1: &str = "00000000000000000000000000000000000000000000";
 const CONTRACT_2: &str =
@@ -113,7 +121,7 @@ fn it_can_de_serialize_a_staking_contract() {
     let size_1_out = contract_1.serialize(&mut bytes_1_out).unwrap();
 
     assert_eq!(size_1_out, contract_1.serialized_size());
-    assert_eq!(hex::encode(bytes_1_out), CONTRACT_1);
+    assert_eq!(hex::encode(bytes_1_out), format!("01{}", CONTRACT_1));
 
     let bytes_2: Vec<u8> = hex::decode(CONTRACT_2).unwrap();
     let contract_2: StakingContract = Deserialize::deserialize(&mut &bytes_2[..]).unwrap();
@@ -130,7 +138,26 @@ fn it_can_de_serialize_a_staking_contract() {
     let size_2_out = contract_2.serialize(&mut bytes_2_out).unwrap();
 
     assert_eq!(size_2_out, contract_2.serialized_size());
-    assert_eq!(hex::encode(bytes_2_out), CONTRACT_2);
+    assert_eq!(hex::encode(bytes_2_out), format!("01{}", CONTRACT_2));
+}
+
+#[test]
+fn it_upgrades_a_legacy_staking_contract() {
+    // `CONTRACT_2` is serialized in the legacy format without a version byte.
+    let legacy_bytes: Vec<u8> = hex::decode(CONTRACT_2).unwrap();
+    let legacy: StakingContract = Deserialize::deserialize(&mut &legacy_bytes[..]).unwrap();
+
+    let bytes = legacy.serialize_to_vec();
+    assert_eq!(bytes[0], StakingContract::VERSION);
+
+    let upgraded: StakingContract = Deserialize::deserialize(&mut &bytes[..]).unwrap();
+    assert_eq!(upgraded, legacy);
+    assert_eq!(upgraded.serialize_to_vec(), bytes);
+
+    // Unknown versions are rejected.
+    let mut unknown = bytes;
+    unknown[0] = StakingContract::VERSION + 1;
+    assert!(StakingContract::deserialize_from_vec(&unknown).is_err());
 }
 
 #[test]
@@ -170,6 +197,29 @@ fn can_get_it() {
     assert_eq!(staker.balance, Coin::from_u64_unchecked(150_000_000));
 }
 
+#[test]
+fn it_can_not_be_created_by_a_transaction() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts_tree = AccountsTrie::new(env.clone(), "AccountsTree");
+    let mut db_txn = WriteTransaction::new(&env);
+
+    let tx = Transaction::new_contract_creation(
+        vec![],
+        Address::from([1u8; 20]),
+        AccountType::Basic,
+        AccountType::Staking,
+        100.try_into().unwrap(),
+        0.try_into().unwrap(),
+        1,
+        NetworkId::Dummy,
+    );
+
+    assert_eq!(
+        Account::create(&accounts_tree, &mut db_txn, &tx, 1, 1),
+        Err(AccountError::StakingContractNotCreatable)
+    );
+}
+
 #[test]
 fn create_validator_works() {
     let env = VolatileEnvironment::new(10).unwrap();
@@ -402,6 +452,97 @@ fn update_validator_works() {
     );
 }
 
+#[test]
+fn update_validator_key_works() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts_tree = AccountsTrie::new(env.clone(), "AccountsTrie");
+    let mut db_txn = WriteTransaction::new(&env);
+
+    make_sample_contract(&accounts_tree, &mut db_txn, true);
+
+    let validator_address = Address::from_any_str(VALIDATOR_ADDRESS).unwrap();
+
+    let old_voting_key =
+        BlsPublicKey::deserialize_from_vec(&hex::decode(VALIDATOR_VOTING_KEY).unwrap()).unwrap();
+
+    let new_voting_keypair = BlsKeyPair::generate_default_csprng();
+
+    // Works in the valid case.
+    let receipt = StakingContract::update_validator_key(
+        &accounts_tree,
+        &mut db_txn,
+        &validator_address,
+        new_voting_keypair.public_key.compress(),
+    )
+    .unwrap();
+
+    assert!(!receipt.no_op);
+    assert_eq!(receipt.old_voting_key, old_voting_key);
+
+    let validator =
+        StakingContract::get_validator(&accounts_tree, &db_txn, &validator_address).unwrap();
+
+    assert_eq!(
+        validator.voting_key,
+        new_voting_keypair.public_key.compress()
+    );
+    assert_eq!(
+        validator.signing_key,
+        PublicKey::deserialize_from_vec(&hex::decode(VALIDATOR_SIGNING_KEY).unwrap()).unwrap()
+    );
+    assert_eq!(validator.reward_address, validator_address);
+
+    // Macro blocks signed with the new voting key verify, the ones signed with the old key don't.
+    let old_voting_keypair = bls_key_pair(VALIDATOR_VOTING_SECRET_KEY);
+
+    let validators =
+        StakingContract::select_validators(&accounts_tree, &db_txn, &VrfSeed::default());
+
+    let block = sign_macro_block(&new_voting_keypair, MacroHeader::default(), None);
+    assert!(TendermintProof::verify(&block, &validators));
+
+    let block = sign_macro_block(&old_voting_keypair, MacroHeader::default(), None);
+    assert!(!TendermintProof::verify(&block, &validators));
+
+    // Doesn't work when the validator doesn't exist.
+    let fake_address = Address::from([1u8; 20]);
+
+    assert_eq!(
+        StakingContract::update_validator_key(
+            &accounts_tree,
+            &mut db_txn,
+            &fake_address,
+            new_voting_keypair.public_key.compress(),
+        ),
+        Err(AccountError::NonExistentAddress {
+            address: fake_address
+        })
+    );
+
+    // Can revert the rotation.
+    StakingContract::revert_update_validator_key(
+        &accounts_tree,
+        &mut db_txn,
+        &validator_address,
+        receipt,
+    )
+    .unwrap();
+
+    let validator =
+        StakingContract::get_validator(&accounts_tree, &db_txn, &validator_address).unwrap();
+
+    assert_eq!(validator.voting_key, old_voting_key);
+
+    let validators =
+        StakingContract::select_validators(&accounts_tree, &db_txn, &VrfSeed::default());
+
+    let block = sign_macro_block(&old_voting_keypair, MacroHeader::default(), None);
+    assert!(TendermintProof::verify(&block, &validators));
+
+    let block = sign_macro_block(&new_voting_keypair, MacroHeader::default(), None);
+    assert!(!TendermintProof::verify(&block, &validators));
+}
+
 #[test]
 fn inactivate_validator_works() {
     let env = VolatileEnvironment::new(10).unwrap();
@@ -1057,6 +1198,7 @@ fn create_staker_works() {
         StakingContract::commit_incoming_transaction(&accounts_tree, &mut db_txn, &tx, 2, 0),
         Ok(None)
     );
+    StakingContract::assert_invariants(&accounts_tree, &db_txn);
 
     let staker = StakingContract::get_staker(&accounts_tree, &db_txn, &staker_address).unwrap();
 
@@ -1106,6 +1248,7 @@ fn create_staker_works() {
         StakingContract::revert_incoming_transaction(&accounts_tree, &mut db_txn, &tx, 2, 0, None),
         Ok(())
     );
+    StakingContract::assert_invariants(&accounts_tree, &db_txn);
 
     assert_eq!(
         StakingContract::get_staker(&accounts_tree, &db_txn, &staker_address),
@@ -1142,6 +1285,320 @@ fn create_staker_works() {
     );
 }
 
+#[test]
+fn stakers_of_lists_all_delegating_stakers() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts_tree = AccountsTrie::new(env.clone(), "AccountsTrie");
+    let mut db_txn = WriteTransaction::new(&env);
+
+    make_sample_contract(&accounts_tree, &mut db_txn, false);
+
+    let validator_address = Address::from_any_str(VALIDATOR_ADDRESS).unwrap();
+
+    assert_eq!(
+        StakingContract::stakers_of(&accounts_tree, &db_txn, &validator_address),
+        vec![]
+    );
+
+    let mut expected = vec![];
+    for i in 1..=3u8 {
+        let staker_address = Address::from([i; 20]);
+        let stake = Coin::from_u64_unchecked(i as u64 * 100_000_000);
+        StakingContract::create_staker(
+            &accounts_tree,
+            &mut db_txn,
+            &staker_address,
+            stake,
+            Some(validator_address.clone()),
+        )
+        .unwrap();
+        expected.push((staker_address, stake));
+    }
+
+    // Stakers that aren't delegating don't show up.
+    StakingContract::create_staker(
+        &accounts_tree,
+        &mut db_txn,
+        &Address::from([4u8; 20]),
+        Coin::from_u64_unchecked(400_000_000),
+        None,
+    )
+    .unwrap();
+    StakingContract::assert_invariants(&accounts_tree, &db_txn);
+
+    assert_eq!(
+        StakingContract::stakers_of(&accounts_tree, &db_txn, &validator_address),
+        expected
+    );
+
+    // Unknown validators don't have any stakers.
+    assert_eq!(
+        StakingContract::stakers_of(&accounts_tree, &db_txn, &Address::from([5u8; 20])),
+        vec![]
+    );
+}
+
+#[test]
+fn validators_can_be_found_by_reward_address() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts_tree = AccountsTrie::new(env.clone(), "AccountsTrie");
+    let mut db_txn = WriteTransaction::new(&env);
+
+    // The sample validator pays its rewards to its own address and has a staker.
+    make_sample_contract(&accounts_tree, &mut db_txn, true);
+    let sample_address = Address::from_any_str(VALIDATOR_ADDRESS).unwrap();
+
+    let signing_key =
+        PublicKey::deserialize_from_vec(&hex::decode(VALIDATOR_SIGNING_KEY).unwrap()).unwrap();
+    let voting_key =
+        BlsPublicKey::deserialize_from_vec(&hex::decode(VALIDATOR_VOTING_KEY).unwrap()).unwrap();
+
+    let pool_address = Address::from([10u8; 20]);
+    for (validator_address, reward_address) in [
+        (Address::from([3u8; 20]), pool_address.clone()),
+        (Address::from([1u8; 20]), pool_address.clone()),
+        (Address::from([2u8; 20]), Address::from([11u8; 20])),
+    ] {
+        StakingContract::create_validator(
+            &accounts_tree,
+            &mut db_txn,
+            &validator_address,
+            signing_key,
+            voting_key.clone(),
+            reward_address,
+            None,
+        )
+        .unwrap();
+    }
+
+    assert_eq!(
+        StakingContract::validators_by_reward_address(&accounts_tree, &db_txn, &pool_address),
+        vec![Address::from([1u8; 20]), Address::from([3u8; 20])]
+    );
+    assert_eq!(
+        StakingContract::validators_by_reward_address(
+            &accounts_tree,
+            &db_txn,
+            &Address::from([11u8; 20])
+        ),
+        vec![Address::from([2u8; 20])]
+    );
+    assert_eq!(
+        StakingContract::validators_by_reward_address(&accounts_tree, &db_txn, &sample_address),
+        vec![sample_address.clone()]
+    );
+    assert_eq!(
+        StakingContract::validators_by_reward_address(
+            &accounts_tree,
+            &db_txn,
+            &Address::from([12u8; 20])
+        ),
+        vec![]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Staking contract balance doesn't match")]
+fn inconsistent_staking_contract_fails_invariants() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts_tree = AccountsTrie::new(env.clone(), "AccountsTrie");
+    let mut db_txn = WriteTransaction::new(&env);
+
+    make_sample_contract(&accounts_tree, &mut db_txn, true);
+    StakingContract::assert_invariants(&accounts_tree, &db_txn);
+
+    // Let the contract balance drift away from the stake it tracks.
+    let mut staking_contract = StakingContract::get_staking_contract(&accounts_tree, &db_txn);
+    staking_contract.balance += Coin::from_u64_unchecked(1);
+    accounts_tree.put(
+        &mut db_txn,
+        &StakingContract::get_key_staking_contract(),
+        Account::Staking(staking_contract),
+    );
+
+    StakingContract::assert_invariants(&accounts_tree, &db_txn);
+}
+
+#[cfg(feature = "staking-events")]
+#[test]
+fn create_staker_emits_an_event() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let log = Arc::new(StakingEventLog::default());
+    let accounts = Accounts::new(env.clone()).with_staking_event_sink(log.clone());
+    let mut db_txn = WriteTransaction::new(&env);
+
+    make_sample_contract(&accounts.tree, &mut db_txn, false);
+
+    let staker_address = Address::from_any_str(STAKER_ADDRESS).unwrap();
+    let validator_address = Address::from_any_str(VALIDATOR_ADDRESS).unwrap();
+
+    accounts.tree.put(
+        &mut db_txn,
+        &KeyNibbles::from(&staker_address),
+        Account::Basic(BasicAccount {
+            balance: Coin::from_u64_unchecked(1_000_000_000),
+        }),
+    );
+
+    let tx = make_signed_incoming_transaction(
+        IncomingStakingTransactionData::CreateStaker {
+            delegation: Some(validator_address.clone()),
+            proof: SignatureProof::default(),
+        },
+        150_000_000,
+        &ed25519_key_pair(STAKER_PRIVATE_KEY),
+    );
+
+    let (receipts, events) = accounts
+        .commit_with_events(&mut db_txn, &[tx.clone()], &[], 2, 0)
+        .unwrap();
+
+    // The event is only emitted once it is delivered.
+    assert!(log.take().is_empty());
+    accounts.notify(events);
+
+    let event = StakingEvent::StakerCreated {
+        staker_address,
+        delegation: Some(validator_address),
+        value: Coin::from_u64_unchecked(150_000_000),
+    };
+    assert_eq!(log.take(), vec![(2, event.clone())]);
+
+    // Reverting the transaction emits the reverted event.
+    let events = accounts
+        .revert_with_events(&mut db_txn, &[tx], &[], 2, 0, &receipts)
+        .unwrap();
+    accounts.notify(events);
+
+    assert_eq!(
+        log.take(),
+        vec![(2, StakingEvent::Reverted(Box::new(event)))]
+    );
+}
+
+#[cfg(feature = "staking-events")]
+#[test]
+fn dry_runs_emit_no_events() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let log = Arc::new(StakingEventLog::default());
+    let accounts = Accounts::new(env.clone()).with_staking_event_sink(log.clone());
+    let mut db_txn = WriteTransaction::new(&env);
+
+    make_sample_contract(&accounts.tree, &mut db_txn, false);
+
+    let staker_address = Address::from_any_str(STAKER_ADDRESS).unwrap();
+    let validator_address = Address::from_any_str(VALIDATOR_ADDRESS).unwrap();
+
+    accounts.tree.put(
+        &mut db_txn,
+        &KeyNibbles::from(&staker_address),
+        Account::Basic(BasicAccount {
+            balance: Coin::from_u64_unchecked(1_000_000_000),
+        }),
+    );
+    db_txn.commit();
+
+    let tx = make_signed_incoming_transaction(
+        IncomingStakingTransactionData::CreateStaker {
+            delegation: Some(validator_address),
+            proof: SignatureProof::default(),
+        },
+        150_000_000,
+        &ed25519_key_pair(STAKER_PRIVATE_KEY),
+    );
+
+    assert!(accounts.get_root_with(&[tx], &[], 2, 0).is_ok());
+    assert!(log.take().is_empty());
+}
+
+#[cfg(feature = "staking-events")]
+#[test]
+fn unstake_emits_the_removed_value() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let log = Arc::new(StakingEventLog::default());
+    let accounts = Accounts::new(env.clone()).with_staking_event_sink(log.clone());
+    let mut db_txn = WriteTransaction::new(&env);
+
+    make_sample_contract(&accounts.tree, &mut db_txn, true);
+
+    let tx = make_unstake_transaction(100_000_000);
+
+    let (_, events) = accounts
+        .commit_with_events(&mut db_txn, &[tx], &[], 2, 0)
+        .unwrap();
+    accounts.notify(events);
+
+    assert_eq!(
+        log.take(),
+        vec![(
+            2,
+            StakingEvent::StakeRemoved {
+                staker_address: Address::from_any_str(STAKER_ADDRESS).unwrap(),
+                value: Coin::from_u64_unchecked(99_999_900),
+                fee: Coin::from_u64_unchecked(100),
+            }
+        )]
+    );
+}
+
+#[cfg(feature = "account-changes")]
+#[test]
+fn unstake_notifies_the_observer_of_the_staking_entries() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let log = Arc::new(AccountChangeLog::default());
+    let accounts = Accounts::new(env.clone()).with_observer(log.clone());
+    let mut db_txn = WriteTransaction::new(&env);
+
+    make_sample_contract(&accounts.tree, &mut db_txn, true);
+
+    let staker_address = Address::from_any_str(STAKER_ADDRESS).unwrap();
+    let validator_address = Address::from_any_str(VALIDATOR_ADDRESS).unwrap();
+
+    let tx = make_unstake_transaction(100_000_000);
+
+    let (_, events) = accounts
+        .commit_with_events(&mut db_txn, &[tx], &[], 2, 0)
+        .unwrap();
+    accounts.notify(events);
+
+    let changes = log.take();
+    assert_eq!(changes.len(), 4);
+
+    let staked_before = Coin::from_u64_unchecked(VALIDATOR_DEPOSIT + 150_000_000);
+    let staked_after = Coin::from_u64_unchecked(VALIDATOR_DEPOSIT + 50_000_000);
+    for change in [
+        (
+            StakingContract::get_key_staking_contract(),
+            AccountChange::Updated {
+                before: staked_before,
+                after: staked_after,
+            },
+        ),
+        (
+            StakingContract::get_key_validator(&validator_address),
+            AccountChange::Updated {
+                before: staked_before,
+                after: staked_after,
+            },
+        ),
+        (
+            StakingContract::get_key_staker(&staker_address),
+            AccountChange::Updated {
+                before: Coin::from_u64_unchecked(150_000_000),
+                after: Coin::from_u64_unchecked(50_000_000),
+            },
+        ),
+        (
+            KeyNibbles::from(&staker_address),
+            AccountChange::Created {
+                balance: Coin::from_u64_unchecked(99_999_900),
+            },
+        ),
+    ] {
+        assert!(changes.contains(&(2, change.0, change.1)));
+    }
+}
+
 #[test]
 fn stake_works() {
     let env = VolatileEnvironment::new(10).unwrap();
@@ -1692,12 +2149,7 @@ fn slash_inherents_work() {
         event_block: 1,
     };
 
-    let inherent = Inherent {
-        ty: InherentType::Slash,
-        target: Default::default(),
-        value: Coin::ZERO,
-        data: slot.serialize_to_vec(),
-    };
+    let inherent = Inherent::slash(&slot);
 
     // Works in current epoch, current batch case.
     let receipt = SlashReceipt {
@@ -1873,12 +2325,7 @@ fn finalize_batch_inherents_work() {
     );
 
     // Works in the valid case.
-    let inherent = Inherent {
-        ty: InherentType::FinalizeBatch,
-        target: Default::default(),
-        value: Coin::ZERO,
-        data: vec![],
-    };
+    let inherent = Inherent::finalize_batch();
 
     assert_eq!(
         StakingContract::commit_inherent(&accounts_tree, &mut db_txn, &inherent, 1, 0),
@@ -1949,12 +2396,7 @@ fn finalize_epoch_inherents_work() {
     );
 
     // Works in the valid case.
-    let inherent = Inherent {
-        ty: InherentType::FinalizeEpoch,
-        target: Default::default(),
-        value: Coin::ZERO,
-        data: vec![],
-    };
+    let inherent = Inherent::finalize_epoch();
 
     assert_eq!(
         StakingContract::commit_inherent(&accounts_tree, &mut db_txn, &inherent, 1, 0),
@@ -1995,6 +2437,76 @@ fn finalize_epoch_inherents_work() {
     );
 }
 
+#[test]
+fn genesis_staking_contract_is_canonical() {
+    let validators: Vec<(Address, PublicKey, BlsKeyPair)> = (1..=3u8)
+        .map(|i| {
+            (
+                Address::from([i; 20]),
+                PublicKey::from([i; 32]),
+                BlsKeyPair::generate_default_csprng(),
+            )
+        })
+        .collect();
+
+    let stakers: Vec<(Address, Address, Coin)> = (1..=3u8)
+        .map(|i| {
+            (
+                Address::from([10 + i; 20]),
+                validators[i as usize - 1].0.clone(),
+                Coin::from_u64_unchecked(i as u64 * 100),
+            )
+        })
+        .collect();
+
+    // Builds a genesis with the validators and stakers added in the given order and returns the
+    // serialized staking contract and the serialized accounts list.
+    let build = |order: &[usize]| {
+        let mut genesis_builder = GenesisBuilder::default();
+
+        for &i in order {
+            let (address, signing_key, voting_key) = &validators[i];
+            genesis_builder.with_genesis_validator(
+                address.clone(),
+                *signing_key,
+                voting_key.public_key,
+                address.clone(),
+            );
+        }
+
+        for &i in order {
+            let (address, delegation, balance) = &stakers[i];
+            genesis_builder.with_genesis_staker(address.clone(), delegation.clone(), *balance);
+        }
+
+        let env = VolatileEnvironment::new(10).unwrap();
+        let genesis_info = genesis_builder.generate(env).unwrap();
+
+        let staking_contract = genesis_info
+            .accounts
+            .iter()
+            .find_map(|(_, account)| match account {
+                Account::Staking(contract) => Some(contract.clone()),
+                _ => None,
+            })
+            .unwrap();
+
+        (
+            staking_contract.serialize_to_vec(),
+            AccountsList(genesis_info.accounts).serialize_to_vec(),
+        )
+    };
+
+    let (contract1, accounts1) = build(&[0, 1, 2]);
+    let (contract2, accounts2) = build(&[0, 1, 2]);
+    let (contract3, accounts3) = build(&[2, 0, 1]);
+
+    assert_eq!(contract1, contract2);
+    assert_eq!(contract1, contract3);
+    assert_eq!(accounts1, accounts2);
+    assert_eq!(accounts1, accounts3);
+}
+
 fn make_empty_contract(accounts_tree: &AccountsTrie, db_txn: &mut WriteTransaction) {
     StakingContract::create(accounts_tree, db_txn)
 }
@@ -33,6 +33,47 @@ fn create_serialized_contract() {
     assert_eq!(CONTRACT, hex::encode(bytes));
 }
 
+#[test]
+fn it_computes_the_claimable_amount() {
+    let contract = VestingContract {
+        balance: Coin::from_u64_unchecked(1000),
+        owner: Address::from([0u8; 20]),
+        start_time: 100,
+        time_step: 10,
+        step_amount: Coin::from_u64_unchecked(250),
+        total_amount: Coin::from_u64_unchecked(1000),
+    };
+
+    // Nothing is claimable before the start and during the first step.
+    assert_eq!(contract.claimable_at(0), Coin::ZERO);
+    assert_eq!(contract.claimable_at(99), Coin::ZERO);
+    assert_eq!(contract.claimable_at(100), Coin::ZERO);
+    assert_eq!(contract.claimable_at(109), Coin::ZERO);
+
+    // One step is vested after each time step.
+    assert_eq!(contract.claimable_at(110), Coin::from_u64_unchecked(250));
+    assert_eq!(contract.claimable_at(125), Coin::from_u64_unchecked(500));
+    assert_eq!(contract.claimable_at(139), Coin::from_u64_unchecked(750));
+
+    // Everything is claimable once fully vested.
+    assert_eq!(contract.claimable_at(140), Coin::from_u64_unchecked(1000));
+    assert_eq!(
+        contract.claimable_at(u64::MAX),
+        Coin::from_u64_unchecked(1000)
+    );
+
+    // Withdrawals reduce the claimable amount.
+    let contract = contract.change_balance(Coin::from_u64_unchecked(600));
+    assert_eq!(contract.claimable_at(110), Coin::ZERO);
+    assert_eq!(contract.claimable_at(125), Coin::from_u64_unchecked(100));
+    assert_eq!(contract.claimable_at(140), Coin::from_u64_unchecked(600));
+
+    // Funds above the total amount are claimable from the start.
+    let contract = contract.change_balance(Coin::from_u64_unchecked(1200));
+    assert_eq!(contract.claimable_at(100), Coin::from_u64_unchecked(200));
+    assert_eq!(contract.claimable_at(140), Coin::from_u64_unchecked(1200));
+}
+
 #[test]
 fn it_can_deserialize_a_vesting_contract() {
     let bytes: Vec<u8> = hex::decode(CONTRACT).unwrap();
@@ -15,7 +15,7 @@ use nimiq_vrf::VrfSeed;
 
 use crate::macro_block::{MacroBlock, MacroHeader};
 use crate::micro_block::{MicroBlock, MicroHeader};
-use crate::{MacroBody, MicroBody, MicroJustification, TendermintProof};
+use crate::{BlockError, MacroBody, MicroBody, MicroJustification, TendermintProof};
 
 /// Defines the type of the block, either Micro or Macro (which includes both checkpoint and
 /// election blocks).
@@ -199,6 +199,22 @@ impl Block {
         })
     }
 
+    /// Verifies that the body of the block matches the body root committed to in its header. This
+    /// can be used to check a body that arrived separately from its header (e.g. during
+    /// header-first sync) before applying it. Returns an error if the block has no body.
+    pub fn verify_body(&self) -> Result<(), BlockError> {
+        let body_hash: Blake2bHash = match self {
+            Block::Macro(ref block) => block.body.as_ref().ok_or(BlockError::MissingBody)?.hash(),
+            Block::Micro(ref block) => block.body.as_ref().ok_or(BlockError::MissingBody)?.hash(),
+        };
+
+        if &body_hash != self.body_root() {
+            return Err(BlockError::BodyHashMismatch);
+        }
+
+        Ok(())
+    }
+
     /// Returns a reference to the transactions of the block. If the block is a Macro block it just
     /// returns None, since Macro blocks don't contain any transactions.
     pub fn transactions(&self) -> Option<&Vec<Transaction>> {
@@ -12,6 +12,8 @@ use crate::MicroHeader;
 /// Struct representing a fork proof. A fork proof proves that a given validator created or
 /// continued a fork. For this it is enough to provide two different headers, with the same block
 /// number and view number, signed by the same validator.
+/// The headers are ordered by their hashes (see `ForkProof::new`), so that there is exactly one
+/// valid proof for each pair of headers.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ForkProof {
     /// Header number 1.
@@ -32,13 +34,50 @@ impl ForkProof {
     /// has the maximum size.
     pub const SIZE: usize = 2 * MicroHeader::MAX_SIZE + 2 * SchnorrSignature::SIZE + VrfSeed::SIZE;
 
+    /// Creates a fork proof from two headers and their justifications. The headers are put in
+    /// canonical order (ascending by hash), so the proof is the same regardless of the order in
+    /// which the headers were observed.
+    pub fn new(
+        header1: MicroHeader,
+        justification1: SchnorrSignature,
+        header2: MicroHeader,
+        justification2: SchnorrSignature,
+        prev_vrf_seed: VrfSeed,
+    ) -> Self {
+        if header1.hash::<Blake2bHash>() <= header2.hash::<Blake2bHash>() {
+            ForkProof {
+                header1,
+                header2,
+                justification1,
+                justification2,
+                prev_vrf_seed,
+            }
+        } else {
+            ForkProof {
+                header1: header2,
+                header2: header1,
+                justification1: justification2,
+                justification2: justification1,
+                prev_vrf_seed,
+            }
+        }
+    }
+
     /// Verify the validity of a fork proof.
     pub fn verify(&self, signing_key: &SchnorrPublicKey) -> Result<(), ForkProofError> {
+        let hash1 = self.header1.hash::<Blake2bHash>();
+        let hash2 = self.header2.hash::<Blake2bHash>();
+
         // Check that the headers are not equal.
-        if self.header1.hash::<Blake2bHash>() == self.header2.hash::<Blake2bHash>() {
+        if hash1 == hash2 {
             return Err(ForkProofError::SameHeader);
         }
 
+        // Check that the headers are in canonical order.
+        if hash1 > hash2 {
+            return Err(ForkProofError::NonCanonicalOrder);
+        }
+
         // Check that the headers have equal block numbers and view numbers as well as seeds.
         if self.header1.block_number != self.header2.block_number
             || self.header1.view_number != self.header2.view_number
@@ -53,8 +92,6 @@ impl ForkProof {
         }
 
         // Check that the justifications are valid.
-        let hash1 = self.header1.hash::<Blake2bHash>();
-        let hash2 = self.header2.hash::<Blake2bHash>();
         if !signing_key.verify(&self.justification1, hash1.as_slice())
             || !signing_key.verify(&self.justification2, hash2.as_slice())
         {
@@ -142,4 +179,5 @@ pub enum ForkProofError {
     SlotMismatch,
     InvalidJustification,
     SameHeader,
+    NonCanonicalOrder,
 }
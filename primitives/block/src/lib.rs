@@ -36,6 +36,17 @@ pub enum BlockError {
     #[error("Block size exceeded")]
     SizeExceeded,
 
+    #[error("Invalid block type")]
+    InvalidBlockType,
+    #[error("Invalid block number")]
+    InvalidBlockNumber,
+    #[error("Invalid parent hash")]
+    InvalidParentHash,
+    #[error("Invalid timestamp")]
+    InvalidTimestamp,
+    #[error("Unknown block proposer")]
+    UnknownProposer,
+
     #[error("Body hash mismatch")]
     BodyHashMismatch,
     #[error("Accounts hash mismatch")]
@@ -54,6 +65,8 @@ pub enum BlockError {
     DuplicateForkProof,
     #[error("Fork proofs incorrectly ordered")]
     ForkProofsNotOrdered,
+    #[error("Too many fork proofs")]
+    TooManyForkProofs,
 
     #[error("Duplicate transaction in block")]
     DuplicateTransaction,
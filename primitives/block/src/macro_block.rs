@@ -6,6 +6,7 @@ use thiserror::Error;
 use beserial::{Deserialize, Serialize};
 use nimiq_collections::bitset::BitSet;
 use nimiq_hash::{Blake2bHash, Blake2sHash, Hash, SerializeContent};
+use nimiq_keys::Address;
 use nimiq_nano_primitives::pk_tree_construct;
 use nimiq_primitives::policy;
 use nimiq_primitives::slots::Validators;
@@ -148,6 +149,39 @@ impl MacroBlock {
         self.header.block_number
     }
 
+    /// Returns the addresses of the validators that signed this macro block, given the validators
+    /// of the epoch the block belongs to. Validators that own several slots are only returned once.
+    /// The addresses are returned in slot order. If the block has no justification, it returns an
+    /// empty vector.
+    pub fn signers(&self, validators: &Validators) -> Vec<Address> {
+        let justification = match &self.justification {
+            None => return vec![],
+            Some(x) => x,
+        };
+
+        let mut signers = vec![];
+        let mut last_band = None;
+
+        // The signer bitmap is iterated in slot order and each validator owns a contiguous range
+        // of slots, so we only need to compare against the slot band of the previous signer.
+        for slot in justification.sig.signers.iter() {
+            let band = validators.get_band_from_slot(slot as u16);
+
+            if last_band != Some(band) {
+                signers.push(validators.get_validator_by_slot_band(band).address.clone());
+                last_band = Some(band);
+            }
+        }
+
+        signers
+    }
+
+    /// Verifies the justification of this macro block against the given validators, requiring at
+    /// least `threshold` votes. See `TendermintProof::verify_with_threshold`.
+    pub fn verify_with_threshold(&self, validators: &Validators, threshold: u16) -> bool {
+        TendermintProof::verify_with_threshold(self, validators, threshold)
+    }
+
     /// Returns the epoch number of this macro block.
     pub fn epoch_number(&self) -> u32 {
         policy::epoch_at(self.header.block_number)
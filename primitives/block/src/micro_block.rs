@@ -3,7 +3,7 @@ use std::{fmt, io};
 
 use beserial::{Deserialize, Serialize};
 use nimiq_database::{FromDatabaseValue, IntoDatabaseValue};
-use nimiq_hash::{Blake2bHash, Hash, SerializeContent};
+use nimiq_hash::{Blake2bHash, Blake2bHasher, Hash, Hasher, SerializeContent};
 use nimiq_hash_derive::SerializeContent;
 use nimiq_keys::Signature;
 use nimiq_primitives::policy;
@@ -142,6 +142,18 @@ impl fmt::Display for MicroHeader {
     }
 }
 
+impl MicroBody {
+    /// Returns the same hash as `hash::<Blake2bHash>()`. The body is serialized straight into the
+    /// hasher with `SerializeContent`, one fork proof and transaction at a time, so that the
+    /// serialization of the whole body is never held in memory at once.
+    pub fn hash_streaming(&self) -> Blake2bHash {
+        let mut hasher = Blake2bHasher::default();
+        self.serialize_content(&mut hasher)
+            .expect("Failed to hash the micro body");
+        hasher.finish()
+    }
+}
+
 impl Hash for MicroBody {}
 
 impl Debug for MicroBody {
@@ -1,9 +1,11 @@
 use log::error;
+use std::collections::HashMap;
 use std::io;
 
 use beserial::{Deserialize, Serialize};
 use nimiq_bls::AggregatePublicKey;
-use nimiq_hash::{Blake2sHash, Hash, SerializeContent};
+use nimiq_collections::BitSet;
+use nimiq_hash::{Blake2bHash, Blake2bHasher, Blake2sHash, Hash, Hasher, SerializeContent};
 use nimiq_hash_derive::SerializeContent;
 use nimiq_primitives::policy::TWO_F_PLUS_ONE;
 use nimiq_primitives::slots::Validators;
@@ -50,6 +52,54 @@ impl TendermintProof {
     /// Verifies the proof. This only checks that the proof is valid for this block, not that the
     /// block itself is valid.
     pub fn verify(block: &MacroBlock, current_validators: &Validators) -> bool {
+        Self::verify_with_threshold(block, current_validators, TWO_F_PLUS_ONE)
+    }
+
+    /// Verifies the proof like `verify`, but requires `threshold` votes instead of
+    /// `TWO_F_PLUS_ONE`. This is meant for testing alternative thresholds, consensus always uses
+    /// `verify`.
+    pub fn verify_with_threshold(
+        block: &MacroBlock,
+        current_validators: &Validators,
+        threshold: u16,
+    ) -> bool {
+        Self::verify_with_aggregate(block, threshold, |signers| {
+            Self::aggregate_public_key(current_validators, signers)
+        })
+    }
+
+    /// Verifies the proof like `verify_with_threshold`, but takes the aggregated public key of the
+    /// signers from the given cache. This saves the aggregation when verifying many blocks of the
+    /// same epoch.
+    pub fn verify_cached(
+        block: &MacroBlock,
+        current_validators: &Validators,
+        threshold: u16,
+        cache: &mut AggPkCache,
+    ) -> bool {
+        Self::verify_with_aggregate(block, threshold, |signers| {
+            cache.get_or_aggregate(current_validators, signers)
+        })
+    }
+
+    /// Aggregates the voting keys of all slots in `signers`.
+    pub fn aggregate_public_key(validators: &Validators, signers: &BitSet) -> AggregatePublicKey {
+        let mut agg_pk = AggregatePublicKey::new();
+
+        for (i, pk) in validators.voting_keys().iter().enumerate() {
+            if signers.contains(i as usize) {
+                agg_pk.aggregate(pk);
+            }
+        }
+
+        agg_pk
+    }
+
+    fn verify_with_aggregate<F: FnOnce(&BitSet) -> AggregatePublicKey>(
+        block: &MacroBlock,
+        threshold: u16,
+        aggregate: F,
+    ) -> bool {
         // If there's no justification then the proof is false evidently.
         let justification = match &block.justification {
             None => {
@@ -60,7 +110,7 @@ impl TendermintProof {
         };
 
         // Check if there are enough votes.
-        if justification.votes() < TWO_F_PLUS_ONE {
+        if justification.votes() < threshold {
             error!("Invalid justification - not enough votes!");
             return false;
         }
@@ -81,19 +131,56 @@ impl TendermintProof {
 
         // Get the public key for each SLOT and add them together to get the aggregated public key
         // (if they are part of the Multisignature Bitset).
-        let mut agg_pk = AggregatePublicKey::new();
-
-        for (i, pk) in current_validators.voting_keys().iter().enumerate() {
-            if justification.sig.signers.contains(i as usize) {
-                agg_pk.aggregate(pk);
-            }
-        }
+        let agg_pk = aggregate(&justification.sig.signers);
 
         // Verify the aggregated signature against our aggregated public key.
         agg_pk.verify(&message, &justification.sig.signature)
     }
 }
 
+/// A cache of the aggregated public keys of signer bitmaps, keyed by the hash of the bitmap. Since
+/// the aggregates depend on the validator set, the cache keeps the set its aggregates were computed
+/// with and is cleared whenever it is used with another set.
+#[derive(Debug, Default)]
+pub struct AggPkCache {
+    validators: Option<Validators>,
+    aggregates: HashMap<Blake2bHash, AggregatePublicKey>,
+}
+
+impl AggPkCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the aggregated public key of `signers` in `validators`. The aggregate is computed
+    /// only if it isn't cached yet for this validator set.
+    pub fn get_or_aggregate(
+        &mut self,
+        validators: &Validators,
+        signers: &BitSet,
+    ) -> AggregatePublicKey {
+        if self.validators.as_ref() != Some(validators) {
+            self.aggregates.clear();
+            self.validators = Some(validators.clone());
+        }
+
+        let key = Blake2bHasher::default().digest(&signers.serialize_to_vec());
+        *self
+            .aggregates
+            .entry(key)
+            .or_insert_with(|| TendermintProof::aggregate_public_key(validators, signers))
+    }
+
+    /// The number of cached aggregates.
+    pub fn len(&self) -> usize {
+        self.aggregates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aggregates.is_empty()
+    }
+}
+
 /// Internal representation of nimiq_tendermint::Step struct. It needs to be Serializable and must not contain Proposal
 /// thus the additional type.
 #[derive(Serialize, Deserialize, Debug, Clone, Ord, PartialOrd, PartialEq, Eq, Hash, Copy)]
@@ -6,7 +6,7 @@ use nimiq_hash::{Hash, SerializeContent};
 use nimiq_hash_derive::SerializeContent;
 use nimiq_primitives::policy::TWO_F_PLUS_ONE;
 use nimiq_primitives::slots::Validators;
-use nimiq_vrf::VrfEntropy;
+use nimiq_vrf::{VrfEntropy, VrfSeed};
 
 use crate::{Message, MultiSignature, SignedMessage, PREFIX_VIEW_CHANGE};
 
@@ -32,6 +32,25 @@ pub struct ViewChange {
     pub vrf_entropy: VrfEntropy,
 }
 
+/// Builds the view change to `new_view_number` for the block with the given number, on top of the
+/// block with the seed `prev_seed`. Both signing and verifying view changes build them here, so
+/// the signed message can't diverge from the verified one.
+///
+/// View changes are signed over `hash_with_prefix`, i.e. the Blake2s hash of `PREFIX_VIEW_CHANGE`
+/// followed by the serialized view change. The prefix separates them from all other signed
+/// messages, so a view change signature can't be reused as e.g. a block signature.
+pub fn view_change_message(
+    block_number: u32,
+    new_view_number: u32,
+    prev_seed: &VrfSeed,
+) -> ViewChange {
+    ViewChange {
+        block_number,
+        new_view_number,
+        vrf_entropy: prev_seed.entropy(),
+    }
+}
+
 impl Message for ViewChange {
     const PREFIX: u8 = PREFIX_VIEW_CHANGE;
 }
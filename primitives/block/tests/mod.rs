@@ -1,14 +1,21 @@
 use std::str::FromStr;
 
 use beserial::{Deserialize, Serialize};
-use nimiq_block::{IndividualSignature, MacroBlock, MacroBody, MacroHeader, MultiSignature};
-use nimiq_bls::{CompressedPublicKey, KeyPair};
+use nimiq_block::{
+    AggPkCache, Block, BlockError, ForkProof, IndividualSignature, MacroBlock, MacroBody,
+    MacroHeader, MicroBlock, MicroBody, MicroHeader, MultiSignature, TendermintProof,
+};
+use nimiq_bls::{AggregateSignature, CompressedPublicKey, KeyPair};
 use nimiq_collections::bitset::BitSet;
 use nimiq_handel::update::LevelUpdate;
-use nimiq_hash::{Blake2bHasher, Hasher};
-use nimiq_keys::{Address, PublicKey};
-use nimiq_primitives::slots::ValidatorsBuilder;
+use nimiq_hash::{Blake2bHash, Blake2bHasher, Hash, Hasher};
+use nimiq_keys::{Address, PublicKey, Signature};
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::networks::NetworkId;
+use nimiq_primitives::policy;
+use nimiq_primitives::slots::{Validator, Validators, ValidatorsBuilder};
 use nimiq_test_log::test;
+use nimiq_transaction::Transaction;
 use nimiq_vrf::VrfSeed;
 
 #[test]
@@ -111,14 +118,268 @@ fn it_can_convert_macro_block_into_slots() {
     assert_eq!(validator_slots, validators_from_macro);
 }
 
-fn create_multisig() -> MultiSignature {
+#[test]
+fn it_can_serialize_and_deserialize_macro_blocks() {
+    let mut signers = BitSet::new();
+    signers.insert(3);
+    signers.insert(511);
+
+    let mut lost_reward_set = BitSet::new();
+    lost_reward_set.insert(7);
+
+    // The pk tree root is a serialized MNT6 G1 point, which takes 95 bytes.
+    let macro_block = MacroBlock {
+        header: MacroHeader {
+            version: 1,
+            block_number: 42,
+            view_number: 3,
+            timestamp: 1_000,
+            parent_hash: Blake2bHasher::default().digest(&[1]),
+            parent_election_hash: Blake2bHasher::default().digest(&[2]),
+            seed: VrfSeed::default(),
+            extra_data: vec![1, 2, 3],
+            state_root: Blake2bHasher::default().digest(&[3]),
+            body_root: Blake2bHasher::default().digest(&[4]),
+            history_root: Blake2bHasher::default().digest(&[5]),
+        },
+        justification: Some(TendermintProof {
+            round: 1,
+            sig: MultiSignature::new(AggregateSignature::new(), signers),
+        }),
+        body: Some(MacroBody {
+            validators: None,
+            pk_tree_root: Some((0..95).collect()),
+            lost_reward_set,
+            disabled_set: BitSet::new(),
+        }),
+    };
+
+    let data = macro_block.serialize_to_vec();
+    assert_eq!(data.len(), macro_block.serialized_size());
+
+    let macro_block_2: MacroBlock = Deserialize::deserialize_from_vec(&data).unwrap();
+    assert_eq!(macro_block_2, macro_block);
+    assert_eq!(macro_block_2.hash(), macro_block.hash());
+}
+
+#[test]
+fn it_can_resolve_macro_block_signers() {
+    let validators = Validators::new(vec![
+        Validator::new(
+            Address::from([0u8; 20]),
+            CompressedPublicKey::default(),
+            PublicKey::from([0u8; 32]),
+            (0, 200),
+        ),
+        Validator::new(
+            Address::from([1u8; 20]),
+            CompressedPublicKey::default(),
+            PublicKey::from([1u8; 32]),
+            (200, 300),
+        ),
+        Validator::new(
+            Address::from([2u8; 20]),
+            CompressedPublicKey::default(),
+            PublicKey::from([2u8; 32]),
+            (300, 512),
+        ),
+    ]);
+
+    // The first validator signed with several slots, the second one didn't sign at all.
+    let mut signers = BitSet::new();
+    for slot in [0, 1, 2, 150, 199, 300, 511] {
+        signers.insert(slot);
+    }
+
+    let mut macro_block = MacroBlock {
+        justification: Some(TendermintProof {
+            round: 0,
+            sig: MultiSignature::new(AggregateSignature::new(), signers),
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        macro_block.signers(&validators),
+        vec![Address::from([0u8; 20]), Address::from([2u8; 20])]
+    );
+
+    macro_block.justification = None;
+    assert!(macro_block.signers(&validators).is_empty());
+}
+
+#[test]
+fn it_caches_aggregate_public_keys() {
+    let key_pair = create_key_pair();
+    let mut builder = ValidatorsBuilder::new();
+    for _ in 0..policy::SLOTS {
+        builder.push(
+            Address::from([0u8; 20]),
+            key_pair.public_key,
+            PublicKey::from([0u8; 32]),
+        );
+    }
+    let validators = builder.build();
+
+    let mut signers = BitSet::new();
+    for slot in [0, 1, 2, 150, 199, 300, 511] {
+        signers.insert(slot);
+    }
+
+    let mut cache = AggPkCache::new();
+    let fresh = TendermintProof::aggregate_public_key(&validators, &signers);
+    assert_eq!(cache.get_or_aggregate(&validators, &signers), fresh);
+    assert_eq!(cache.get_or_aggregate(&validators, &signers), fresh);
+    assert_eq!(cache.len(), 1);
+
+    // Other bitmaps are cached separately.
+    let mut other_signers = signers.clone();
+    other_signers.insert(42);
+    assert_ne!(cache.get_or_aggregate(&validators, &other_signers), fresh);
+    assert_eq!(cache.len(), 2);
+
+    // The cache is cleared when it is used with another validator set.
+    let mut builder = ValidatorsBuilder::new();
+    for _ in 0..policy::SLOTS {
+        builder.push(
+            Address::from([1u8; 20]),
+            key_pair.public_key,
+            PublicKey::from([0u8; 32]),
+        );
+    }
+    let other_validators = builder.build();
+    assert_eq!(cache.get_or_aggregate(&other_validators, &signers), fresh);
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn it_can_hash_large_bodies_incrementally() {
+    let transactions: Vec<Transaction> = (0..2000u32)
+        .map(|i| {
+            Transaction::new_basic(
+                Address::from([(i % 256) as u8; Address::SIZE]),
+                Address::from([(i / 256) as u8; Address::SIZE]),
+                Coin::from_u64_unchecked(i as u64 + 1),
+                Coin::from_u64_unchecked(1),
+                i,
+                NetworkId::UnitAlbatross,
+            )
+        })
+        .collect();
+
+    let body = MicroBody {
+        fork_proofs: vec![],
+        transactions,
+    };
+    assert_eq!(body.hash_streaming(), body.hash::<Blake2bHash>());
+
+    let empty_body = MicroBody {
+        fork_proofs: vec![],
+        transactions: vec![],
+    };
+    assert_eq!(
+        empty_body.hash_streaming(),
+        empty_body.hash::<Blake2bHash>()
+    );
+}
+
+#[test]
+fn fork_proofs_are_independent_of_the_observation_order() {
+    let header1 = MicroHeader {
+        version: 1,
+        block_number: 1,
+        view_number: 0,
+        timestamp: 0,
+        parent_hash: Blake2bHash::default(),
+        seed: VrfSeed::default(),
+        extra_data: vec![],
+        state_root: Blake2bHash::default(),
+        body_root: Blake2bHash::default(),
+        history_root: Blake2bHash::default(),
+    };
+    let mut header2 = header1.clone();
+    header2.timestamp = 1;
+
+    let justification1 = Signature::from([1u8; Signature::SIZE]);
+    let justification2 = Signature::from([2u8; Signature::SIZE]);
+
+    let proof1 = ForkProof::new(
+        header1.clone(),
+        justification1.clone(),
+        header2.clone(),
+        justification2.clone(),
+        VrfSeed::default(),
+    );
+    let proof2 = ForkProof::new(
+        header2,
+        justification2,
+        header1,
+        justification1,
+        VrfSeed::default(),
+    );
+
+    assert_eq!(proof1.serialize_to_vec(), proof2.serialize_to_vec());
+    assert_eq!(proof1.hash::<Blake2bHash>(), proof2.hash::<Blake2bHash>());
+    assert!(proof1.header1.hash::<Blake2bHash>() < proof1.header2.hash::<Blake2bHash>());
+}
+
+#[test]
+fn it_can_verify_the_block_body() {
+    let transaction = Transaction::new_basic(
+        Address::from([1u8; Address::SIZE]),
+        Address::from([2u8; Address::SIZE]),
+        Coin::from_u64_unchecked(100),
+        Coin::from_u64_unchecked(1),
+        1,
+        NetworkId::UnitAlbatross,
+    );
+    let body = MicroBody {
+        fork_proofs: vec![],
+        transactions: vec![transaction],
+    };
+    let header = MicroHeader {
+        version: 1,
+        block_number: 1,
+        view_number: 0,
+        timestamp: 0,
+        parent_hash: Blake2bHash::default(),
+        seed: VrfSeed::default(),
+        extra_data: vec![],
+        state_root: Blake2bHash::default(),
+        body_root: body.hash(),
+        history_root: Blake2bHash::default(),
+    };
+
+    let mut block = Block::Micro(MicroBlock {
+        header,
+        justification: None,
+        body: Some(body),
+    });
+    assert_eq!(block.verify_body(), Ok(()));
+
+    // Tamper with the body.
+    block.transactions_mut().unwrap()[0].value = Coin::from_u64_unchecked(1000);
+    assert_eq!(block.verify_body(), Err(BlockError::BodyHashMismatch));
+
+    // A block without a body can't be verified.
+    if let Block::Micro(ref mut micro_block) = block {
+        micro_block.body = None;
+    }
+    assert_eq!(block.verify_body(), Err(BlockError::MissingBody));
+}
+
+fn create_key_pair() -> KeyPair {
     let raw_key = hex::decode(
         "1b9e470e0deb06fe55774bb2cf499b411f55265c10d8d78742078381803451e058c88\
         391431799462edde4c7872649964137d8e03cd618dd4a25690c56ffd7f42fb7ae8049d29f38d569598b38d4\
         39f69107cc0b6f4ecd00a250c74409510100",
     )
     .unwrap();
-    let key_pair = KeyPair::deserialize_from_vec(&raw_key).unwrap();
+    KeyPair::deserialize_from_vec(&raw_key).unwrap()
+}
+
+fn create_multisig() -> MultiSignature {
+    let key_pair = create_key_pair();
     let signature = key_pair.sign(&"foobar");
     IndividualSignature::new(signature, 1).as_multisig()
 }
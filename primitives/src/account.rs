@@ -5,6 +5,9 @@ use thiserror::Error;
 
 use beserial::{Deserialize, Serialize};
 
+/// The type of an account. The discriminant is serialized as the tag byte of every account, which
+/// is also how accounts are stored in the accounts trie. Therefore the discriminants must never be
+/// changed or reused, new types must get a new one.
 #[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize, Display)]
 #[repr(u8)]
 #[cfg_attr(
@@ -14,11 +14,6 @@ use thiserror::Error;
 use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteBytesExt};
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Default)]
-#[cfg_attr(
-    feature = "serde-derive",
-    derive(serde::Serialize, serde::Deserialize),
-    serde(transparent)
-)]
 pub struct Coin(u64);
 
 impl Coin {
@@ -31,6 +26,12 @@ impl Coin {
     // JavaScript's Number.MAX_SAFE_INTEGER: 2^53 - 1
     pub const MAX_SAFE_VALUE: u64 = 9_007_199_254_740_991u64;
 
+    // The maximum valid coin value.
+    pub const MAX: Coin = Coin(Coin::MAX_SAFE_VALUE);
+
+    /// The number of basis points that make up a whole value, see `mul_bps`.
+    pub const BASIS_POINTS: u64 = 10_000u64;
+
     #[inline]
     pub fn from_u64_unchecked(val: u64) -> Coin {
         Coin(val)
@@ -65,6 +66,81 @@ impl Coin {
             None => None,
         }
     }
+
+    /// Adds two coin values, saturating at `Coin::MAX` instead of overflowing.
+    ///
+    /// This is meant for display purposes only (e.g. showing a total in a UI). Do NOT use it for
+    /// accounting, where an overflow must be treated as an error (see `checked_add`).
+    #[inline]
+    pub fn saturating_add(self, rhs: Coin) -> Coin {
+        Coin(self.0.saturating_add(rhs.0).min(Coin::MAX_SAFE_VALUE))
+    }
+
+    /// Returns the value as 8 big-endian bytes. This is the same encoding as the beserial
+    /// serialization, but with a guaranteed size, so it can be used in fixed-size records. Since it
+    /// is big-endian, the byte-wise order of the encoded values matches their numeric order.
+    #[inline]
+    pub fn to_fixed_bytes(self) -> [u8; 8] {
+        self.0.to_be_bytes()
+    }
+
+    /// Reads a value that was encoded with `to_fixed_bytes`. Fails if the value exceeds
+    /// `Coin::MAX_SAFE_VALUE`.
+    #[inline]
+    pub fn from_fixed_bytes(bytes: [u8; 8]) -> Result<Coin, CoinConvertError> {
+        Coin::try_from(u64::from_be_bytes(bytes))
+    }
+
+    /// Subtracts two coin values, saturating at zero instead of underflowing.
+    ///
+    /// This is meant for display purposes only (e.g. showing "pending = balance - locked" in a UI).
+    /// Do NOT use it for accounting, where an underflow must be treated as an error (see
+    /// `checked_sub`).
+    #[inline]
+    pub fn saturating_sub(self, rhs: Coin) -> Coin {
+        Coin(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Returns the given fraction of the value, in basis points (1/100 of a percent), rounded
+    /// down. Fractions above 10000 basis points saturate at `Coin::MAX`.
+    #[inline]
+    pub fn mul_bps(self, basis_points: u16) -> Coin {
+        let value = self.0 as u128 * basis_points as u128 / Coin::BASIS_POINTS as u128;
+        Coin(value.min(Coin::MAX_SAFE_VALUE as u128) as u64)
+    }
+
+    /// Distributes `total` proportionally to the given weights. Every share is rounded down and
+    /// the remaining Lunas (at most one per weight) go to the shares with the largest rounding
+    /// errors, with ties going to the lowest index. This way the shares always add up to
+    /// `total`, unless all weights are zero, in which case all shares are zero.
+    pub fn split_proportional(total: Coin, weights: &[u64]) -> Vec<Coin> {
+        let total_weight: u128 = weights.iter().map(|weight| *weight as u128).sum();
+        if total_weight == 0 {
+            return vec![Coin::ZERO; weights.len()];
+        }
+
+        let mut shares = Vec::with_capacity(weights.len());
+        let mut errors = Vec::with_capacity(weights.len());
+        let mut distributed = 0u64;
+
+        for (i, weight) in weights.iter().enumerate() {
+            let product = total.0 as u128 * *weight as u128;
+            let share = (product / total_weight) as u64;
+            shares.push(share);
+            errors.push((product % total_weight, i));
+            distributed += share;
+        }
+
+        // Sort by descending rounding error, then by ascending index.
+        errors.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        let remainder = (total.0 - distributed) as usize;
+        for (_, i) in errors.iter().take(remainder) {
+            shares[*i] += 1;
+        }
+
+        shares.into_iter().map(Coin).collect()
+    }
 }
 
 impl From<Coin> for u64 {
@@ -213,6 +289,68 @@ impl Serialize for Coin {
     }
 }
 
+#[cfg(feature = "serde-derive")]
+mod serde_derive {
+    use std::convert::TryFrom;
+    use std::fmt;
+
+    use serde::de::{Error, Unexpected, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Coin;
+
+    // Coin values never exceed `Coin::MAX_SAFE_VALUE`, so they are always represented exactly as
+    // JSON numbers, even by parsers that use doubles.
+    impl Serialize for Coin {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_u64(self.0)
+        }
+    }
+
+    struct CoinVisitor;
+
+    impl<'de> Visitor<'de> for CoinVisitor {
+        type Value = Coin;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(
+                f,
+                "an amount of Lunas up to {} as a number or a decimal string",
+                Coin::MAX_SAFE_VALUE
+            )
+        }
+
+        fn visit_u64<E: Error>(self, value: u64) -> Result<Coin, E> {
+            Coin::try_from(value).map_err(|_| E::invalid_value(Unexpected::Unsigned(value), &self))
+        }
+
+        fn visit_i64<E: Error>(self, value: i64) -> Result<Coin, E> {
+            let value = u64::try_from(value)
+                .map_err(|_| E::invalid_value(Unexpected::Signed(value), &self))?;
+            self.visit_u64(value)
+        }
+
+        fn visit_str<E: Error>(self, value: &str) -> Result<Coin, E> {
+            // Only accept plain decimal digits, no signs or whitespace.
+            if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(E::invalid_value(Unexpected::Str(value), &self));
+            }
+            let value = value
+                .parse::<u64>()
+                .map_err(|_| E::invalid_value(Unexpected::Str(value), &self))?;
+            self.visit_u64(value)
+        }
+    }
+
+    /// Coins are deserialized from numbers and, for clients that encode large integers as
+    /// strings, from decimal strings. Values above `Coin::MAX_SAFE_VALUE` are rejected.
+    impl<'de> Deserialize<'de> for Coin {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Coin, D::Error> {
+            deserializer.deserialize_any(CoinVisitor)
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 #[error("Can't parse Coin value: '{0}'")]
 pub struct CoinParseError(String);
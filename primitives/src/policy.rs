@@ -25,6 +25,15 @@ pub const TRANSACTION_VALIDITY_WINDOW: u32 = 7200;
 /// The maximum allowed size, in bytes, for a micro block body.
 pub const MAX_SIZE_MICRO_BODY: usize = 100_000;
 
+/// The maximum number of fork proofs that can be included in a single micro block.
+pub const MAX_FORK_PROOFS_PER_BLOCK: usize = 10;
+
+/// The maximum allowed size, in bytes, for the data field of a transaction.
+pub const MAX_TX_DATA_SIZE: usize = 1024;
+
+/// The maximum number of macro blocks that are returned by a single macro block query.
+pub const MAX_MACRO_BLOCKS_PER_REQUEST: u32 = 1000;
+
 /// The current version number of the protocol. Changing this always results in a hard fork.
 pub const VERSION: u16 = 1;
 
@@ -72,6 +81,8 @@ pub const TENDERMINT_TIMEOUT_DELTA: u64 = 1000; // TODO: Set
 /// The deposit necessary to create a validator in Lunas (1 NIM = 100,000 Lunas).
 /// A validator is someone who actually participates in block production. They are akin to miners
 /// in proof-of-work.
+/// The deposit is fixed rather than a minimum: transactions creating a validator must have exactly
+/// this value, and genesis validators are created with it as well.
 pub const VALIDATOR_DEPOSIT: u64 = 1_000_000_000;
 
 /// Total supply in units.
@@ -119,6 +130,13 @@ pub fn election_block_after(block_number: u32) -> u32 {
     (block_number / BLOCKS_PER_EPOCH + 1) * BLOCKS_PER_EPOCH
 }
 
+/// Like `election_block_after`, but returns `None` if the next election macro block is beyond the
+/// largest block number.
+#[inline]
+pub fn checked_election_block_after(block_number: u32) -> Option<u32> {
+    (block_number / BLOCKS_PER_EPOCH + 1).checked_mul(BLOCKS_PER_EPOCH)
+}
+
 /// Returns the number (height) of the preceding election macro block before a given block number (height).
 /// If the given block number is an  election macro block, it returns the election macro block before it.
 #[inline]
@@ -148,6 +166,13 @@ pub fn macro_block_after(block_number: u32) -> u32 {
     (block_number / BLOCKS_PER_BATCH + 1) * BLOCKS_PER_BATCH
 }
 
+/// Like `macro_block_after`, but returns `None` if the next macro block is beyond the largest
+/// block number.
+#[inline]
+pub fn checked_macro_block_after(block_number: u32) -> Option<u32> {
+    (block_number / BLOCKS_PER_BATCH + 1).checked_mul(BLOCKS_PER_BATCH)
+}
+
 /// Returns the number (height) of the preceding macro block before a given block number (height).
 /// If the given block number is a macro block, it returns the macro block before it.
 #[inline]
@@ -198,6 +223,12 @@ pub fn election_block_of(epoch: u32) -> u32 {
     epoch * BLOCKS_PER_EPOCH
 }
 
+/// Like `election_block_of`, but returns `None` if the election macro block of the given epoch is
+/// beyond the largest block number.
+pub fn checked_election_block_of(epoch: u32) -> Option<u32> {
+    epoch.checked_mul(BLOCKS_PER_EPOCH)
+}
+
 /// Returns the block number of the macro block (checkpoint or election) of the given batch (which
 /// is always the last block).
 pub fn macro_block_of(batch: u32) -> u32 {
@@ -315,6 +346,9 @@ mod tests {
         assert_eq!(macro_block_before(128), 96);
         assert_eq!(macro_block_before(129), 128);
         assert_eq!(macro_block_before(130), 128);
+
+        assert_eq!(checked_macro_block_after(129), Some(160));
+        assert_eq!(checked_macro_block_after(u32::MAX), None);
     }
 
     #[test]
@@ -337,6 +371,12 @@ mod tests {
         assert_eq!(last_election_block(127), 0);
         assert_eq!(last_election_block(128), 128);
         assert_eq!(last_election_block(129), 128);
+
+        assert_eq!(checked_election_block_after(129), Some(256));
+        assert_eq!(checked_election_block_after(u32::MAX), None);
+
+        assert_eq!(checked_election_block_of(2), Some(256));
+        assert_eq!(checked_election_block_of(u32::MAX), None);
     }
 
     #[test]
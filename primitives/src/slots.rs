@@ -165,6 +165,52 @@ impl Validators {
     pub fn iter(&self) -> Iter<Validator> {
         self.validators.iter()
     }
+
+    /// Computes how the validator set changed from `self` to `other`. All lists in the result are
+    /// ordered by validator address.
+    pub fn diff(&self, other: &Validators) -> ValidatorSetDiff {
+        let mut diff = ValidatorSetDiff::default();
+
+        for (address, band) in &self.validator_map {
+            let old_slots = self.validators[*band as usize].num_slots();
+            match other.validator_map.get(address) {
+                None => diff.removed.push(address.clone()),
+                Some(new_band) => {
+                    let new_slots = other.validators[*new_band as usize].num_slots();
+                    if old_slots != new_slots {
+                        diff.changed.push((address.clone(), old_slots, new_slots));
+                    }
+                }
+            }
+        }
+
+        for address in other.validator_map.keys() {
+            if !self.validator_map.contains_key(address) {
+                diff.added.push(address.clone());
+            }
+        }
+
+        diff
+    }
+}
+
+/// The changes of the validator set between two epochs.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ValidatorSetDiff {
+    /// The validators that joined the set.
+    pub added: Vec<Address>,
+    /// The validators that left the set.
+    pub removed: Vec<Address>,
+    /// The validators that stayed in the set but whose stake, and thus number of slots, changed.
+    /// Contains the number of slots before and after the change.
+    pub changed: Vec<(Address, u16, u16)>,
+}
+
+impl ValidatorSetDiff {
+    /// Returns true if the validator set didn't change.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
 }
 
 impl Serialize for Validators {
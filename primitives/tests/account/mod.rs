@@ -0,0 +1,28 @@
+use std::convert::TryFrom;
+
+use beserial::{Deserialize, Serialize};
+use nimiq_test_log::test;
+use primitives::account::AccountType;
+
+const ACCOUNT_TYPE_TAGS: [(AccountType, u8); 7] = [
+    (AccountType::Basic, 0),
+    (AccountType::Vesting, 1),
+    (AccountType::HTLC, 2),
+    (AccountType::Staking, 3),
+    (AccountType::StakingValidator, 4),
+    (AccountType::StakingValidatorsStaker, 5),
+    (AccountType::StakingStaker, 6),
+];
+
+#[test]
+fn account_types_serialize_to_their_tags() {
+    for (ty, tag) in ACCOUNT_TYPE_TAGS {
+        assert_eq!(ty.serialize_to_vec(), vec![tag]);
+        assert_eq!(AccountType::deserialize_from_vec(&[tag]).unwrap(), ty);
+        assert_eq!(u8::from(ty), tag);
+        assert_eq!(AccountType::try_from(tag).unwrap(), ty);
+    }
+
+    assert!(AccountType::deserialize_from_vec(&[ACCOUNT_TYPE_TAGS.len() as u8]).is_err());
+    assert!(AccountType::try_from(ACCOUNT_TYPE_TAGS.len() as u8).is_err());
+}
@@ -180,3 +180,163 @@ fn test_empty_frac_part() {
 fn test_integrity_frac_digits_and_lunas_per_coin() {
     assert_eq!(10u64.pow(Coin::FRAC_DIGITS), Coin::LUNAS_PER_COIN);
 }
+
+#[test]
+fn test_saturating_add() {
+    assert_eq!(
+        Coin::from_u64_unchecked(1).saturating_add(Coin::from_u64_unchecked(2)),
+        Coin::from_u64_unchecked(3)
+    );
+    assert_eq!(Coin::MAX.saturating_add(Coin::ZERO), Coin::MAX);
+    assert_eq!(
+        Coin::MAX.saturating_add(Coin::from_u64_unchecked(1)),
+        Coin::MAX
+    );
+    assert_eq!(Coin::MAX.saturating_add(Coin::MAX), Coin::MAX);
+    assert_eq!(
+        Coin::from_u64_unchecked(u64::MAX).saturating_add(Coin::from_u64_unchecked(1)),
+        Coin::MAX
+    );
+}
+
+#[test]
+fn test_saturating_sub() {
+    assert_eq!(
+        Coin::from_u64_unchecked(3).saturating_sub(Coin::from_u64_unchecked(2)),
+        Coin::from_u64_unchecked(1)
+    );
+    assert_eq!(
+        Coin::from_u64_unchecked(2).saturating_sub(Coin::from_u64_unchecked(2)),
+        Coin::ZERO
+    );
+    assert_eq!(
+        Coin::from_u64_unchecked(2).saturating_sub(Coin::from_u64_unchecked(3)),
+        Coin::ZERO
+    );
+    assert_eq!(Coin::ZERO.saturating_sub(Coin::MAX), Coin::ZERO);
+    assert_eq!(Coin::MAX.saturating_sub(Coin::ZERO), Coin::MAX);
+}
+
+#[test]
+fn test_checked_mul() {
+    let coin = Coin::from_u64_unchecked(12345);
+    assert_eq!(coin.checked_mul(0), Some(Coin::ZERO));
+    assert_eq!(coin.checked_mul(1), Some(coin));
+    assert_eq!(coin.checked_mul(3), Some(Coin::from_u64_unchecked(37035)));
+
+    // The result must not exceed Coin::MAX.
+    assert_eq!(Coin::MAX.checked_mul(1), Some(Coin::MAX));
+    assert_eq!(Coin::MAX.checked_mul(2), None);
+    assert_eq!(
+        Coin::from_u64_unchecked(Coin::MAX_SAFE_VALUE / 3).checked_mul(3),
+        Some(Coin::from_u64_unchecked(Coin::MAX_SAFE_VALUE - 1))
+    );
+    assert_eq!(
+        Coin::from_u64_unchecked(Coin::MAX_SAFE_VALUE / 2 + 1).checked_mul(2),
+        None
+    );
+
+    // Overflowing the underlying u64 fails as well.
+    assert_eq!(Coin::from_u64_unchecked(2).checked_mul(u64::MAX), None);
+}
+
+#[test]
+fn test_mul_bps() {
+    let coin = Coin::from_u64_unchecked(12345);
+    assert_eq!(coin.mul_bps(0), Coin::ZERO);
+    assert_eq!(coin.mul_bps(10_000), coin);
+    assert_eq!(coin.mul_bps(5_000), Coin::from_u64_unchecked(6172));
+    assert_eq!(coin.mul_bps(1), Coin::from_u64_unchecked(1));
+    assert_eq!(Coin::from_u64_unchecked(9999).mul_bps(1), Coin::ZERO);
+    assert_eq!(coin.mul_bps(20_000), Coin::from_u64_unchecked(24690));
+    assert_eq!(Coin::MAX.mul_bps(u16::MAX), Coin::MAX);
+}
+
+#[test]
+fn test_split_proportional() {
+    let total = Coin::from_u64_unchecked(100);
+
+    // Exact splits don't need rounding.
+    assert_eq!(
+        Coin::split_proportional(total, &[1, 3]),
+        vec![Coin::from_u64_unchecked(25), Coin::from_u64_unchecked(75)]
+    );
+
+    // The remainder goes to the shares with the largest rounding errors.
+    assert_eq!(
+        Coin::split_proportional(total, &[1, 2, 3]),
+        vec![
+            Coin::from_u64_unchecked(17),
+            Coin::from_u64_unchecked(33),
+            Coin::from_u64_unchecked(50),
+        ]
+    );
+
+    // Ties go to the lowest index.
+    assert_eq!(
+        Coin::split_proportional(total, &[1, 1, 1]),
+        vec![
+            Coin::from_u64_unchecked(34),
+            Coin::from_u64_unchecked(33),
+            Coin::from_u64_unchecked(33),
+        ]
+    );
+
+    // The shares always add up to the total.
+    let weights = [7, 0, 13, 1, 999];
+    let shares = Coin::split_proportional(Coin::MAX, &weights);
+    assert_eq!(shares[1], Coin::ZERO);
+    assert_eq!(shares.into_iter().sum::<Coin>(), Coin::MAX);
+
+    assert_eq!(
+        Coin::split_proportional(total, &[0, 0]),
+        vec![Coin::ZERO, Coin::ZERO]
+    );
+    assert!(Coin::split_proportional(total, &[]).is_empty());
+}
+
+#[test]
+fn test_fixed_bytes() {
+    for test in NON_FAILING_TESTS.iter() {
+        let bytes = test.coin.to_fixed_bytes();
+
+        // The fixed encoding is the same as the beserial encoding.
+        assert_eq!(bytes.to_vec(), test.coin.serialize_to_vec());
+        assert_eq!(Coin::from_fixed_bytes(bytes), Ok(test.coin));
+    }
+
+    // The byte-wise order matches the numeric order.
+    let small = Coin::from_u64_unchecked(255).to_fixed_bytes();
+    let large = Coin::from_u64_unchecked(256).to_fixed_bytes();
+    assert!(small < large);
+
+    assert!(Coin::from_fixed_bytes(u64::MAX.to_be_bytes()).is_err());
+}
+
+#[test]
+#[cfg(feature = "serde-derive")]
+fn test_json() {
+    // Coin::MAX is the largest integer that doubles represent exactly, so it survives a JSON round
+    // trip as a number.
+    let json = serde_json::to_string(&Coin::MAX).unwrap();
+    assert_eq!(json, "9007199254740991");
+    assert_eq!(serde_json::from_str::<Coin>(&json).unwrap(), Coin::MAX);
+
+    // Decimal strings are accepted as well.
+    assert_eq!(
+        serde_json::from_str::<Coin>("\"9007199254740991\"").unwrap(),
+        Coin::MAX
+    );
+    assert_eq!(
+        serde_json::from_str::<Coin>("\"12345\"").unwrap(),
+        Coin::from_u64_unchecked(12345)
+    );
+
+    // Values above Coin::MAX, negative values and malformed strings are rejected.
+    assert!(serde_json::from_str::<Coin>("9007199254740992").is_err());
+    assert!(serde_json::from_str::<Coin>("\"9007199254740992\"").is_err());
+    assert!(serde_json::from_str::<Coin>("-1").is_err());
+    assert!(serde_json::from_str::<Coin>("\"1.5\"").is_err());
+    assert!(serde_json::from_str::<Coin>("\"+1\"").is_err());
+    assert!(serde_json::from_str::<Coin>("1.5").is_err());
+}
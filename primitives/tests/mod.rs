@@ -1,4 +1,6 @@
 extern crate nimiq_primitives as primitives;
 
+#[cfg(feature = "account")]
+mod account;
 #[cfg(feature = "coin")]
 mod coin;
@@ -45,16 +45,34 @@ impl AccountTransactionVerification for BasicAccountVerifier {
         Ok(())
     }
 
-    fn verify_outgoing_transaction(transaction: &Transaction) -> Result<(), TransactionError> {
+    fn verify_outgoing_transaction_without_signature(
+        transaction: &Transaction,
+    ) -> Result<(), TransactionError> {
         assert_eq!(transaction.sender_type, AccountType::Basic);
 
-        // Verify signer & signature.
+        // Verify signer.
         let signature_proof: SignatureProof =
             Deserialize::deserialize(&mut &transaction.proof[..])?;
 
-        if !signature_proof.is_signed_by(&transaction.sender)
-            || !signature_proof.verify(transaction.serialize_content().as_slice())
-        {
+        if !signature_proof.is_signed_by(&transaction.sender) {
+            error!(
+                "The following transaction has an invalid proof:\n{:?}",
+                transaction
+            );
+            return Err(TransactionError::InvalidProof);
+        }
+
+        Ok(())
+    }
+
+    fn verify_outgoing_signature(transaction: &Transaction) -> Result<(), TransactionError> {
+        assert_eq!(transaction.sender_type, AccountType::Basic);
+
+        // Verify signature.
+        let signature_proof: SignatureProof =
+            Deserialize::deserialize(&mut &transaction.proof[..])?;
+
+        if !signature_proof.verify(transaction.serialize_content().as_slice()) {
             error!(
                 "The following transaction has an invalid proof:\n{:?}",
                 transaction
@@ -62,7 +62,9 @@ impl AccountTransactionVerification for HashedTimeLockedContractVerifier {
         CreationTransactionData::parse(transaction)?.verify()
     }
 
-    fn verify_outgoing_transaction(transaction: &Transaction) -> Result<(), TransactionError> {
+    fn verify_outgoing_transaction_without_signature(
+        transaction: &Transaction,
+    ) -> Result<(), TransactionError> {
         assert_eq!(transaction.sender_type, AccountType::HTLC);
 
         // Verify proof.
@@ -16,7 +16,24 @@ pub mod vesting_contract;
 /// This particularly does not require an account to exist.
 pub trait AccountTransactionVerification: Sized {
     fn verify_incoming_transaction(transaction: &Transaction) -> Result<(), TransactionError>;
-    fn verify_outgoing_transaction(transaction: &Transaction) -> Result<(), TransactionError>;
+
+    fn verify_outgoing_transaction(transaction: &Transaction) -> Result<(), TransactionError> {
+        Self::verify_outgoing_transaction_without_signature(transaction)?;
+        Self::verify_outgoing_signature(transaction)
+    }
+
+    /// Verifies the outgoing part of a transaction, except for the signature that is checked by
+    /// `verify_outgoing_signature`.
+    fn verify_outgoing_transaction_without_signature(
+        transaction: &Transaction,
+    ) -> Result<(), TransactionError>;
+
+    /// Verifies the signature of the plain signature proof that spends the sender account. Only
+    /// accounts that are spent with a plain signature proof check anything here, these signatures
+    /// can also be verified in a batch with `Transaction::batch_verify`.
+    fn verify_outgoing_signature(_transaction: &Transaction) -> Result<(), TransactionError> {
+        Ok(())
+    }
 }
 
 impl AccountTransactionVerification for AccountType {
@@ -39,19 +56,41 @@ impl AccountTransactionVerification for AccountType {
         }
     }
 
-    /// Verifies the outgoing part of a transaction only using the static data available in the transaction.
-    fn verify_outgoing_transaction(transaction: &Transaction) -> Result<(), TransactionError> {
+    /// Verifies the outgoing part of a transaction only using the static data available in the
+    /// transaction, except for the signature that is checked by `verify_outgoing_signature`.
+    fn verify_outgoing_transaction_without_signature(
+        transaction: &Transaction,
+    ) -> Result<(), TransactionError> {
         match transaction.sender_type {
-            AccountType::Basic => BasicAccountVerifier::verify_outgoing_transaction(transaction),
+            AccountType::Basic => {
+                BasicAccountVerifier::verify_outgoing_transaction_without_signature(transaction)
+            }
             AccountType::Vesting => {
-                VestingContractVerifier::verify_outgoing_transaction(transaction)
+                VestingContractVerifier::verify_outgoing_transaction_without_signature(transaction)
             }
             AccountType::HTLC => {
-                HashedTimeLockedContractVerifier::verify_outgoing_transaction(transaction)
+                HashedTimeLockedContractVerifier::verify_outgoing_transaction_without_signature(
+                    transaction,
+                )
             }
             AccountType::Staking => {
-                StakingContractVerifier::verify_outgoing_transaction(transaction)
+                StakingContractVerifier::verify_outgoing_transaction_without_signature(transaction)
+            }
+            _ => {
+                unreachable!()
+            }
+        }
+    }
+
+    /// Verifies the signature of the plain signature proof that spends the sender account.
+    fn verify_outgoing_signature(transaction: &Transaction) -> Result<(), TransactionError> {
+        match transaction.sender_type {
+            AccountType::Basic => BasicAccountVerifier::verify_outgoing_signature(transaction),
+            AccountType::Vesting => VestingContractVerifier::verify_outgoing_signature(transaction),
+            AccountType::HTLC => {
+                HashedTimeLockedContractVerifier::verify_outgoing_signature(transaction)
             }
+            AccountType::Staking => StakingContractVerifier::verify_outgoing_signature(transaction),
             _ => {
                 unreachable!()
             }
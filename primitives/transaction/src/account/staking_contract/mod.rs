@@ -45,7 +45,9 @@ impl AccountTransactionVerification for StakingContractVerifier {
         Ok(())
     }
 
-    fn verify_outgoing_transaction(transaction: &Transaction) -> Result<(), TransactionError> {
+    fn verify_outgoing_transaction_without_signature(
+        transaction: &Transaction,
+    ) -> Result<(), TransactionError> {
         assert_eq!(transaction.sender_type, AccountType::Staking);
 
         let proof = OutgoingStakingTransactionProof::parse(transaction)?;
@@ -61,7 +61,17 @@ impl AccountTransactionVerification for VestingContractVerifier {
         CreationTransactionData::parse(transaction).map(|_| ())
     }
 
-    fn verify_outgoing_transaction(transaction: &Transaction) -> Result<(), TransactionError> {
+    fn verify_outgoing_transaction_without_signature(
+        transaction: &Transaction,
+    ) -> Result<(), TransactionError> {
+        assert_eq!(transaction.sender_type, AccountType::Vesting);
+
+        // The signer is checked against the owner of the contract when the transaction is
+        // committed.
+        Ok(())
+    }
+
+    fn verify_outgoing_signature(transaction: &Transaction) -> Result<(), TransactionError> {
         assert_eq!(transaction.sender_type, AccountType::Vesting);
 
         // Verify signature.
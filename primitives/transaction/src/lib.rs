@@ -285,6 +285,20 @@ impl Transaction {
     }
 
     pub fn verify(&self, network_id: NetworkId) -> Result<(), TransactionError> {
+        self.verify_with(network_id, false)
+    }
+
+    /// Verifies the transaction like `verify`, but doesn't verify the signature proof again that
+    /// was already checked with `batch_verify`.
+    pub(crate) fn verify_batched(&self, network_id: NetworkId) -> Result<(), TransactionError> {
+        self.verify_with(network_id, true)
+    }
+
+    fn verify_with(
+        &self,
+        network_id: NetworkId,
+        batch_verified: bool,
+    ) -> Result<(), TransactionError> {
         if self.valid {
             return Ok(());
         }
@@ -293,6 +307,12 @@ impl Transaction {
             return Err(TransactionError::ForeignNetwork);
         }
 
+        if self.data.len() > policy::MAX_TX_DATA_SIZE {
+            return Err(TransactionError::DataTooLarge {
+                size: self.data.len(),
+            });
+        }
+
         // Check that value > 0 except if it is a signalling transaction.
         if self.flags.contains(TransactionFlags::SIGNALLING) {
             if self.value != Coin::ZERO {
@@ -313,7 +333,11 @@ impl Transaction {
         }
 
         // Check transaction validity for sender account.
-        AccountType::verify_outgoing_transaction(self)?;
+        if batch_verified {
+            AccountType::verify_outgoing_transaction_without_signature(self)?;
+        } else {
+            AccountType::verify_outgoing_transaction(self)?;
+        }
 
         // Check transaction validity for recipient account.
         AccountType::verify_incoming_transaction(self)?;
@@ -321,6 +345,74 @@ impl Transaction {
         Ok(())
     }
 
+    /// Verifies the given transactions like `verify`, but verifies the signatures of their plain
+    /// signature proofs in a single batch, which is significantly faster than verifying them one by
+    /// one. If any transaction is invalid, returns its index together with the error.
+    pub fn verify_all(
+        txns: &[&Transaction],
+        network_id: NetworkId,
+    ) -> Result<(), (usize, TransactionError)> {
+        if let Err(index) = Transaction::batch_verify(txns.iter().copied()) {
+            return Err((index, TransactionError::InvalidProof));
+        }
+
+        for (index, tx) in txns.iter().enumerate() {
+            tx.verify_batched(network_id)
+                .map_err(|error| (index, error))?;
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the signature proofs of the given transactions in a single batch. Only
+    /// transactions sent from basic accounts and vesting contracts are spent with a plain signature
+    /// proof, which `AccountTransactionVerification::verify_outgoing_signature` checks, all other
+    /// transactions are skipped. If any signature is invalid, returns the index of the first
+    /// transaction with an invalid signature.
+    pub fn batch_verify<'a, I>(txns: I) -> Result<(), usize>
+    where
+        I: IntoIterator<Item = &'a Transaction>,
+    {
+        let mut proofs = vec![];
+        let mut malformed = None;
+
+        for (i, tx) in txns.into_iter().enumerate() {
+            if tx.sender_type != AccountType::Basic && tx.sender_type != AccountType::Vesting {
+                continue;
+            }
+
+            match SignatureProof::deserialize(&mut &tx.proof[..]) {
+                Ok(proof) => proofs.push((i, proof, tx.serialize_content())),
+                Err(_) => {
+                    malformed = Some(i);
+                    break;
+                }
+            }
+        }
+
+        if malformed.is_none()
+            && PublicKey::verify_batch(
+                proofs
+                    .iter()
+                    .map(|(_, proof, content)| (&proof.public_key, &proof.signature, &content[..])),
+            )
+        {
+            return Ok(());
+        }
+
+        // The batch is invalid, so find the first invalid signature.
+        let invalid = proofs
+            .iter()
+            .find(|(_, proof, content)| !proof.verify(content))
+            .map(|(i, _, _)| *i);
+
+        invalid
+            .into_iter()
+            .chain(malformed)
+            .min()
+            .map_or(Ok(()), Err)
+    }
+
     pub fn check_set_valid(&mut self, tx: &Arc<Transaction>) {
         if tx.valid && self.hash::<Blake2bHash>() == tx.hash() {
             self.valid = true;
@@ -328,9 +420,20 @@ impl Transaction {
     }
 
     pub fn is_valid_at(&self, block_height: u32) -> bool {
-        let window = policy::TRANSACTION_VALIDITY_WINDOW;
-        block_height >= self.validity_start_height
-            && block_height < self.validity_start_height + window
+        block_height >= self.validity_start_height && !self.is_expired_at(block_height)
+    }
+
+    /// Returns whether the validity window of this transaction has already passed at the given
+    /// block height.
+    pub fn is_expired_at(&self, block_height: u32) -> bool {
+        match self
+            .validity_start_height
+            .checked_add(policy::TRANSACTION_VALIDITY_WINDOW)
+        {
+            Some(expiry) => block_height >= expiry,
+            // The window only ends after the last possible block height.
+            None => false,
+        }
     }
 
     pub fn contract_creation_address(&self) -> Address {
@@ -360,7 +463,7 @@ impl Transaction {
 
     pub fn total_value(&self) -> Coin {
         // Avoid wrapping in case this is called before verify().
-        self.value.saturating_add(&self.fee)
+        SaturatingAdd::saturating_add(&self.value, &self.fee)
     }
 }
 
@@ -557,6 +660,8 @@ pub enum TransactionError {
     InvalidForRecipient,
     #[error("Invalid transaction data")]
     InvalidData,
+    #[error("Transaction data too large: {size} bytes")]
+    DataTooLarge { size: usize },
     #[error("Invalid serialization: {0}")]
     InvalidSerialization(#[from] SerializingError),
 }
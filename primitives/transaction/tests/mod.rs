@@ -1,10 +1,11 @@
 use std::convert::{TryFrom, TryInto};
 
 use beserial::{Deserialize, Serialize, SerializingError};
-use nimiq_keys::Address;
+use nimiq_keys::{Address, KeyPair, SecureGenerate};
 use nimiq_primitives::account::AccountType;
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
+use nimiq_primitives::policy;
 use nimiq_test_log::test;
 use nimiq_transaction::*;
 
@@ -84,3 +85,90 @@ fn it_can_serialize_basic_transaction() {
     assert_eq!(size, t.serialized_size());
     assert_eq!(hex::encode(v2), BASIC_TRANSACTION);
 }
+
+#[test]
+fn it_reports_the_first_invalid_signature_in_a_batch() {
+    let key_pair = KeyPair::generate_default_csprng();
+    let sender = Address::from(&key_pair.public);
+
+    let mut txns: Vec<Transaction> = (1..=16)
+        .map(|value| {
+            let mut tx = Transaction::new_basic(
+                sender.clone(),
+                Address::from([1u8; 20]),
+                Coin::from_u64_unchecked(value),
+                Coin::ZERO,
+                1,
+                NetworkId::UnitAlbatross,
+            );
+            let signature = key_pair.sign(&tx.serialize_content());
+            tx.proof = SignatureProof::from(key_pair.public, signature).serialize_to_vec();
+            tx
+        })
+        .collect();
+
+    assert_eq!(Transaction::batch_verify(&txns), Ok(()));
+    assert_eq!(Transaction::batch_verify(&[]), Ok(()));
+    let refs: Vec<&Transaction> = txns.iter().collect();
+    assert_eq!(
+        Transaction::verify_all(&refs, NetworkId::UnitAlbatross),
+        Ok(())
+    );
+    assert_eq!(
+        Transaction::verify_all(&refs, NetworkId::Dummy),
+        Err((0, TransactionError::ForeignNetwork))
+    );
+
+    // A valid signature by someone other than the sender passes the batch, but not the full
+    // verification.
+    let other_key_pair = KeyPair::generate_default_csprng();
+    let mut foreign_tx = txns[3].clone();
+    let signature = other_key_pair.sign(&foreign_tx.serialize_content());
+    foreign_tx.proof = SignatureProof::from(other_key_pair.public, signature).serialize_to_vec();
+    assert_eq!(Transaction::batch_verify([&foreign_tx]), Ok(()));
+    assert_eq!(
+        Transaction::verify_all(&[&txns[0], &foreign_tx], NetworkId::UnitAlbatross),
+        Err((1, TransactionError::InvalidProof))
+    );
+
+    // Invalidate the signature of a single transaction by changing its content.
+    txns[9].fee = Coin::from_u64_unchecked(1);
+    assert_eq!(Transaction::batch_verify(&txns), Err(9));
+
+    // A malformed proof further back doesn't hide the invalid signature.
+    txns[12].proof = vec![0u8; 3];
+    assert_eq!(Transaction::batch_verify(&txns), Err(9));
+    assert_eq!(Transaction::batch_verify(&txns[10..]), Err(2));
+}
+
+#[test]
+fn it_rejects_oversized_data() {
+    let key_pair = KeyPair::generate_default_csprng();
+    let sign = |data: Vec<u8>| {
+        let mut tx = Transaction::new_extended(
+            Address::from(&key_pair.public),
+            AccountType::Basic,
+            Address::from([1u8; 20]),
+            AccountType::Basic,
+            Coin::from_u64_unchecked(100),
+            Coin::ZERO,
+            data,
+            1,
+            NetworkId::UnitAlbatross,
+        );
+        let signature = key_pair.sign(&tx.serialize_content());
+        tx.proof = SignatureProof::from(key_pair.public, signature).serialize_to_vec();
+        tx
+    };
+
+    let tx = sign(vec![0x41; policy::MAX_TX_DATA_SIZE]);
+    assert_eq!(tx.verify(NetworkId::UnitAlbatross), Ok(()));
+
+    let tx = sign(vec![0x41; policy::MAX_TX_DATA_SIZE + 1]);
+    assert_eq!(
+        tx.verify(NetworkId::UnitAlbatross),
+        Err(TransactionError::DataTooLarge {
+            size: policy::MAX_TX_DATA_SIZE + 1
+        })
+    );
+}
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::key_nibbles::KeyNibbles;
+
 /// An enum containing possible errors that can happen in the Merkle Radix Trie.
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum MerkleRadixTrieError {
@@ -11,4 +13,10 @@ pub enum MerkleRadixTrieError {
     BranchesHaveNoValue,
     #[error("Tried to query a child that does not exist.")]
     ChildDoesNotExist,
+    #[error("Node with key {0} is referenced by its parent but is missing from the database.")]
+    DanglingReference(KeyNibbles),
+    #[error("Stored hash for the node with key {0} doesn't match the hash of the node.")]
+    HashMismatch(KeyNibbles),
+    #[error("Node stored at key {0} has a different key.")]
+    KeyMismatch(KeyNibbles),
 }
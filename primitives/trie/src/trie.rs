@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::marker::PhantomData;
 
 use log::error;
@@ -6,10 +7,22 @@ use beserial::{Deserialize, Serialize};
 use nimiq_database::{Database, Environment, Transaction, WriteTransaction};
 use nimiq_hash::{Blake2bHash, Hash};
 
+use crate::error::MerkleRadixTrieError;
 use crate::key_nibbles::KeyNibbles;
 use crate::trie_node::TrieNode;
 use crate::trie_proof::TrieProof;
 
+/// Statistics about the shape of a Merkle Radix Trie, as returned by `MerkleRadixTrie::stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrieStats {
+    /// The number of branch nodes, including the root.
+    pub num_branches: usize,
+    /// The number of leaf nodes.
+    pub num_leaves: usize,
+    /// The length of the longest path from the root to a leaf, in nodes.
+    pub max_depth: usize,
+}
+
 /// A Merkle Radix Trie is a hybrid between a Merkle tree and a Radix trie. Like a Merkle tree each
 /// node contains the hashes of all its children. That creates a tree that is resistant to
 /// unauthorized modification and allows proofs of inclusion and exclusion. Like a Radix trie each
@@ -80,6 +93,76 @@ impl<A: Serialize + Deserialize + Clone> MerkleRadixTrie<A> {
         size
     }
 
+    /// Returns statistics about the shape of the Merkle Radix Trie. It will traverse the entire
+    /// tree.
+    pub fn stats(&self, txn: &Transaction) -> TrieStats {
+        let mut stats = TrieStats::default();
+
+        let mut stack = vec![(
+            self.get_root(txn)
+                .expect("The Merkle Radix Trie didn't have a root node!"),
+            0,
+        )];
+
+        while let Some((item, depth)) = stack.pop() {
+            stats.max_depth = stats.max_depth.max(depth);
+
+            match item {
+                TrieNode::BranchNode { children, key } => {
+                    stats.num_branches += 1;
+
+                    for child in children.iter().flatten() {
+                        let combined = &key + &child.suffix;
+
+                        stack.push((txn.get(&self.db, &combined)
+                                .expect("Failed to find the child of a Merkle Radix Trie node. The database must be corrupt!"), depth + 1));
+                    }
+                }
+                TrieNode::LeafNode { .. } => {
+                    stats.num_leaves += 1;
+                }
+            }
+        }
+
+        stats
+    }
+
+    /// Checks the integrity of the Merkle Radix Trie. It will traverse the entire tree and verify
+    /// that every node referenced by a branch node exists, is stored under its own key and that
+    /// the hash stored in the branch node matches the hash of the child. This is meant as a
+    /// debugging and maintenance tool, it must only be called after the root was updated.
+    pub fn check_integrity(&self, txn: &Transaction) -> Result<(), MerkleRadixTrieError> {
+        let root = KeyNibbles::root();
+
+        let mut stack = vec![self
+            .get_root(txn)
+            .ok_or(MerkleRadixTrieError::DanglingReference(root))?];
+
+        while let Some(item) = stack.pop() {
+            if let TrieNode::BranchNode { children, key } = item {
+                for child in children.iter().flatten() {
+                    let combined = &key + &child.suffix;
+
+                    let child_node: TrieNode<A> = txn
+                        .get(&self.db, &combined)
+                        .ok_or_else(|| MerkleRadixTrieError::DanglingReference(combined.clone()))?;
+
+                    if child_node.key() != &combined {
+                        return Err(MerkleRadixTrieError::KeyMismatch(combined));
+                    }
+
+                    if child_node.hash::<Blake2bHash>() != child.hash {
+                        return Err(MerkleRadixTrieError::HashMismatch(combined));
+                    }
+
+                    stack.push(child_node);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Get the value at the given key. If there's no leaf node at the given key then it returns None.
     pub fn get(&self, txn: &Transaction, key: &KeyNibbles) -> Option<A> {
         let node = txn.get(&self.db, key)?;
@@ -99,6 +182,49 @@ impl<A: Serialize + Deserialize + Clone> MerkleRadixTrie<A> {
         chunk.iter().map(|node| node.value().unwrap()).collect()
     }
 
+    /// Returns all the leaf nodes of the Merkle Radix Trie as key-value pairs, ordered by key. It
+    /// will traverse the entire tree.
+    pub fn get_all(&self, txn: &Transaction) -> Vec<(KeyNibbles, A)> {
+        let chunk = self.get_trie_chunk(txn, &KeyNibbles::root(), usize::MAX);
+
+        chunk
+            .into_iter()
+            .map(|node| (node.key().clone(), node.value().unwrap()))
+            .collect()
+    }
+
+    /// Returns all the leaf nodes whose key starts with the given prefix as key-value pairs, ordered
+    /// by key. Only the part of the tree below the prefix is traversed.
+    pub fn get_with_prefix(&self, txn: &Transaction, prefix: &KeyNibbles) -> Vec<(KeyNibbles, A)> {
+        let mut leaves = Vec::new();
+
+        let mut stack = vec![self
+            .get_root(txn)
+            .expect("The Merkle Radix Trie didn't have a root node!")];
+
+        while let Some(item) = stack.pop() {
+            match item {
+                TrieNode::BranchNode { children, key } => {
+                    for child in children.iter().flatten().rev() {
+                        let combined = &key + &child.suffix;
+
+                        if combined.is_prefix_of(prefix) || prefix.is_prefix_of(&combined) {
+                            stack.push(txn.get(&self.db, &combined)
+                                .expect("Failed to find the child of a Merkle Radix Trie node. The database must be corrupt!"));
+                        }
+                    }
+                }
+                TrieNode::LeafNode { key, value } => {
+                    if prefix.is_prefix_of(&key) {
+                        leaves.push((key, value));
+                    }
+                }
+            }
+        }
+
+        leaves
+    }
+
     /// Insert a value into the Merkle Radix Trie at the given key. If the key already exists then
     /// it will overwrite it. You can't use this function to check the existence of a given key.
     pub fn put(&self, txn: &mut WriteTransaction, key: &KeyNibbles, value: A) {
@@ -401,6 +527,70 @@ impl<A: Serialize + Deserialize + Clone> MerkleRadixTrie<A> {
         Some(TrieProof::new(proof_nodes))
     }
 
+    /// Produces a single Merkle proof for all of the given keys. Unlike `get_proof`, the keys don't
+    /// need to be part of the trie: for a missing key the proof contains the path down to the node
+    /// that shows its absence, which is either a branch node without a matching child or a node
+    /// whose key diverges from the missing key. Nodes that are shared between the paths of several
+    /// keys are only included once, and the nodes are returned in post-order.
+    pub fn get_multi_proof(&self, txn: &Transaction, keys: &[KeyNibbles]) -> TrieProof<A> {
+        let root = self
+            .get_root(txn)
+            .expect("Merkle Radix Trie must have a root node!");
+
+        // Collect the paths of all keys, so that shared nodes are only fetched once.
+        let mut nodes = BTreeMap::new();
+
+        for key in keys {
+            let mut pointer_node = root.clone();
+
+            loop {
+                let child_key = if pointer_node.is_branch()
+                    && pointer_node.key() != key
+                    && pointer_node.key().is_prefix_of(key)
+                {
+                    pointer_node.get_child_key(key).ok()
+                } else {
+                    None
+                };
+
+                nodes.insert(pointer_node.key().clone(), pointer_node);
+
+                match child_key {
+                    Some(child_key) => match nodes.get(&child_key) {
+                        Some(node) => pointer_node = node.clone(),
+                        None => pointer_node = txn.get(&self.db, &child_key).unwrap(),
+                    },
+                    None => break,
+                }
+            }
+        }
+
+        // Order the collected nodes in post-order, starting at the root.
+        let mut proof_nodes = Vec::with_capacity(nodes.len());
+        Self::collect_post_order(&mut nodes, KeyNibbles::root(), &mut proof_nodes);
+
+        TrieProof::new(proof_nodes)
+    }
+
+    /// Moves the node with the given key and all of its descendants from `nodes` into
+    /// `proof_nodes`, in post-order.
+    fn collect_post_order(
+        nodes: &mut BTreeMap<KeyNibbles, TrieNode<A>>,
+        key: KeyNibbles,
+        proof_nodes: &mut Vec<TrieNode<A>>,
+    ) {
+        let node = match nodes.remove(&key) {
+            Some(node) => node,
+            None => return,
+        };
+
+        for child in node.iter_children() {
+            Self::collect_post_order(nodes, node.key() + &child.suffix, proof_nodes);
+        }
+
+        proof_nodes.push(node);
+    }
+
     /// Creates a proof for the chunk of the Merkle Radix Trie that starts at the key `start` (which
     /// might or not be a part of the trie, if it is then it will be part of the chunk) and contains
     /// at most `size` leaf nodes.
@@ -417,8 +607,120 @@ impl<A: Serialize + Deserialize + Clone> MerkleRadixTrie<A> {
         self.get_proof(txn, chunk_keys)
     }
 
-    pub fn update_root(&self, txn: &mut WriteTransaction) {
-        self.update_hashes(txn, &KeyNibbles::root());
+    /// Returns the leaves that differ between the states of the Merkle Radix Trie seen by the two
+    /// given transactions, as `(key, value in txn_a, value in txn_b)` ordered by key. Subtrees with
+    /// the same hash in both states are skipped, so only the changed paths are traversed.
+    pub fn diff(
+        &self,
+        txn_a: &Transaction,
+        txn_b: &Transaction,
+    ) -> Vec<(KeyNibbles, Option<A>, Option<A>)> {
+        let mut diff = Vec::new();
+
+        self.diff_nodes(
+            txn_a,
+            self.get_root(txn_a),
+            txn_b,
+            self.get_root(txn_b),
+            &mut diff,
+        );
+
+        diff
+    }
+
+    /// Appends the leaves that differ between the subtrees of `node_a` and `node_b` to `diff`.
+    fn diff_nodes(
+        &self,
+        txn_a: &Transaction,
+        node_a: Option<TrieNode<A>>,
+        txn_b: &Transaction,
+        node_b: Option<TrieNode<A>>,
+        diff: &mut Vec<(KeyNibbles, Option<A>, Option<A>)>,
+    ) {
+        match (node_a, node_b) {
+            (
+                Some(TrieNode::BranchNode {
+                    key,
+                    children: children_a,
+                }),
+                Some(TrieNode::BranchNode {
+                    key: key_b,
+                    children: children_b,
+                }),
+            ) if key == key_b => {
+                let default_hash = Blake2bHash::default();
+
+                for (child_a, child_b) in children_a.iter().zip(children_b.iter()) {
+                    // Dirty hashes can't be compared, those subtrees are always traversed.
+                    if child_a == child_b
+                        && child_a
+                            .as_ref()
+                            .map_or(true, |child| child.hash != default_hash)
+                    {
+                        continue;
+                    }
+
+                    let child_a = child_a
+                        .as_ref()
+                        .map(|child| txn_a.get(&self.db, &(&key + &child.suffix)).unwrap());
+                    let child_b = child_b
+                        .as_ref()
+                        .map(|child| txn_b.get(&self.db, &(&key + &child.suffix)).unwrap());
+
+                    self.diff_nodes(txn_a, child_a, txn_b, child_b, diff);
+                }
+            }
+            (node_a, node_b) => {
+                // The subtrees are shaped differently, so compare all of their leaves.
+                let mut leaves: BTreeMap<KeyNibbles, (Option<TrieNode<A>>, Option<TrieNode<A>>)> =
+                    BTreeMap::new();
+
+                for leaf in self.get_leaves(txn_a, node_a) {
+                    leaves.entry(leaf.key().clone()).or_default().0 = Some(leaf);
+                }
+                for leaf in self.get_leaves(txn_b, node_b) {
+                    leaves.entry(leaf.key().clone()).or_default().1 = Some(leaf);
+                }
+
+                for (key, (leaf_a, leaf_b)) in leaves {
+                    let hash_a = leaf_a.as_ref().map(|leaf| leaf.hash::<Blake2bHash>());
+                    let hash_b = leaf_b.as_ref().map(|leaf| leaf.hash::<Blake2bHash>());
+
+                    if hash_a != hash_b {
+                        diff.push((
+                            key,
+                            leaf_a.map(|leaf| leaf.value().unwrap()),
+                            leaf_b.map(|leaf| leaf.value().unwrap()),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns all leaf nodes in the subtree of the given node, ordered by key.
+    fn get_leaves(&self, txn: &Transaction, node: Option<TrieNode<A>>) -> Vec<TrieNode<A>> {
+        let mut leaves = Vec::new();
+        let mut stack: Vec<TrieNode<A>> = node.into_iter().collect();
+
+        while let Some(item) = stack.pop() {
+            match item {
+                TrieNode::BranchNode { children, key } => {
+                    for child in children.iter().flatten().rev() {
+                        stack.push(txn.get(&self.db, &(&key + &child.suffix))
+                            .expect("Failed to find the child of a Merkle Radix Trie node. The database must be corrupt!"));
+                    }
+                }
+                TrieNode::LeafNode { .. } => leaves.push(item),
+            }
+        }
+
+        leaves
+    }
+
+    /// Updates the hashes of all dirty nodes and returns the new root hash.
+    pub fn update_root(&self, txn: &mut WriteTransaction) -> Blake2bHash {
+        self.update_hashes(txn, &KeyNibbles::root())
     }
 
     /// Returns the root node, if there is one.
@@ -510,6 +812,65 @@ mod tests {
     use super::*;
     use nimiq_test_log::test;
 
+    #[test]
+    fn only_children_are_merged_with_their_parents() {
+        // Two 40 nibble keys that only differ in the last nibble, plus one unrelated key.
+        let key_1: KeyNibbles = "0000000000000000000000000000000000000001".parse().unwrap();
+        let key_2: KeyNibbles = "0000000000000000000000000000000000000002".parse().unwrap();
+        let key_3: KeyNibbles = "1000000000000000000000000000000000000000".parse().unwrap();
+
+        let env = nimiq_database::volatile::VolatileEnvironment::new(10).unwrap();
+        let trie = MerkleRadixTrie::new(env.clone(), "database");
+        let mut txn = WriteTransaction::new(&env);
+
+        trie.put(&mut txn, &key_1, 1);
+        trie.put(&mut txn, &key_2, 2);
+        trie.put(&mut txn, &key_3, 3);
+
+        // Without path compression there would be a branch node for each of the 39 shared nibbles.
+        // With it, the shared prefix collapses into a single branch node below the root.
+        assert_eq!(
+            trie.stats(&txn),
+            TrieStats {
+                num_branches: 2,
+                num_leaves: 3,
+                max_depth: 2,
+            }
+        );
+        assert_eq!(trie.size(&txn), 3);
+    }
+
+    #[test]
+    fn get_with_prefix_works() {
+        let key_1: KeyNibbles = "413f22b3e".parse().unwrap();
+        let key_2: KeyNibbles = "413b39931".parse().unwrap();
+        let key_3: KeyNibbles = "413b397fa".parse().unwrap();
+        let key_4: KeyNibbles = "cfb986f5a".parse().unwrap();
+
+        let env = nimiq_database::volatile::VolatileEnvironment::new(10).unwrap();
+        let trie = MerkleRadixTrie::new(env.clone(), "database");
+        let mut txn = WriteTransaction::new(&env);
+
+        trie.put(&mut txn, &key_1, 1);
+        trie.put(&mut txn, &key_2, 2);
+        trie.put(&mut txn, &key_3, 3);
+        trie.put(&mut txn, &key_4, 4);
+
+        assert_eq!(
+            trie.get_with_prefix(&txn, &"413b".parse().unwrap()),
+            vec![(key_2.clone(), 2), (key_3.clone(), 3)]
+        );
+        assert_eq!(
+            trie.get_with_prefix(&txn, &"41".parse().unwrap()),
+            vec![(key_2, 2), (key_3, 3), (key_1, 1)]
+        );
+        assert_eq!(trie.get_with_prefix(&txn, &key_4), vec![(key_4.clone(), 4)]);
+        assert!(trie
+            .get_with_prefix(&txn, &"50".parse().unwrap())
+            .is_empty());
+        assert_eq!(trie.get_with_prefix(&txn, &KeyNibbles::root()).len(), 4);
+    }
+
     #[test]
     fn get_put_remove_works() {
         let key_1 = "413f22b3e".parse().unwrap();
@@ -558,6 +919,54 @@ mod tests {
         assert_eq!(trie.get(&txn, &key_3), None);
     }
 
+    #[test]
+    fn check_integrity_works() {
+        let key_1 = "cfb986f5a".parse().unwrap();
+        let key_2 = "cfb986ab9".parse().unwrap();
+        let key_3 = "cfb98e0f6".parse().unwrap();
+
+        let env = nimiq_database::volatile::VolatileEnvironment::new(10).unwrap();
+        let trie = MerkleRadixTrie::new(env.clone(), "database");
+        let mut txn = WriteTransaction::new(&env);
+
+        trie.put(&mut txn, &key_1, 9);
+        trie.put(&mut txn, &key_2, 8);
+        trie.put(&mut txn, &key_3, 7);
+        trie.update_root(&mut txn);
+
+        assert_eq!(trie.check_integrity(&txn), Ok(()));
+
+        // Corrupt the stored hash of the leaf with key 1 in its parent.
+        let parent_key: KeyNibbles = "cfb986".parse().unwrap();
+        let mut parent: TrieNode<i32> = txn.get(&trie.db, &parent_key).unwrap();
+        for child in parent.iter_children_mut() {
+            if &parent_key + &child.suffix == key_1 {
+                child.hash = Blake2bHash::from([1u8; 32]);
+            }
+        }
+        txn.put_reserve(&trie.db, &parent_key, &parent);
+
+        assert_eq!(
+            trie.check_integrity(&txn),
+            Err(MerkleRadixTrieError::HashMismatch(key_1))
+        );
+
+        // Remove a leaf without updating its parent.
+        let env = nimiq_database::volatile::VolatileEnvironment::new(10).unwrap();
+        let trie = MerkleRadixTrie::new(env.clone(), "database");
+        let mut txn = WriteTransaction::new(&env);
+
+        trie.put(&mut txn, &key_2, 8);
+        trie.put(&mut txn, &key_3, 7);
+        trie.update_root(&mut txn);
+        txn.remove(&trie.db, &key_3);
+
+        assert_eq!(
+            trie.check_integrity(&txn),
+            Err(MerkleRadixTrieError::DanglingReference(key_3))
+        );
+    }
+
     #[test]
     fn get_proof_works() {
         let key_1 = "cfb986f5a".parse().unwrap();
@@ -49,6 +49,36 @@ impl<A: Serialize + Deserialize + Clone> TrieProof<A> {
         leaf_nodes
     }
 
+    /// Looks up the given key in the proof, walking down from the root node. Returns `Some(value)`
+    /// if the proof shows that the key is part of the trie, `Some(None)` if it shows that the key
+    /// is absent and `None` if the proof doesn't cover the key at all.
+    /// The result is only meaningful for proofs that passed `verify`.
+    pub fn lookup(&self, key: &KeyNibbles) -> Option<Option<A>> {
+        // The nodes are in post-order, so the root node is always the last one.
+        let mut pointer_node = self.nodes.last()?;
+
+        loop {
+            if pointer_node.key() == key {
+                // Branch nodes don't have values, so the key is absent in that case.
+                return match pointer_node {
+                    TrieNode::LeafNode { value, .. } => Some(Some(value.clone())),
+                    TrieNode::BranchNode { .. } => Some(None),
+                };
+            }
+
+            if pointer_node.is_leaf() || !pointer_node.key().is_prefix_of(key) {
+                return Some(None);
+            }
+
+            let child_key = match pointer_node.get_child_key(key) {
+                Ok(child_key) => child_key,
+                Err(_) => return Some(None),
+            };
+
+            pointer_node = self.nodes.iter().find(|node| node.key() == &child_key)?;
+        }
+    }
+
     /// Verifies a proof against the given root hash. Note that this doesn't check that whatever keys
     /// we want to prove are actually included in the proof. For that we need to call leaf_nodes()
     /// and compare their keys to the ones we want.
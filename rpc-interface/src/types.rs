@@ -325,6 +325,206 @@ impl Block {
     }
 }
 
+/// A view of a `Block` that only contains the sections selected with a `BlockViewBuilder`, e.g. to
+/// return lightweight block summaries. The hash, size, batch, epoch and number of the block are
+/// always included, as is the number of transactions if the block was loaded with them.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockView {
+    pub hash: Blake2bHash,
+    pub size: u32,
+    pub batch: u32,
+    pub epoch: u32,
+    pub number: u32,
+
+    #[serde(flatten)]
+    pub header: Option<BlockHeaderView>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub justification: Option<BlockJustification>,
+
+    #[serde(flatten)]
+    pub body: Option<BlockBodyView>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_count: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<Vec<Transaction>>,
+}
+
+impl BlockView {
+    pub fn builder() -> BlockViewBuilder {
+        BlockViewBuilder::default()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHeaderView {
+    pub version: u16,
+    pub view: u32,
+    pub timestamp: u64,
+    pub parent_hash: Blake2bHash,
+    pub seed: VrfSeed,
+    #[serde(with = "crate::serde_helpers::hex")]
+    pub extra_data: Vec<u8>,
+    pub state_hash: Blake2bHash,
+    pub body_hash: Blake2bHash,
+    pub history_hash: Blake2bHash,
+
+    // Only set for macro blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_election_block: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_election_hash: Option<Blake2bHash>,
+
+    // Only set for micro blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub producer: Option<Slot>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum BlockJustification {
+    Macro(TendermintProof),
+    Micro(MicroJustification),
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum BlockBodyView {
+    #[serde(rename_all = "camelCase")]
+    Macro {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        slots: Option<Vec<Slots>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        lost_reward_set: Option<BitSet>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        disabled_set: Option<BitSet>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Micro {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        fork_proofs: Option<Vec<ForkProof>>,
+    },
+}
+
+/// Selects the sections of a `Block` that are included in a `BlockView`. All sections are
+/// included by default. The transaction list is part of the body, so it is only included if the
+/// body is.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockViewBuilder {
+    header: bool,
+    justification: bool,
+    body: bool,
+    transactions: bool,
+}
+
+impl Default for BlockViewBuilder {
+    fn default() -> Self {
+        Self {
+            header: true,
+            justification: true,
+            body: true,
+            transactions: true,
+        }
+    }
+}
+
+impl BlockViewBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn header(mut self, include: bool) -> Self {
+        self.header = include;
+        self
+    }
+
+    pub fn justification(mut self, include: bool) -> Self {
+        self.justification = include;
+        self
+    }
+
+    pub fn body(mut self, include: bool) -> Self {
+        self.body = include;
+        self
+    }
+
+    pub fn transactions(mut self, include: bool) -> Self {
+        self.transactions = include;
+        self
+    }
+
+    pub fn build(&self, block: Block) -> BlockView {
+        let transaction_count = block.transactions.as_ref().map(Vec::len);
+
+        let (is_election_block, parent_election_hash, producer, justification, body) =
+            match block.additional_fields {
+                BlockAdditionalFields::Macro {
+                    is_election_block,
+                    parent_election_hash,
+                    slots,
+                    lost_reward_set,
+                    disabled_set,
+                    justification,
+                } => (
+                    Some(is_election_block),
+                    Some(parent_election_hash),
+                    None,
+                    justification.map(BlockJustification::Macro),
+                    BlockBodyView::Macro {
+                        slots,
+                        lost_reward_set,
+                        disabled_set,
+                    },
+                ),
+                BlockAdditionalFields::Micro {
+                    producer,
+                    fork_proofs,
+                    justification,
+                } => (
+                    None,
+                    None,
+                    Some(producer),
+                    justification.map(BlockJustification::Micro),
+                    BlockBodyView::Micro { fork_proofs },
+                ),
+            };
+
+        let header = BlockHeaderView {
+            version: block.version,
+            view: block.view,
+            timestamp: block.timestamp,
+            parent_hash: block.parent_hash,
+            seed: block.seed,
+            extra_data: block.extra_data,
+            state_hash: block.state_hash,
+            body_hash: block.body_hash,
+            history_hash: block.history_hash,
+            is_election_block,
+            parent_election_hash,
+            producer,
+        };
+
+        BlockView {
+            hash: block.hash,
+            size: block.size,
+            batch: block.batch,
+            epoch: block.epoch,
+            number: block.number,
+            header: Some(header).filter(|_| self.header),
+            justification: justification.filter(|_| self.justification),
+            body: Some(body).filter(|_| self.body),
+            transaction_count,
+            transactions: block
+                .transactions
+                .filter(|_| self.body && self.transactions),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TendermintProof {
@@ -870,3 +1070,74 @@ impl MempoolInfo {
         info
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nimiq_primitives::networks::NetworkId;
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    fn micro_block() -> Block {
+        let tx = nimiq_transaction::Transaction::new_basic(
+            Address::from([1u8; Address::SIZE]),
+            Address::from([2u8; Address::SIZE]),
+            Coin::from_u64_unchecked(10),
+            Coin::from_u64_unchecked(1),
+            1,
+            NetworkId::UnitAlbatross,
+        );
+
+        Block {
+            hash: Blake2bHash::default(),
+            size: 100,
+            batch: 1,
+            epoch: 1,
+            version: 1,
+            number: 2,
+            view: 0,
+            timestamp: 1000,
+            parent_hash: Blake2bHash::default(),
+            seed: VrfSeed::default(),
+            extra_data: vec![],
+            state_hash: Blake2bHash::default(),
+            body_hash: Blake2bHash::default(),
+            history_hash: Blake2bHash::default(),
+            transactions: Some(vec![Transaction::from_transaction(tx)]),
+            additional_fields: BlockAdditionalFields::Micro {
+                producer: Slot {
+                    slot_number: 3,
+                    validator: Address::from([3u8; Address::SIZE]),
+                    public_key: CompressedPublicKey::default(),
+                },
+                fork_proofs: Some(vec![]),
+                justification: None,
+            },
+        }
+    }
+
+    #[test]
+    fn block_view_can_omit_the_body() {
+        let view = BlockView::builder().body(false).build(micro_block());
+        let json = serde_json::to_value(&view).unwrap();
+
+        assert_eq!(json["number"], 2);
+        assert_eq!(json["timestamp"], 1000);
+        assert_eq!(json["producer"]["slotNumber"], 3);
+        assert_eq!(json["transactionCount"], 1);
+        assert!(json.get("transactions").is_none());
+        assert!(json.get("forkProofs").is_none());
+    }
+
+    #[test]
+    fn block_view_can_omit_the_header() {
+        let view = BlockView::builder().header(false).build(micro_block());
+        let json = serde_json::to_value(&view).unwrap();
+
+        assert_eq!(json["number"], 2);
+        assert!(json.get("timestamp").is_none());
+        assert!(json.get("producer").is_none());
+        assert_eq!(json["forkProofs"], serde_json::json!([]));
+        assert_eq!(json["transactions"].as_array().unwrap().len(), 1);
+    }
+}
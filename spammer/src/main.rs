@@ -546,6 +546,15 @@ fn generate_basic_transactions(
             let recipient = Address::from(&new_kp);
             let amount = Coin::from_u64_unchecked(50);
 
+            // If the sender can't afford the transaction anymore we need to remove it
+            let remaining_balance = match account.balance.checked_sub(amount) {
+                Some(balance) => balance,
+                None => {
+                    state.balances.swap_remove(index);
+                    continue;
+                }
+            };
+
             let tx = TransactionBuilder::new_basic(
                 &account.key_pair,
                 recipient,
@@ -558,7 +567,7 @@ fn generate_basic_transactions(
             txs.push(tx);
 
             //Update the senders balance
-            account.balance -= amount;
+            account.balance = remaining_balance;
             //Create a new recipients account and add it to the vector
             state.balances.push(SpammerAccounts {
                 key_pair: new_kp,
@@ -0,0 +1,543 @@
+use std::collections::{BTreeMap, HashSet};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use nimiq_account::{
+    Account, Accounts, BasicAccount, Inherent, Receipts, StakingContract, VestingContract,
+};
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_database::{
+    Environment, ReadTransaction, Transaction as DBTransaction, WriteTransaction,
+};
+use nimiq_hash::Blake2bHash;
+use nimiq_keys::{Address, KeyPair, SecureGenerate};
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::networks::NetworkId;
+use nimiq_transaction::account::htlc_contract::{AnyHash, HashAlgorithm};
+use nimiq_transaction::Transaction;
+use nimiq_transaction_builder::TransactionBuilder;
+use nimiq_trie::key_nibbles::KeyNibbles;
+
+/// The initial balance of every key of the fuzzer, in Lunas.
+const INITIAL_BALANCE: u64 = 1_000_000_000;
+
+/// The time between two blocks, in milliseconds.
+const BLOCK_TIME: u64 = 1000;
+
+/// The operations the fuzzer generates, one variant per commit path of the accounts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Operation {
+    Transfer,
+    CreateVesting,
+    RedeemVesting,
+    CreateHtlc,
+    TimeoutHtlc,
+    CreateStaker,
+    Stake,
+    Unstake,
+    Reward,
+}
+
+impl Operation {
+    const TRANSACTIONS: [Operation; 8] = [
+        Operation::Transfer,
+        Operation::CreateVesting,
+        Operation::RedeemVesting,
+        Operation::CreateHtlc,
+        Operation::TimeoutHtlc,
+        Operation::CreateStaker,
+        Operation::Stake,
+        Operation::Unstake,
+    ];
+}
+
+/// A block committed by the fuzzer, kept so it can be reverted again.
+struct CommittedBlock {
+    transactions: Vec<Transaction>,
+    inherents: Vec<Inherent>,
+    block_height: u32,
+    timestamp: u64,
+    receipts: Receipts,
+    prev_root: Blake2bHash,
+}
+
+/// The senders, contracts and stakers that were already used in the block that is being
+/// generated. Each of them is used at most once per block, so that the balances the operations
+/// are generated from can't be spent twice.
+#[derive(Default)]
+struct UsedInBlock {
+    senders: HashSet<usize>,
+    contracts: HashSet<Address>,
+    stakers: HashSet<usize>,
+}
+
+/// A randomized commit/revert harness for the accounts. It commits blocks of random, valid
+/// transactions and inherents touching basic accounts, vesting contracts, HTLCs and the staking
+/// contract, then reverts all of them in reverse order and checks that every revert restores the
+/// root the accounts had before the block was committed.
+///
+/// All randomness is derived from the seed, so a failing run can be reproduced from it.
+pub struct AccountsFuzzer {
+    rng: StdRng,
+    env: Environment,
+    accounts: Accounts,
+    key_pairs: Vec<KeyPair>,
+    /// Vesting contracts with the index of their owner's key.
+    vesting_contracts: Vec<(Address, usize)>,
+    /// HTLCs with the index of their sender's key.
+    htlcs: Vec<(Address, usize)>,
+    block_height: u32,
+    timestamp: u64,
+}
+
+impl AccountsFuzzer {
+    /// Creates a fuzzer with `num_keys` funded basic accounts and an empty staking contract.
+    pub fn new(seed: u64, num_keys: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let key_pairs: Vec<KeyPair> = (0..num_keys).map(|_| KeyPair::generate(&mut rng)).collect();
+
+        let env = VolatileEnvironment::new(10).unwrap();
+        let accounts = Accounts::new(env.clone());
+
+        let mut txn = WriteTransaction::new(&env);
+        StakingContract::create(&accounts.tree, &mut txn);
+        accounts.init(
+            &mut txn,
+            key_pairs
+                .iter()
+                .map(|key_pair| {
+                    (
+                        KeyNibbles::from(&Address::from(key_pair)),
+                        Account::Basic(BasicAccount {
+                            balance: Coin::from_u64_unchecked(INITIAL_BALANCE),
+                        }),
+                    )
+                })
+                .collect(),
+        );
+        txn.commit();
+
+        AccountsFuzzer {
+            rng,
+            env,
+            accounts,
+            key_pairs,
+            vesting_contracts: vec![],
+            htlcs: vec![],
+            block_height: 1,
+            timestamp: BLOCK_TIME,
+        }
+    }
+
+    /// Commits `num_blocks` random blocks and reverts them again. Panics if committing or
+    /// reverting fails or if a revert doesn't restore the previous root. Returns how often each
+    /// operation was committed.
+    pub fn run(&mut self, num_blocks: usize) -> BTreeMap<Operation, usize> {
+        let mut counts = BTreeMap::new();
+        let mut committed = Vec::with_capacity(num_blocks);
+
+        for _ in 0..num_blocks {
+            let (transactions, inherents, operations) = self.next_block();
+
+            let prev_root = self.accounts.get_root(None);
+            let mut txn = WriteTransaction::new(&self.env);
+            let receipts = self
+                .accounts
+                .commit(
+                    &mut txn,
+                    &transactions,
+                    &inherents,
+                    self.block_height,
+                    self.timestamp,
+                )
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "Failed to commit block {} with {:?}: {}",
+                        self.block_height, operations, e
+                    )
+                });
+            txn.commit();
+
+            for operation in operations {
+                *counts.entry(operation).or_insert(0) += 1;
+            }
+
+            committed.push(CommittedBlock {
+                transactions,
+                inherents,
+                block_height: self.block_height,
+                timestamp: self.timestamp,
+                receipts,
+                prev_root,
+            });
+
+            self.block_height += 1;
+            self.timestamp += BLOCK_TIME;
+        }
+
+        for block in committed.into_iter().rev() {
+            let mut txn = WriteTransaction::new(&self.env);
+            self.accounts
+                .revert(
+                    &mut txn,
+                    &block.transactions,
+                    &block.inherents,
+                    block.block_height,
+                    block.timestamp,
+                    &block.receipts,
+                )
+                .unwrap_or_else(|e| panic!("Failed to revert block {}: {}", block.block_height, e));
+            txn.commit();
+
+            assert_eq!(
+                self.accounts.get_root(None),
+                block.prev_root,
+                "Reverting block {} didn't restore the previous root",
+                block.block_height
+            );
+        }
+
+        counts
+    }
+
+    /// Generates the transactions and inherents of the next block from the current state.
+    fn next_block(&mut self) -> (Vec<Transaction>, Vec<Inherent>, Vec<Operation>) {
+        let env = self.env.clone();
+        let txn = ReadTransaction::new(&env);
+
+        // Forget the contracts that were drained (and thus pruned).
+        let accounts = &self.accounts;
+        self.vesting_contracts.retain(|(address, _)| {
+            accounts
+                .get(&KeyNibbles::from(address), Some(&txn))
+                .is_some()
+        });
+        self.htlcs.retain(|(address, _)| {
+            accounts
+                .get(&KeyNibbles::from(address), Some(&txn))
+                .is_some()
+        });
+
+        let mut used = UsedInBlock::default();
+        let mut transactions = vec![];
+        let mut operations = vec![];
+        let mut new_vesting_contracts = vec![];
+        let mut new_htlcs = vec![];
+
+        for _ in 0..self.rng.gen_range(1..=4) {
+            let operation =
+                Operation::TRANSACTIONS[self.rng.gen_range(0..Operation::TRANSACTIONS.len())];
+
+            let transaction = match operation {
+                Operation::Transfer => self.transfer(&txn, &mut used),
+                Operation::CreateVesting => {
+                    self.create_vesting(&txn, &mut used).map(|(tx, owner)| {
+                        new_vesting_contracts.push((tx.contract_creation_address(), owner));
+                        tx
+                    })
+                }
+                Operation::RedeemVesting => self.redeem_vesting(&txn, &mut used),
+                Operation::CreateHtlc => self.create_htlc(&txn, &mut used).map(|(tx, sender)| {
+                    new_htlcs.push((tx.contract_creation_address(), sender));
+                    tx
+                }),
+                Operation::TimeoutHtlc => self.timeout_htlc(&txn, &mut used),
+                Operation::CreateStaker => self.create_staker(&txn, &mut used),
+                Operation::Stake => self.stake(&txn, &mut used),
+                Operation::Unstake => self.unstake(&txn, &mut used),
+                Operation::Reward => unreachable!(),
+            };
+
+            if let Some(transaction) = transaction {
+                transactions.push(transaction);
+                operations.push(operation);
+            }
+        }
+
+        let mut inherents = vec![];
+        for _ in 0..self.rng.gen_range(0..=2) {
+            let target = self.random_key();
+            inherents.push(Inherent::reward(
+                Address::from(&self.key_pairs[target]),
+                Coin::from_u64_unchecked(self.rng.gen_range(1..=1000)),
+            ));
+            operations.push(Operation::Reward);
+        }
+
+        self.vesting_contracts.append(&mut new_vesting_contracts);
+        self.htlcs.append(&mut new_htlcs);
+
+        (transactions, inherents, operations)
+    }
+
+    fn transfer(&mut self, txn: &DBTransaction, used: &mut UsedInBlock) -> Option<Transaction> {
+        let (sender, value, fee) = self.spend(txn, used)?;
+
+        let recipient = if self.rng.gen_bool(0.25) {
+            Address::from(self.rng.gen::<[u8; Address::SIZE]>())
+        } else {
+            let recipient = self.random_key();
+            if recipient == sender {
+                return None;
+            }
+            Address::from(&self.key_pairs[recipient])
+        };
+
+        Some(
+            TransactionBuilder::new_basic(
+                &self.key_pairs[sender],
+                recipient,
+                value,
+                fee,
+                self.block_height,
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap(),
+        )
+    }
+
+    fn create_vesting(
+        &mut self,
+        txn: &DBTransaction,
+        used: &mut UsedInBlock,
+    ) -> Option<(Transaction, usize)> {
+        let (sender, value, fee) = self.spend(txn, used)?;
+        let owner = self.random_key();
+
+        let transaction = TransactionBuilder::new_create_vesting(
+            &self.key_pairs[sender],
+            Address::from(&self.key_pairs[owner]),
+            self.timestamp,
+            BLOCK_TIME,
+            self.rng.gen_range(1..=4),
+            value,
+            fee,
+            self.block_height,
+            NetworkId::UnitAlbatross,
+        )
+        .unwrap();
+
+        Some((transaction, owner))
+    }
+
+    fn redeem_vesting(
+        &mut self,
+        txn: &DBTransaction,
+        used: &mut UsedInBlock,
+    ) -> Option<Transaction> {
+        let (address, owner) = self.unused_contract(&self.vesting_contracts.clone(), used)?;
+
+        let contract = match self.accounts.get(&KeyNibbles::from(&address), Some(txn)) {
+            Some(Account::Vesting(contract)) => contract,
+            _ => return None,
+        };
+
+        let (value, fee) = self.split(VestingContract::claimable_at(&contract, self.timestamp))?;
+
+        Some(
+            TransactionBuilder::new_redeem_vesting(
+                &self.key_pairs[owner],
+                address,
+                Address::from(&self.key_pairs[owner]),
+                value,
+                fee,
+                self.block_height,
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap(),
+        )
+    }
+
+    fn create_htlc(
+        &mut self,
+        txn: &DBTransaction,
+        used: &mut UsedInBlock,
+    ) -> Option<(Transaction, usize)> {
+        let (sender, value, fee) = self.spend(txn, used)?;
+        let recipient = self.random_key();
+
+        // The HTLC times out right away, so it can be resolved from the next block on.
+        let transaction = TransactionBuilder::new_create_htlc(
+            &self.key_pairs[sender],
+            Address::from(&self.key_pairs[sender]),
+            Address::from(&self.key_pairs[recipient]),
+            AnyHash::from(self.rng.gen::<[u8; AnyHash::SIZE]>()),
+            1,
+            HashAlgorithm::Blake2b,
+            self.timestamp,
+            value,
+            fee,
+            self.block_height,
+            NetworkId::UnitAlbatross,
+        )
+        .unwrap();
+
+        Some((transaction, sender))
+    }
+
+    fn timeout_htlc(&mut self, txn: &DBTransaction, used: &mut UsedInBlock) -> Option<Transaction> {
+        let (address, sender) = self.unused_contract(&self.htlcs.clone(), used)?;
+        let balance = self.balance(txn, &address);
+
+        // Either drain the contract or redeem a part of it.
+        let (value, fee) = if self.rng.gen_bool(0.5) && balance > Coin::ZERO {
+            (balance, Coin::ZERO)
+        } else {
+            self.split(balance)?
+        };
+
+        Some(
+            TransactionBuilder::new_redeem_htlc_timeout(
+                &self.key_pairs[sender],
+                address,
+                Address::from(&self.key_pairs[sender]),
+                value,
+                fee,
+                self.block_height,
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap(),
+        )
+    }
+
+    fn create_staker(
+        &mut self,
+        txn: &DBTransaction,
+        used: &mut UsedInBlock,
+    ) -> Option<Transaction> {
+        let (sender, value, fee) = self.spend(txn, used)?;
+        if self.staker_balance(txn, sender).is_some() {
+            return None;
+        }
+
+        Some(
+            TransactionBuilder::new_create_staker(
+                &self.key_pairs[sender],
+                &self.key_pairs[sender],
+                None,
+                value,
+                fee,
+                self.block_height,
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap(),
+        )
+    }
+
+    fn stake(&mut self, txn: &DBTransaction, used: &mut UsedInBlock) -> Option<Transaction> {
+        let staker = self.random_key();
+        if used.stakers.contains(&staker) {
+            return None;
+        }
+        self.staker_balance(txn, staker)?;
+        let (sender, value, fee) = self.spend(txn, used)?;
+        used.stakers.insert(staker);
+
+        Some(
+            TransactionBuilder::new_stake(
+                &self.key_pairs[sender],
+                Address::from(&self.key_pairs[staker]),
+                value,
+                fee,
+                self.block_height,
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap(),
+        )
+    }
+
+    fn unstake(&mut self, txn: &DBTransaction, used: &mut UsedInBlock) -> Option<Transaction> {
+        let staker = self.random_key();
+        if used.stakers.contains(&staker) {
+            return None;
+        }
+        let (value, fee) = self.split(self.staker_balance(txn, staker)?)?;
+        used.stakers.insert(staker);
+
+        Some(
+            TransactionBuilder::new_unstake(
+                &self.key_pairs[staker],
+                Address::from(&self.key_pairs[staker]),
+                value,
+                fee,
+                self.block_height,
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap(),
+        )
+    }
+
+    /// Picks an unused sender and the value and fee it spends, which together are at most a tenth
+    /// of its balance.
+    fn spend(
+        &mut self,
+        txn: &DBTransaction,
+        used: &mut UsedInBlock,
+    ) -> Option<(usize, Coin, Coin)> {
+        let sender = self.random_key();
+        if used.senders.contains(&sender) {
+            return None;
+        }
+
+        let balance = self.balance(txn, &Address::from(&self.key_pairs[sender]));
+        let (value, fee) = self.split(balance / 10)?;
+        used.senders.insert(sender);
+
+        Some((sender, value, fee))
+    }
+
+    /// Picks a contract from `contracts` that wasn't used in this block yet.
+    fn unused_contract(
+        &mut self,
+        contracts: &[(Address, usize)],
+        used: &mut UsedInBlock,
+    ) -> Option<(Address, usize)> {
+        if contracts.is_empty() {
+            return None;
+        }
+
+        let (address, key) = contracts[self.rng.gen_range(0..contracts.len())].clone();
+        if !used.contracts.insert(address.clone()) {
+            return None;
+        }
+
+        Some((address, key))
+    }
+
+    /// Splits at most `available` into a non-zero value and a fee.
+    fn split(&mut self, available: Coin) -> Option<(Coin, Coin)> {
+        let available = u64::from(available);
+        if available < 2 {
+            return None;
+        }
+
+        let fee = self.rng.gen_range(0..=1);
+        let value = self.rng.gen_range(1..=available - fee);
+
+        Some((
+            Coin::from_u64_unchecked(value),
+            Coin::from_u64_unchecked(fee),
+        ))
+    }
+
+    fn random_key(&mut self) -> usize {
+        self.rng.gen_range(0..self.key_pairs.len())
+    }
+
+    fn balance(&self, txn: &DBTransaction, address: &Address) -> Coin {
+        self.accounts
+            .get(&KeyNibbles::from(address), Some(txn))
+            .map(|account| account.balance())
+            .unwrap_or(Coin::ZERO)
+    }
+
+    fn staker_balance(&self, txn: &DBTransaction, key: usize) -> Option<Coin> {
+        StakingContract::get_staker(
+            &self.accounts.tree,
+            txn,
+            &Address::from(&self.key_pairs[key]),
+        )
+        .map(|staker| staker.balance)
+    }
+}
@@ -0,0 +1,168 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+
+use nimiq_block::Block;
+use nimiq_block_production::BlockProducer;
+use nimiq_blockchain::{AbstractBlockchain, Blockchain, PushResult};
+use nimiq_database::mdbx::MdbxEnvironment;
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_database::Environment;
+use nimiq_genesis::NetworkId;
+use nimiq_keys::{KeyPair, PrivateKey};
+use nimiq_primitives::policy;
+use nimiq_utils::time::OffsetTime;
+
+use crate::blockchain::{
+    generate_transactions, sign_macro_block, signing_key, voting_key, UNIT_KEY,
+};
+
+/// The kind of database environment the benchmark runs on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BenchmarkEnv {
+    /// An in-memory environment.
+    Volatile,
+    /// An MDBX environment in the output directory of the benchmark.
+    Mdbx,
+}
+
+/// A bounded throughput benchmark of the blockchain. It produces and pushes a given number of
+/// blocks (micro and macro blocks, starting after the genesis block), filling every micro block
+/// with basic transactions.
+#[derive(Clone, Debug)]
+pub struct BlockchainBenchmark {
+    /// The number of transactions per micro block.
+    pub num_transactions: usize,
+    /// The number of blocks to produce.
+    pub num_blocks: u32,
+    /// The database environment to use.
+    pub env: BenchmarkEnv,
+    /// The directory the MDBX environment is created in.
+    pub output_dir: Option<PathBuf>,
+}
+
+/// The results of a benchmark run. Latencies are measured per block, from the start of its
+/// production until it was pushed to the blockchain.
+#[derive(Clone, Debug)]
+pub struct BenchReport {
+    pub num_blocks: u32,
+    pub num_transactions: usize,
+    pub elapsed: Duration,
+    /// Transactions per second.
+    pub tps: f64,
+    pub latency_p50: Duration,
+    pub latency_p90: Duration,
+    pub latency_p99: Duration,
+}
+
+impl BlockchainBenchmark {
+    /// Creates a benchmark on a volatile environment.
+    pub fn new(num_transactions: usize, num_blocks: u32) -> Self {
+        BlockchainBenchmark {
+            num_transactions,
+            num_blocks,
+            env: BenchmarkEnv::Volatile,
+            output_dir: None,
+        }
+    }
+
+    /// Runs the benchmark on an MDBX environment in the given directory instead.
+    #[must_use]
+    pub fn with_mdbx<P: Into<PathBuf>>(mut self, output_dir: P) -> Self {
+        self.env = BenchmarkEnv::Mdbx;
+        self.output_dir = Some(output_dir.into());
+        self
+    }
+
+    fn environment(&self) -> Environment {
+        match self.env {
+            BenchmarkEnv::Volatile => VolatileEnvironment::new(10).unwrap(),
+            BenchmarkEnv::Mdbx => {
+                let output_dir = self
+                    .output_dir
+                    .as_ref()
+                    .expect("MDBX benchmarks need an output directory");
+                MdbxEnvironment::new(output_dir, 1024 * 1024 * 1024 * 1024, 21).unwrap()
+            }
+        }
+    }
+
+    /// Runs the benchmark and returns its report. Panics if any block fails to be pushed.
+    pub fn run(&self) -> BenchReport {
+        let time = Arc::new(OffsetTime::new());
+        let blockchain = Arc::new(RwLock::new(
+            Blockchain::new(self.environment(), NetworkId::UnitAlbatross, time).unwrap(),
+        ));
+        let producer = BlockProducer::new(signing_key(), voting_key());
+        let key_pair = KeyPair::from(PrivateKey::from_str(UNIT_KEY).unwrap());
+
+        let mut latencies = Vec::with_capacity(self.num_blocks as usize);
+        let mut num_transactions = 0;
+        let start = Instant::now();
+
+        for _ in 0..self.num_blocks {
+            let block_start = Instant::now();
+            let blockchain = blockchain.upgradable_read();
+            let block_number = blockchain.block_number() + 1;
+            let timestamp = blockchain.time.now() + block_number as u64 * 1000;
+
+            let block = if policy::is_macro_block_at(block_number) {
+                let proposal =
+                    producer.next_macro_block_proposal(&blockchain, timestamp, 0u32, vec![]);
+                Block::Macro(sign_macro_block(
+                    &producer.voting_key,
+                    proposal.header,
+                    proposal.body,
+                ))
+            } else {
+                let txns = generate_transactions(
+                    &key_pair,
+                    block_number,
+                    NetworkId::UnitAlbatross,
+                    self.num_transactions,
+                    block_number as u64,
+                );
+                num_transactions += txns.len();
+                Block::Micro(producer.next_micro_block(
+                    &blockchain,
+                    timestamp,
+                    0,
+                    None,
+                    vec![],
+                    txns,
+                    vec![0x42],
+                ))
+            };
+
+            assert_eq!(
+                Blockchain::push(blockchain, block),
+                Ok(PushResult::Extended)
+            );
+            latencies.push(block_start.elapsed());
+        }
+
+        let elapsed = start.elapsed();
+        latencies.sort();
+
+        BenchReport {
+            num_blocks: self.num_blocks,
+            num_transactions,
+            elapsed,
+            tps: num_transactions as f64 / elapsed.as_secs_f64(),
+            latency_p50: percentile(&latencies, 50),
+            latency_p90: percentile(&latencies, 90),
+            latency_p99: percentile(&latencies, 99),
+        }
+    }
+}
+
+/// Returns the given percentile of the sorted latencies.
+fn percentile(latencies: &[Duration], percentile: usize) -> Duration {
+    if latencies.is_empty() {
+        return Duration::ZERO;
+    }
+    latencies[(latencies.len() - 1) * percentile / 100]
+}
@@ -7,8 +7,8 @@ use rand::{prelude::StdRng, RngCore, SeedableRng};
 
 use beserial::Deserialize;
 use nimiq_block::{
-    Block, MacroBlock, MacroBody, MacroHeader, MultiSignature, SignedViewChange,
-    TendermintIdentifier, TendermintProof, TendermintStep, TendermintVote, ViewChange,
+    view_change_message, Block, MacroBlock, MacroBody, MacroHeader, MultiSignature,
+    SignedViewChange, TendermintIdentifier, TendermintProof, TendermintStep, TendermintVote,
     ViewChangeProof,
 };
 use nimiq_block_production::BlockProducer;
@@ -274,11 +274,7 @@ pub fn sign_view_change(
     let keypair = voting_key();
 
     // Create the view change.
-    let view_change = ViewChange {
-        block_number,
-        new_view_number,
-        vrf_entropy: prev_seed.entropy(),
-    };
+    let view_change = view_change_message(block_number, new_view_number, &prev_seed);
 
     // Sign the view change.
     let signed_view_change =
@@ -1,3 +1,5 @@
+pub mod accounts_fuzzer;
+pub mod benchmark;
 pub mod blockchain;
 pub mod consensus;
 pub mod node;
@@ -19,7 +19,7 @@ use transaction::{SignatureProof, Transaction};
 pub use crate::proof::TransactionProofBuilder;
 pub use crate::recipient::Recipient;
 use hash::Blake2bHash;
-use primitives::policy::{STAKING_CONTRACT_ADDRESS, VALIDATOR_DEPOSIT};
+use primitives::policy::{STAKING_CONTRACT_ADDRESS, TOTAL_SUPPLY, VALIDATOR_DEPOSIT};
 use transaction::account::htlc_contract::{AnyHash, HashAlgorithm};
 
 pub mod proof;
@@ -79,6 +79,10 @@ pub enum TransactionBuilderError {
     /// [`signalling transaction`]: struct.TransactionBuilder.html#method.with_value
     #[error("The value must be zero for signalling transactions and cannot be zero for others.")]
     InvalidValue,
+    /// The sum of the value and the fee exceeds the total supply, so the transaction could never
+    /// be valid.
+    #[error("The transaction's value plus fee exceeds the total supply.")]
+    ValueOverflow,
 }
 
 /// A helper to build arbitrary transactions.
@@ -435,6 +439,12 @@ impl TransactionBuilder {
             return Err(TransactionBuilderError::InvalidValue);
         }
 
+        let fee = self.fee.unwrap_or(Coin::ZERO);
+        match value.checked_add(fee) {
+            Some(total) if u64::from(total) <= TOTAL_SUPPLY => {}
+            _ => return Err(TransactionBuilderError::ValueOverflow),
+        }
+
         // Currently, the flags for creation & signalling can never occur at the same time.
         let tx = if recipient.is_creation() {
             Transaction::new_contract_creation(
@@ -443,7 +453,7 @@ impl TransactionBuilder {
                 self.sender_type.unwrap_or(AccountType::Basic),
                 recipient.account_type(),
                 value,
-                self.fee.unwrap_or(Coin::ZERO),
+                fee,
                 validity_start_height,
                 network_id,
             )
@@ -454,7 +464,7 @@ impl TransactionBuilder {
                 recipient.address().unwrap(), // For non-creation recipients, this should never return None.
                 recipient.account_type(),
                 value,
-                self.fee.unwrap_or(Coin::ZERO),
+                fee,
                 recipient.data(),
                 validity_start_height,
                 network_id,
@@ -466,7 +476,7 @@ impl TransactionBuilder {
                 recipient.address().unwrap(), // For non-creation recipients, this should never return None.
                 recipient.account_type(),
                 value,
-                self.fee.unwrap_or(Coin::ZERO),
+                fee,
                 recipient.data(),
                 validity_start_height,
                 network_id,
@@ -1288,6 +1298,50 @@ impl TransactionBuilder {
         }
     }
 
+    /// Creates a transaction that rotates the voting key of a validator. This is an update
+    /// validator transaction that only changes the voting key.
+    ///
+    /// # Arguments
+    ///
+    ///  - `key_pair`:                 The key pair used to sign the transaction. The transaction
+    ///                                fee is taken from the account belonging to this key pair.
+    ///  - `cold_key_pair`:            The key pair that corresponds to the validator address. The
+    ///                                data is signed using this key pair, which authorizes the
+    ///                                new voting key.
+    ///  - `new_voting_key_pair`:      The new validator BLS key pair used by the validator.
+    ///  - `fee`:                      Transaction fee.
+    ///  - `validity_start_height`:    Block height from which this transaction is valid.
+    ///  - `network_id`:               ID of network for which the transaction is valid.
+    ///
+    /// # Returns
+    ///
+    /// The finalized transaction.
+    ///
+    /// # Note
+    ///
+    /// This is a *signalling transaction*.
+    ///
+    pub fn new_update_validator_key(
+        key_pair: &KeyPair,
+        cold_key_pair: &KeyPair,
+        new_voting_key_pair: &BlsKeyPair,
+        fee: Coin,
+        validity_start_height: u32,
+        network_id: NetworkId,
+    ) -> Result<Transaction, TransactionBuilderError> {
+        Self::new_update_validator(
+            key_pair,
+            cold_key_pair,
+            None,
+            Some(new_voting_key_pair),
+            None,
+            None,
+            fee,
+            validity_start_height,
+            network_id,
+        )
+    }
+
     /// Creates a transaction that inactivates a validator.
     ///
     /// # Arguments
@@ -0,0 +1,69 @@
+use nimiq_keys::Address;
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::networks::NetworkId;
+use nimiq_primitives::policy::{TOTAL_SUPPLY, TRANSACTION_VALIDITY_WINDOW};
+use nimiq_test_log::test;
+use nimiq_transaction_builder::{Recipient, TransactionBuilder, TransactionBuilderError};
+
+fn basic_builder(validity_start_height: u32) -> TransactionBuilder {
+    let mut builder = TransactionBuilder::new();
+    builder
+        .with_sender(Address::from([1u8; 20]))
+        .with_recipient(Recipient::new_basic(Address::from([2u8; 20])))
+        .with_value(Coin::from_u64_unchecked(100))
+        .with_validity_start_height(validity_start_height)
+        .with_network_id(NetworkId::Dummy);
+    builder
+}
+
+#[test]
+fn it_builds_transactions_with_the_policy_validity_window() {
+    let start_height = 10_000;
+
+    let tx = basic_builder(start_height)
+        .generate()
+        .unwrap()
+        .preliminary_transaction()
+        .clone();
+    assert!(tx.is_valid_at(start_height));
+    assert!(tx.is_valid_at(start_height + TRANSACTION_VALIDITY_WINDOW - 1));
+    assert!(!tx.is_expired_at(start_height + TRANSACTION_VALIDITY_WINDOW - 1));
+    assert!(!tx.is_valid_at(start_height + TRANSACTION_VALIDITY_WINDOW));
+    assert!(tx.is_expired_at(start_height + TRANSACTION_VALIDITY_WINDOW));
+
+    // A window that ends after the last block height doesn't expire.
+    let tx = basic_builder(u32::MAX)
+        .generate()
+        .unwrap()
+        .preliminary_transaction()
+        .clone();
+    assert!(tx.is_valid_at(u32::MAX));
+    assert!(!tx.is_expired_at(u32::MAX));
+}
+
+#[test]
+fn it_rejects_invalid_values() {
+    // Non-signalling transactions can't have a zero value.
+    let mut builder = basic_builder(1);
+    builder.with_value(Coin::ZERO);
+    assert!(matches!(
+        builder.generate(),
+        Err(TransactionBuilderError::InvalidValue)
+    ));
+
+    // The value plus the fee can't exceed the total supply.
+    let mut builder = basic_builder(1);
+    builder
+        .with_value(Coin::from_u64_unchecked(TOTAL_SUPPLY))
+        .with_fee(Coin::from_u64_unchecked(1));
+    assert!(matches!(
+        builder.generate(),
+        Err(TransactionBuilderError::ValueOverflow)
+    ));
+
+    let mut builder = basic_builder(1);
+    builder
+        .with_value(Coin::from_u64_unchecked(TOTAL_SUPPLY - 1))
+        .with_fee(Coin::from_u64_unchecked(1));
+    assert!(builder.generate().is_ok());
+}
@@ -1,6 +1,7 @@
 extern crate beserial;
 extern crate rand;
 
+mod basic;
 mod htlc_contract;
 mod staking_contract;
 mod vesting_contract;
@@ -215,6 +215,32 @@ fn it_can_create_validator_transactions() {
 
     assert_eq!(tx, tx2);
 
+    // Update voting key
+    let tx = make_signed_incoming_transaction(
+        IncomingStakingTransactionData::UpdateValidator {
+            new_signing_key: None,
+            new_voting_key: Some(bls_pair.public_key.compress()),
+            new_proof_of_knowledge: Some(bls_pair.sign(&bls_pair.public_key).compress()),
+            new_reward_address: None,
+            new_signal_data: None,
+            proof: Default::default(),
+        },
+        0,
+        &key_pair,
+    );
+
+    let tx2 = TransactionBuilder::new_update_validator_key(
+        &key_pair,
+        &key_pair,
+        &bls_pair,
+        100.try_into().unwrap(),
+        1,
+        NetworkId::Dummy,
+    )
+    .unwrap();
+
+    assert_eq!(tx, tx2);
+
     // Inactivate
     let tx = make_signed_incoming_transaction(
         IncomingStakingTransactionData::InactivateValidator {
@@ -1,12 +1,16 @@
+use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::time::Duration;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use atomic::{Atomic, Ordering};
 
-/// Time with fixed offset from wall-clock, in milliseconds
+/// Time with fixed offset from wall-clock, in milliseconds. It can also be pinned to a fixed time,
+/// which makes it fully controllable, e.g. in tests.
 #[derive(Debug, Default)]
 pub struct OffsetTime {
     offset: Atomic<i64>,
+    is_fixed: AtomicBool,
+    fixed: AtomicU64,
 }
 
 impl OffsetTime {
@@ -17,6 +21,18 @@ impl OffsetTime {
     pub fn with_offset(offset: i64) -> Self {
         OffsetTime {
             offset: Atomic::new(offset),
+            is_fixed: AtomicBool::new(false),
+            fixed: AtomicU64::new(0),
+        }
+    }
+
+    /// Creates a time that is pinned to the given timestamp (in milliseconds) instead of following
+    /// the wall-clock. It only changes when it is advanced.
+    pub fn with_fixed(timestamp: u64) -> Self {
+        OffsetTime {
+            offset: Atomic::new(0),
+            is_fixed: AtomicBool::new(true),
+            fixed: AtomicU64::new(timestamp),
         }
     }
 
@@ -24,7 +40,21 @@ impl OffsetTime {
         self.offset.store(new_offset, Ordering::Relaxed);
     }
 
+    /// Moves the time forward by `delta` milliseconds. For a time following the wall-clock, this
+    /// increases its offset.
+    pub fn advance(&self, delta: u64) {
+        if self.is_fixed.load(Ordering::Relaxed) {
+            self.fixed.fetch_add(delta, Ordering::Relaxed);
+        } else {
+            self.offset.fetch_add(delta as i64, Ordering::Relaxed);
+        }
+    }
+
     pub fn now(&self) -> u64 {
+        if self.is_fixed.load(Ordering::Relaxed) {
+            return self.fixed.load(Ordering::Relaxed);
+        }
+
         let offset = self.offset.load(Ordering::Relaxed);
         let abs_offset = offset.abs() as u64;
         let system_time = if offset > 0 {
@@ -16,5 +16,7 @@ pub mod otp;
 pub mod rate_limit;
 #[cfg(feature = "throttled-queue")]
 pub mod throttled_queue;
+#[cfg(feature = "time")]
+pub mod time;
 #[cfg(feature = "unique-id")]
 pub mod unique_id;
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+use std::thread;
+
+use nimiq_test_log::test;
+use nimiq_utils::time::OffsetTime;
+
+#[test]
+fn it_can_pin_and_advance_the_time() {
+    let time = OffsetTime::with_fixed(1_000);
+    assert_eq!(time.now(), 1_000);
+    assert_eq!(time.now(), 1_000);
+
+    time.advance(250);
+    assert_eq!(time.now(), 1_250);
+
+    // The offset doesn't apply to a pinned time.
+    time.set_offset(10_000);
+    assert_eq!(time.now(), 1_250);
+}
+
+#[test]
+fn it_doesnt_lose_concurrent_advances() {
+    let time = Arc::new(OffsetTime::with_fixed(0));
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let time = Arc::clone(&time);
+            thread::spawn(move || {
+                for _ in 0..1_000 {
+                    time.advance(1);
+                }
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(time.now(), 4_000);
+}
+
+#[test]
+fn it_can_advance_the_wall_clock_time() {
+    let time = OffsetTime::new();
+    let before = time.now();
+
+    time.advance(60_000);
+    assert!(time.now() >= before + 60_000);
+}
@@ -8,7 +8,7 @@ use futures::{ready, FutureExt, Stream};
 use parking_lot::RwLock;
 use tokio::time;
 
-use block::{Block, ForkProof, MicroBlock, ViewChange, ViewChangeProof};
+use block::{view_change_message, Block, ForkProof, MicroBlock, ViewChange, ViewChangeProof};
 use block_production::BlockProducer;
 use blockchain::{AbstractBlockchain, Blockchain, PushResult};
 use mempool::mempool::Mempool;
@@ -216,17 +216,13 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
             systemtime_to_timestamp(SystemTime::now()),
         );
 
-        let transactions = self
-            .mempool
-            .get_transactions_for_block(MicroBlock::get_available_bytes(self.fork_proofs.len()));
-
-        self.block_producer.next_micro_block(
+        self.block_producer.next_micro_block_from_mempool(
             blockchain,
+            &self.mempool,
             timestamp,
             self.view_number,
             self.view_change_proof.clone(),
             self.fork_proofs.clone(),
-            transactions,
             vec![], // TODO: Allow validators to set extra data field.
         )
     }
@@ -236,11 +232,7 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
         active_validators: Validators,
     ) -> (ViewChange, ViewChangeProof) {
         let new_view_number = self.view_number + 1;
-        let view_change = ViewChange {
-            block_number: self.block_number,
-            new_view_number,
-            vrf_entropy: self.prev_seed.entropy(),
-        };
+        let view_change = view_change_message(self.block_number, new_view_number, &self.prev_seed);
 
         // Include the previous_view_change_proof only if it has not yet been persisted on chain.
         let view_change_proof = self.view_change.as_ref().and_then(|vc| {
@@ -2,6 +2,7 @@ use std::collections::HashSet;
 
 use beserial::Serialize;
 use block::{Block, ForkProof, MacroBlock, MacroHeader, MicroBlock};
+use primitives::policy;
 
 #[derive(Default)]
 pub struct ForkProofPool {
@@ -56,11 +57,14 @@ impl ForkProofPool {
         }
     }
 
-    /// Returns a list of current fork proofs.
+    /// Returns a list of current fork proofs, limited to `policy::MAX_FORK_PROOFS_PER_BLOCK`.
     pub fn get_fork_proofs_for_block(&self, max_size: usize) -> Vec<ForkProof> {
         let mut proofs = Vec::new();
         let mut size = 0;
         for proof in self.fork_proofs.iter() {
+            if proofs.len() >= policy::MAX_FORK_PROOFS_PER_BLOCK {
+                break;
+            }
             if size + proof.serialized_size() < max_size {
                 proofs.push(proof.clone());
                 size += proof.serialized_size();
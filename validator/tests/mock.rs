@@ -1,7 +1,7 @@
 use futures::{future, StreamExt};
 use tokio::time;
 
-use nimiq_block::{MultiSignature, SignedViewChange, ViewChange};
+use nimiq_block::{view_change_message, MultiSignature, SignedViewChange, ViewChange};
 use nimiq_blockchain::{AbstractBlockchain, BlockchainEvent};
 use nimiq_bls::{AggregateSignature, KeyPair as BlsKeyPair};
 use nimiq_collections::BitSet;
@@ -130,11 +130,7 @@ fn create_view_change_update(
     slots: &Vec<u16>,
 ) -> LevelUpdateMessage<SignedViewChangeMessage, ViewChange> {
     // create view change according to parameters
-    let view_change = ViewChange {
-        block_number,
-        new_view_number,
-        vrf_entropy: prev_seed.entropy(),
-    };
+    let view_change = view_change_message(block_number, new_view_number, &prev_seed);
 
     // get a single signature for this view_change
     let signed_view_change =
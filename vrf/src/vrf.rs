@@ -12,6 +12,7 @@ use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::IsIdentity;
 use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
 #[cfg(feature = "serde-derive")]
 use serde_big_array::BigArray;
 use sha2::{Digest, Sha256, Sha512};
@@ -216,6 +217,24 @@ impl VrfSeed {
         // Pass the entropy to the VRF RNG.
         VrfRng::new(entropy, use_case)
     }
+
+    // Initializes a general purpose CSPRNG from the current VRF Seed and a domain-separating
+    // context. The same seed and context always produce the same stream, while different contexts
+    // produce independent streams. This is meant for components outside of the consensus (e.g.
+    // lotteries) that need randomness derived from the chain. It must not be used for anything the
+    // consensus already derives from the seed (slot selection, reward distribution), use `rng`
+    // with the corresponding `VrfUseCase` for that. We assume that the VRF Seed is valid, if it is
+    // not this function might panic.
+    pub fn context_rng(&self, context: &[u8]) -> impl RngCore {
+        // Hash the context, prefixed with its length, together with the entropy to get the seed.
+        let mut hasher = Blake2bHasher::new();
+        hasher.write_u32::<BigEndian>(context.len() as u32).unwrap();
+        hasher.write_all(context).unwrap();
+        hasher.write_all(self.entropy().as_slice()).unwrap();
+        let seed: Blake2bHash = hasher.finish();
+
+        ChaCha20Rng::from_seed(seed.into())
+    }
 }
 
 impl Default for VrfSeed {
@@ -370,4 +389,20 @@ mod tests {
             assert!(fake_seed.verify(&prev_seed, &key_pair.public).is_err());
         }
     }
+
+    #[test]
+    fn context_rng_is_deterministic_and_domain_separated() {
+        let key_pair = KeyPair::generate(&mut rand::thread_rng());
+        let seed = VrfSeed::default().sign_next(&key_pair);
+
+        let mut bytes_a = [0u8; 64];
+        let mut bytes_b = [0u8; 64];
+        seed.context_rng(b"lottery").fill_bytes(&mut bytes_a);
+        seed.context_rng(b"lottery").fill_bytes(&mut bytes_b);
+        assert_eq!(bytes_a, bytes_b);
+
+        let mut bytes_c = [0u8; 64];
+        seed.context_rng(b"beacon").fill_bytes(&mut bytes_c);
+        assert_ne!(bytes_a, bytes_c);
+    }
 }